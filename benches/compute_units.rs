@@ -0,0 +1,416 @@
+// Compute-unit benchmarks for the journal program (project-1) and journal_pda_optimized (project-3).
+//
+// Build both programs first (`anchor build` in each project), then run `cargo bench` from this directory.
+// Every instruction is sent once through LiteSVM and its compute units are compared with compute-units.json.
+// The run fails if any instruction uses more than CU_TOLERANCE_PERCENT (default 5) over its baseline.
+// After an intended change, rerun with UPDATE_CU_BASELINE=1 and commit the rewritten baseline with it.
+//
+// Instructions that need other programs deployed (token, Metaplex, Bubblegum, Pyth, Switchboard) or
+// multi-step setup (sessions, recovery, relayed writes) aren't covered yet.
+
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+
+use anchor_lang::{AnchorSerialize, Discriminator, InstructionData, Space, ToAccountMetas};
+use litesvm::LiteSVM;
+use solana_loader_v3_interface::get_program_data_address;
+use solana_loader_v3_interface::state::UpgradeableLoaderState;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use solana_sdk_ids::{bpf_loader_upgradeable, system_program, sysvar};
+
+const JOURNAL_SO: &str = "../project-1/anchor/target/deploy/journal.so";
+const NEW_CRUD_APP_SO: &str = "../project-3-pdas/target/deploy/new_crud_app.so";
+const BASELINE_PATH: &str = "compute-units.json";
+const DEFAULT_TOLERANCE_PERCENT: u64 = 5;
+
+// Worst case for the default locale: titles and messages at their character limits
+const TITLE_CHARS: usize = 50;
+const MESSAGE_CHARS: usize = 280;
+
+struct Bench {
+    svm: LiteSVM,
+    payer: Keypair,
+    results: BTreeMap<String, u64>,
+}
+
+impl Bench {
+    fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 100_000_000_000).expect("airdrop");
+        Self { svm, payer, results: BTreeMap::new() }
+    }
+
+    // Sends `instruction` signed by the payer, who is also the authority of everything created here
+    fn measure(&mut self, name: &str, instruction: Instruction) {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.svm.latest_blockhash(),
+        );
+        match self.svm.send_transaction(transaction) {
+            Ok(meta) => {
+                self.results.insert(name.to_string(), meta.compute_units_consumed);
+            }
+            Err(failed) => panic!("{name} failed: {:?}\n{}", failed.err, failed.meta.logs.join("\n")),
+        }
+        // Identical transactions later in the run would otherwise be rejected as duplicates
+        self.svm.expire_blockhash();
+    }
+
+    fn set_account(&mut self, address: Pubkey, owner: Pubkey, data: Vec<u8>, executable: bool) {
+        let account = Account {
+            lamports: self.svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner,
+            executable,
+            rent_epoch: 0,
+        };
+        self.svm.set_account(address, account).expect("set_account");
+    }
+
+    // initialize_config only accepts the upgrade authority, so the journal is deployed behind the upgradeable
+    // loader with the payer as authority instead of through LiteSVM::add_program
+    fn deploy_upgradeable(&mut self, program_id: Pubkey, path: &str) {
+        let elf = read_program(path);
+        let programdata_address = get_program_data_address(&program_id);
+        let mut programdata = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(self.payer.pubkey()),
+        })
+        .unwrap();
+        programdata.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+        programdata.extend_from_slice(&elf);
+        let program = bincode::serialize(&UpgradeableLoaderState::Program { programdata_address }).unwrap();
+        self.set_account(programdata_address, bpf_loader_upgradeable::id(), programdata, false);
+        self.set_account(program_id, bpf_loader_upgradeable::id(), program, true);
+    }
+}
+
+fn read_program(path: &str) -> Vec<u8> {
+    std::fs::read(path).unwrap_or_else(|err| panic!("reading {path}: {err} (run `anchor build` first)"))
+}
+
+fn pda(seeds: &[&[u8]], program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(seeds, program_id).0
+}
+
+fn instruction(program_id: Pubkey, accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction { program_id, accounts: accounts.to_account_metas(None), data: data.data() }
+}
+
+fn bench_journal(bench: &mut Bench) {
+    use journal::{accounts, instruction as ix};
+
+    let program_id = journal::ID;
+    bench.deploy_upgradeable(program_id, JOURNAL_SO);
+    let authority = bench.payer.pubkey();
+    let program_config = pda(&[b"program_config"], &program_id);
+    let treasury = pda(&[b"treasury"], &program_id);
+    let feed_head = pda(&[b"feed_head"], &program_id);
+    let user_profile = pda(&[b"user_profile", authority.as_ref()], &program_id);
+    let journal_entry = pda(&[b"journal_entry", authority.as_ref(), &0u64.to_le_bytes()], &program_id);
+    let zero_copy_entry = pda(&[b"zero_copy_entry", authority.as_ref(), &1u64.to_le_bytes()], &program_id);
+    let title = "t".repeat(TITLE_CHARS);
+    let message = "m".repeat(MESSAGE_CHARS);
+
+    bench.measure(
+        "journal.initialize_config",
+        instruction(
+            program_id,
+            accounts::InitializeConfig {
+                program_config,
+                treasury,
+                program: program_id,
+                program_data: get_program_data_address(&program_id),
+                admin: authority,
+                system_program: system_program::ID,
+            },
+            ix::InitializeConfig {
+                fee_lamports: 0,
+                max_entries_per_day: 0,
+                entry_deposit_lamports: 0,
+                deposit_cooldown_seconds: 0,
+            },
+        ),
+    );
+    bench.measure(
+        "journal.initialize_feed_head",
+        instruction(
+            program_id,
+            accounts::InitializeFeedHead { program_config, feed_head, admin: authority, system_program: system_program::ID },
+            ix::InitializeFeedHead {},
+        ),
+    );
+    bench.measure(
+        "journal.initialize_user_profile",
+        instruction(
+            program_id,
+            accounts::InitializeUserProfile {
+                program_config,
+                user_profile,
+                authority,
+                payer: authority,
+                system_program: system_program::ID,
+            },
+            ix::InitializeUserProfile {},
+        ),
+    );
+    bench.measure(
+        "journal.add_journal_entry",
+        instruction(
+            program_id,
+            accounts::AddJournalEntry {
+                user_profile,
+                journal_entry,
+                program_config,
+                treasury,
+                authority,
+                signer: authority,
+                session_key: None,
+                gate_token_account: None,
+                title_claim: None,
+                price_update: None,
+                daily_prompt: None,
+                journal: None,
+                feed_head: None,
+                reply_parent: None,
+                memo_program: None,
+                instructions_sysvar: sysvar::instructions::ID,
+                payer: authority,
+                system_program: system_program::ID,
+            },
+            ix::AddJournalEntry { title: title.clone(), message: message.clone() },
+        ),
+    );
+    bench.measure(
+        "journal.update_journal_entry",
+        instruction(
+            program_id,
+            accounts::UpdateJournalEntry {
+                program_config,
+                user_profile,
+                journal_entry,
+                authority,
+                signer: authority,
+                session_key: None,
+                memo_program: None,
+                system_program: system_program::ID,
+            },
+            ix::UpdateJournalEntry { _entry_id: 0, title: title.clone(), message: message.to_uppercase() },
+        ),
+    );
+    bench.measure(
+        "journal.set_entry_visibility",
+        instruction(
+            program_id,
+            accounts::SetEntryVisibility {
+                program_config,
+                journal_entry,
+                feed_head,
+                authority,
+                signer: authority,
+                session_key: None,
+            },
+            ix::SetEntryVisibility { _entry_id: 0, is_public: true },
+        ),
+    );
+
+    let zero_copy_accounts = || accounts::ModifyZeroCopyEntry {
+        program_config,
+        user_profile,
+        zero_copy_entry,
+        authority,
+        system_program: system_program::ID,
+    };
+    bench.measure(
+        "journal.add_zero_copy_entry",
+        instruction(
+            program_id,
+            accounts::AddZeroCopyEntry {
+                program_config,
+                user_profile,
+                zero_copy_entry,
+                authority,
+                system_program: system_program::ID,
+            },
+            ix::AddZeroCopyEntry { title: title.clone(), message: message.clone() },
+        ),
+    );
+    bench.measure(
+        "journal.update_zero_copy_entry",
+        instruction(
+            program_id,
+            zero_copy_accounts(),
+            ix::UpdateZeroCopyEntry { _entry_id: 1, title: title.clone(), message: message.to_uppercase() },
+        ),
+    );
+    bench.measure(
+        "journal.patch_zero_copy_message",
+        instruction(
+            program_id,
+            zero_copy_accounts(),
+            ix::PatchZeroCopyMessage { _entry_id: 1, offset: 0, bytes: b"patched".to_vec() },
+        ),
+    );
+    bench.measure(
+        "journal.delete_zero_copy_entry",
+        instruction(
+            program_id,
+            accounts::DeleteZeroCopyEntry {
+                program_config,
+                user_profile,
+                zero_copy_entry,
+                authority,
+                system_program: system_program::ID,
+            },
+            ix::DeleteZeroCopyEntry { _entry_id: 1 },
+        ),
+    );
+    bench.measure(
+        "journal.delete_journal_entry",
+        instruction(
+            program_id,
+            accounts::DeleteJournalEntry {
+                program_config,
+                user_profile,
+                journal_entry,
+                rent_recipient: authority,
+                authority,
+                signer: authority,
+                session_key: None,
+                title_claim: None,
+                system_program: system_program::ID,
+            },
+            ix::DeleteJournalEntry { _entry_id: 0 },
+        ),
+    );
+}
+
+fn bench_new_crud_app(bench: &mut Bench) {
+    use new_crud_app::{accounts, instruction as ix, JournalEntryState, JournalEntryStateV0};
+
+    let program_id = new_crud_app::ID;
+    bench.svm.add_program(program_id, &read_program(NEW_CRUD_APP_SO));
+    let owner = bench.payer.pubkey();
+    let user_journal_counter = pda(&[b"counter", owner.as_ref()], &program_id);
+    let journal_entry = pda(&[owner.as_ref(), b"journal", &0u64.to_le_bytes()], &program_id);
+    let title = "t".repeat(TITLE_CHARS);
+    let message = "m".repeat(MESSAGE_CHARS);
+
+    bench.measure(
+        "new_crud_app.initialize_user_journal_counter",
+        instruction(
+            program_id,
+            accounts::InitializeUserJournalCounter { user_journal_counter, owner, system_program: system_program::ID },
+            ix::InitializeUserJournalCounter {},
+        ),
+    );
+    bench.measure(
+        "new_crud_app.create_journal_entry",
+        instruction(
+            program_id,
+            accounts::CreateEntry {
+                user_journal_counter,
+                journal_entry,
+                previous_entry: None,
+                idempotency_record: None,
+                owner,
+                system_program: system_program::ID,
+            },
+            ix::CreateJournalEntry {
+                expected_index: 0,
+                title: title.clone(),
+                message: message.clone(),
+                idempotency_key: None,
+            },
+        ),
+    );
+    bench.measure(
+        "new_crud_app.update_journal_entry",
+        instruction(
+            program_id,
+            accounts::UpdateEntry { journal_entry, owner, system_program: system_program::ID },
+            ix::UpdateJournalEntry {
+                _entry_index: 0,
+                _expected_edit_count: 0,
+                new_title: title.clone(),
+                new_message: message.to_uppercase(),
+            },
+        ),
+    );
+    bench.measure(
+        "new_crud_app.delete_journal_entry",
+        instruction(
+            program_id,
+            accounts::DeleteEntry { journal_entry, owner, system_program: system_program::ID },
+            ix::DeleteJournalEntry { _entry_index: 0 },
+        ),
+    );
+
+    // migrate_entry needs an entry in the V0 layout, which the current program can no longer create
+    let legacy_index = 1u64;
+    let (legacy_entry, bump) =
+        Pubkey::find_program_address(&[owner.as_ref(), b"journal", &legacy_index.to_le_bytes()], &program_id);
+    let mut data = JournalEntryState::DISCRIMINATOR.to_vec();
+    (owner, title.clone(), message.clone(), legacy_index, bump).serialize(&mut data).unwrap();
+    data.resize(8 + JournalEntryStateV0::INIT_SPACE, 0);
+    bench.set_account(legacy_entry, program_id, data, false);
+    bench.measure(
+        "new_crud_app.migrate_entry",
+        instruction(
+            program_id,
+            accounts::MigrateEntry { journal_entry: legacy_entry, owner, system_program: system_program::ID },
+            ix::MigrateEntry { _entry_index: legacy_index },
+        ),
+    );
+}
+
+fn main() -> ExitCode {
+    let mut bench = Bench::new();
+    bench_journal(&mut bench);
+    bench_new_crud_app(&mut bench);
+    let results = bench.results;
+
+    if std::env::var_os("UPDATE_CU_BASELINE").is_some() {
+        let json = serde_json::to_string_pretty(&results).unwrap();
+        std::fs::write(BASELINE_PATH, json + "\n").expect("writing the baseline");
+        println!("Wrote {} instructions to {BASELINE_PATH}", results.len());
+        return ExitCode::SUCCESS;
+    }
+
+    let baseline: BTreeMap<String, u64> = match std::fs::read_to_string(BASELINE_PATH) {
+        Ok(json) => serde_json::from_str(&json).expect("parsing the baseline"),
+        Err(_) => BTreeMap::new(),
+    };
+    let tolerance_percent = std::env::var("CU_TOLERANCE_PERCENT")
+        .ok()
+        .map(|value| value.parse().expect("CU_TOLERANCE_PERCENT must be a whole number"))
+        .unwrap_or(DEFAULT_TOLERANCE_PERCENT);
+
+    let mut regressions = 0;
+    println!("{:<48} {:>10} {:>10}", "instruction", "baseline", "units");
+    for (name, &units) in &results {
+        let status = match baseline.get(name) {
+            Some(&base) if units * 100 > base * (100 + tolerance_percent) => {
+                regressions += 1;
+                "REGRESSED"
+            }
+            Some(_) => "",
+            None => "no baseline",
+        };
+        let base = baseline.get(name).map_or("-".to_string(), u64::to_string);
+        println!("{name:<48} {base:>10} {units:>10} {status}");
+    }
+
+    if regressions > 0 {
+        eprintln!("{regressions} instruction(s) use more than {tolerance_percent}% over their baseline");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
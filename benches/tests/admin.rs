@@ -0,0 +1,225 @@
+// The admin's and the moderator's powers, and who else is refused them: creating the config, withdrawing from
+// the treasury, pausing, handing the admin role over, allowlisting CPI callers and flagging entries. Also
+// migrate_entry between two wallets that both sign, which the recovery tests don't cover.
+
+mod common;
+
+use anchor_lang::InstructionData;
+use common::{assert_error, instruction, journal_entry, pda, user_profile, Journal};
+use journal::{accounts, instruction as ix, JournalEntry, JournalError, ProgramConfig, UserProfile};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk_ids::system_program;
+
+// An instruction that only `admin`, as the config's admin, may send
+fn update_config(admin: &Pubkey, data: impl InstructionData) -> Instruction {
+    instruction(accounts::UpdateConfig { program_config: common::program_config(), admin: *admin }, data)
+}
+
+fn config(journal: &Journal) -> ProgramConfig {
+    journal.fetch(&common::program_config())
+}
+
+#[test]
+fn only_the_upgrade_authority_can_create_the_config() {
+    let mut journal = Journal::deployed();
+    let stranger = journal.wallet();
+    let initialize = journal.initialize_config_instruction(&stranger.pubkey(), 0, 0, 0, 0);
+    assert_error(journal.send(initialize, &[&stranger]), JournalError::Unauthorized);
+    assert!(!journal.exists(&common::program_config()));
+
+    let admin = journal.admin.insecure_clone();
+    let initialize = journal.initialize_config_instruction(&admin.pubkey(), 0, 0, 0, 0);
+    journal.ok(initialize, &[&admin]);
+    assert_eq!(config(&journal).admin, admin.pubkey());
+}
+
+#[test]
+fn the_admin_withdraws_only_what_is_above_rent() {
+    let mut journal = Journal::new();
+    let treasury = pda(&[b"treasury"]);
+    journal.svm.airdrop(&treasury, 1_000_000).expect("airdrop");
+    let account = journal.svm.get_account(&treasury).unwrap();
+    let available = account.lamports - journal.svm.minimum_balance_for_rent_exemption(account.data.len());
+    let admin = journal.admin.insecure_clone();
+    let stranger = journal.wallet();
+    let recipient = Keypair::new().pubkey();
+    let withdraw = |admin: &Pubkey, amount| {
+        instruction(
+            accounts::WithdrawTreasury { program_config: common::program_config(), treasury, recipient, admin: *admin },
+            ix::WithdrawTreasury { amount },
+        )
+    };
+
+    assert_error(journal.send(withdraw(&stranger.pubkey(), 1), &[&stranger]), JournalError::Unauthorized);
+    let too_much = withdraw(&admin.pubkey(), available + 1);
+    assert_error(journal.send(too_much, &[&admin]), JournalError::InsufficientTreasuryBalance);
+    assert_error(journal.send(withdraw(&admin.pubkey(), 0), &[&admin]), JournalError::InsufficientTreasuryBalance);
+
+    journal.ok(withdraw(&admin.pubkey(), available), &[&admin]);
+    assert_eq!(journal.svm.get_balance(&recipient), Some(available));
+    assert!(journal.exists(&treasury));
+}
+
+#[test]
+fn only_the_admin_can_pause() {
+    let mut journal = Journal::new();
+    let author = journal.wallet();
+    journal.initialize_profile(&author);
+    let pause = update_config(&author.pubkey(), ix::SetPaused { paused: true });
+    assert_error(journal.send(pause, &[&author]), JournalError::Unauthorized);
+
+    journal.set_paused(true);
+    assert!(config(&journal).paused);
+    let add = journal.add_entry_instruction(&author.pubkey(), &author.pubkey(), None, "Paused", "Refused");
+    assert_error(journal.send(add, &[&author]), JournalError::ProgramPaused);
+    // Which instructions check the switch is covered by the program's unit tests; this one only moves lamports
+    let fund = instruction(
+        accounts::FundSponsorPool {
+            program_config: common::program_config(),
+            sponsor_pool: pda(&[b"sponsor_pool"]),
+            funder: author.pubkey(),
+            system_program: system_program::ID,
+        },
+        ix::FundSponsorPool { amount: 1_000_000 },
+    );
+    assert_error(journal.send(fund, &[&author]), JournalError::ProgramPaused);
+
+    journal.set_paused(false);
+    journal.add_entry(&author, "Unpaused", "Accepted again");
+}
+
+#[test]
+fn the_admin_role_moves_only_when_the_proposed_key_accepts() {
+    let mut journal = Journal::new();
+    let admin = journal.admin.insecure_clone();
+    let new_admin = journal.wallet();
+    let stranger = journal.wallet();
+    let accept = |new_admin: &Pubkey| {
+        instruction(
+            accounts::AcceptAdmin { program_config: common::program_config(), new_admin: *new_admin },
+            ix::AcceptAdmin {},
+        )
+    };
+
+    let propose = update_config(&stranger.pubkey(), ix::ProposeAdmin { new_admin: stranger.pubkey() });
+    assert_error(journal.send(propose, &[&stranger]), JournalError::Unauthorized);
+    assert_error(journal.send(accept(&new_admin.pubkey()), &[&new_admin]), JournalError::Unauthorized);
+
+    journal.ok(update_config(&admin.pubkey(), ix::ProposeAdmin { new_admin: new_admin.pubkey() }), &[&admin]);
+    assert_error(journal.send(accept(&stranger.pubkey()), &[&stranger]), JournalError::Unauthorized);
+    assert_eq!(config(&journal).admin, admin.pubkey());
+
+    journal.ok(accept(&new_admin.pubkey()), &[&new_admin]);
+    let config = config(&journal);
+    assert_eq!((config.admin, config.pending_admin), (new_admin.pubkey(), None));
+    let pause = update_config(&admin.pubkey(), ix::SetPaused { paused: true });
+    assert_error(journal.send(pause, &[&admin]), JournalError::Unauthorized);
+}
+
+// Calls through an allowlisted program are tested with journal-vault in project-1/anchor/tests
+#[test]
+fn only_the_admin_sets_the_cpi_allowlist() {
+    let mut journal = Journal::new();
+    let admin = journal.admin.insecure_clone();
+    let stranger = journal.wallet();
+    let caller = Pubkey::new_unique();
+    let allow = update_config(&stranger.pubkey(), ix::SetCpiAllowlist { programs: vec![caller] });
+    assert_error(journal.send(allow, &[&stranger]), JournalError::Unauthorized);
+    let too_many = update_config(&admin.pubkey(), ix::SetCpiAllowlist { programs: vec![caller; 9] });
+    assert_error(journal.send(too_many, &[&admin]), JournalError::TooManyCpiCallers);
+
+    journal.ok(update_config(&admin.pubkey(), ix::SetCpiAllowlist { programs: vec![caller] }), &[&admin]);
+    assert_eq!(config(&journal).cpi_allowlist, vec![caller]);
+}
+
+#[test]
+fn only_the_moderator_can_flag_and_only_public_entries() {
+    let mut journal = Journal::new();
+    let admin = journal.admin.insecure_clone();
+    let moderator = journal.wallet();
+    let author = journal.wallet();
+    journal.ok(update_config(&admin.pubkey(), ix::SetModerator { moderator: Some(moderator.pubkey()) }), &[&admin]);
+    journal.ok(
+        instruction(
+            accounts::InitializeFeedHead {
+                program_config: common::program_config(),
+                feed_head: pda(&[b"feed_head"]),
+                admin: admin.pubkey(),
+                system_program: system_program::ID,
+            },
+            ix::InitializeFeedHead {},
+        ),
+        &[&admin],
+    );
+    journal.initialize_profile(&author);
+    let entry = journal.add_entry(&author, "Private", "Not flaggable yet");
+    let flag = |moderator: &Pubkey| {
+        instruction(
+            accounts::SetEntryFlagged {
+                program_config: common::program_config(),
+                journal_entry: entry,
+                moderator: *moderator,
+            },
+            ix::SetEntryFlagged { flagged: true },
+        )
+    };
+
+    assert_error(journal.send(flag(&moderator.pubkey()), &[&moderator]), JournalError::EntryNotPublic);
+    journal.ok(
+        instruction(
+            accounts::SetEntryVisibility {
+                program_config: common::program_config(),
+                journal_entry: entry,
+                feed_head: pda(&[b"feed_head"]),
+                authority: author.pubkey(),
+                signer: author.pubkey(),
+                session_key: None,
+            },
+            ix::SetEntryVisibility { _entry_id: 0, is_public: true },
+        ),
+        &[&author],
+    );
+    // Neither the admin nor the author is the moderator
+    assert_error(journal.send(flag(&admin.pubkey()), &[&admin]), JournalError::Unauthorized);
+    assert_error(journal.send(flag(&author.pubkey()), &[&author]), JournalError::Unauthorized);
+
+    journal.ok(flag(&moderator.pubkey()), &[&moderator]);
+    assert!(journal.fetch::<JournalEntry>(&entry).flagged);
+}
+
+#[test]
+fn migrating_between_wallets_needs_both_signatures() {
+    let mut journal = Journal::new();
+    let old_wallet = journal.wallet();
+    let new_wallet = journal.wallet();
+    journal.initialize_profile(&old_wallet);
+    journal.initialize_profile(&new_wallet);
+    let old_entry = journal.add_entry(&old_wallet, "Moving", "To the new wallet");
+    let new_entry = journal_entry(&new_wallet.pubkey(), 0);
+    let mut migrate = instruction(
+        accounts::MigrateEntry {
+            program_config: common::program_config(),
+            old_profile: user_profile(&old_wallet.pubkey()),
+            new_profile: user_profile(&new_wallet.pubkey()),
+            old_authority: old_wallet.pubkey(),
+            new_authority: new_wallet.pubkey(),
+            system_program: system_program::ID,
+        },
+        ix::MigrateEntry {},
+    );
+    migrate.accounts.push(AccountMeta::new(old_entry, false));
+    migrate.accounts.push(AccountMeta::new(new_entry, false));
+
+    assert_error(journal.send(migrate.clone(), &[&new_wallet]), JournalError::Unauthorized);
+    assert!(journal.exists(&old_entry));
+
+    let old_authority = migrate.accounts.iter_mut().find(|meta| meta.pubkey == old_wallet.pubkey()).unwrap();
+    old_authority.is_signer = true;
+    journal.ok(migrate, &[&new_wallet, &old_wallet]);
+    assert!(!journal.exists(&old_entry));
+    let entry: JournalEntry = journal.fetch(&new_entry);
+    assert_eq!((entry.authority, entry.title.as_str()), (new_wallet.pubkey(), "Moving"));
+    assert_eq!(journal.fetch::<UserProfile>(&user_profile(&new_wallet.pubkey())).entry_count, 1);
+}
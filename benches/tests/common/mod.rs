@@ -0,0 +1,272 @@
+// Shared setup for the LiteSVM tests of the journal program. Like the benchmarks, they load the program that
+// `anchor build` left in project-1/anchor/target/deploy, so build it first, then run `cargo test` from benches/.
+
+#![allow(dead_code)] // Each test file uses a different part of this
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use journal::{accounts, instruction as ix};
+use litesvm::types::TransactionResult;
+use litesvm::LiteSVM;
+use solana_loader_v3_interface::get_program_data_address;
+use solana_loader_v3_interface::state::UpgradeableLoaderState;
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_sdk_ids::{bpf_loader_upgradeable, ed25519_program, system_program, sysvar};
+
+const JOURNAL_SO: &str = "../project-1/anchor/target/deploy/journal.so";
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+pub struct Journal {
+    pub svm: LiteSVM,
+    pub admin: Keypair, // Upgrade authority and config admin
+}
+
+impl Journal {
+    // The program deployed behind the upgradeable loader, so initialize_config accepts the admin, and a config
+    // with fees, limits and deposits disabled
+    pub fn new() -> Self {
+        let mut journal = Self::deployed();
+        let admin = journal.admin.insecure_clone();
+        let initialize_config = journal.initialize_config_instruction(&admin.pubkey(), 0, 0, 0, 0);
+        journal.ok(initialize_config, &[&admin]);
+        journal
+    }
+
+    // The program deployed with `admin` as its upgrade authority, and no config yet
+    pub fn deployed() -> Self {
+        let mut svm = LiteSVM::new();
+        let admin = Keypair::new();
+        svm.airdrop(&admin.pubkey(), 100_000_000_000).expect("airdrop");
+        let mut journal = Self { svm, admin };
+        journal.deploy();
+        journal
+    }
+
+    fn deploy(&mut self) {
+        let elf = std::fs::read(JOURNAL_SO)
+            .unwrap_or_else(|err| panic!("reading {JOURNAL_SO}: {err} (run `anchor build` first)"));
+        let programdata_address = get_program_data_address(&journal::ID);
+        let mut programdata = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(self.admin.pubkey()),
+        })
+        .unwrap();
+        programdata.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+        programdata.extend_from_slice(&elf);
+        let program = bincode::serialize(&UpgradeableLoaderState::Program { programdata_address }).unwrap();
+        self.set_account(programdata_address, bpf_loader_upgradeable::id(), programdata, false);
+        self.set_account(journal::ID, bpf_loader_upgradeable::id(), program, true);
+    }
+
+    pub fn set_account(&mut self, address: Pubkey, owner: Pubkey, data: Vec<u8>, executable: bool) {
+        let account = Account {
+            lamports: self.svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner,
+            executable,
+            rent_epoch: 0,
+        };
+        self.svm.set_account(address, account).expect("set_account");
+    }
+
+    pub fn initialize_config_instruction(
+        &self,
+        admin: &Pubkey,
+        fee_lamports: u64,
+        max_entries_per_day: u32,
+        entry_deposit_lamports: u64,
+        deposit_cooldown_seconds: i64,
+    ) -> Instruction {
+        instruction(
+            accounts::InitializeConfig {
+                program_config: program_config(),
+                treasury: pda(&[b"treasury"]),
+                program: journal::ID,
+                program_data: get_program_data_address(&journal::ID),
+                admin: *admin,
+                system_program: system_program::ID,
+            },
+            ix::InitializeConfig {
+                fee_lamports,
+                max_entries_per_day,
+                entry_deposit_lamports,
+                deposit_cooldown_seconds,
+            },
+        )
+    }
+
+    // A funded wallet
+    pub fn wallet(&mut self) -> Keypair {
+        let wallet = Keypair::new();
+        self.svm.airdrop(&wallet.pubkey(), 10_000_000_000).expect("airdrop");
+        wallet
+    }
+
+    // Sends `instruction` with the first signer paying
+    pub fn send(&mut self, instruction: Instruction, signers: &[&Keypair]) -> TransactionResult {
+        self.send_all(&[instruction], signers)
+    }
+
+    pub fn send_all(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> TransactionResult {
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&signers[0].pubkey()),
+            signers,
+            self.svm.latest_blockhash(),
+        );
+        let result = self.svm.send_transaction(transaction);
+        // Sending the same instruction again later would otherwise be rejected as a duplicate
+        self.svm.expire_blockhash();
+        result
+    }
+
+    pub fn ok(&mut self, instruction: Instruction, signers: &[&Keypair]) {
+        if let Err(failed) = self.send(instruction, signers) {
+            panic!("{:?}\n{}", failed.err, failed.meta.logs.join("\n"));
+        }
+    }
+
+    pub fn fetch<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self.svm.get_account(address).unwrap_or_else(|| panic!("no account at {address}"));
+        T::try_deserialize(&mut &account.data[..]).expect("deserialize")
+    }
+
+    pub fn exists(&self, address: &Pubkey) -> bool {
+        self.svm.get_account(address).is_some_and(|account| account.lamports > 0)
+    }
+
+    pub fn warp(&mut self, seconds: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += seconds;
+        self.svm.set_sysvar(&clock);
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        let admin = self.admin.insecure_clone();
+        let set_paused = instruction(
+            accounts::UpdateConfig { program_config: program_config(), admin: admin.pubkey() },
+            ix::SetPaused { paused },
+        );
+        self.ok(set_paused, &[&admin]);
+    }
+
+    pub fn initialize_profile(&mut self, authority: &Keypair) {
+        self.ok(
+            instruction(
+                accounts::InitializeUserProfile {
+                    program_config: program_config(),
+                    user_profile: user_profile(&authority.pubkey()),
+                    authority: authority.pubkey(),
+                    payer: authority.pubkey(),
+                    system_program: system_program::ID,
+                },
+                ix::InitializeUserProfile {},
+            ),
+            &[authority],
+        );
+    }
+
+    // Adds the profile's next entry, signed by `signer` (the authority, or one of its session keys)
+    pub fn add_entry_instruction(
+        &self,
+        authority: &Pubkey,
+        signer: &Pubkey,
+        session_key: Option<Pubkey>,
+        title: &str,
+        message: &str,
+    ) -> Instruction {
+        let profile = self.fetch::<journal::UserProfile>(&user_profile(authority));
+        instruction(
+            accounts::AddJournalEntry {
+                user_profile: user_profile(authority),
+                journal_entry: journal_entry(authority, profile.entry_count),
+                program_config: program_config(),
+                treasury: pda(&[b"treasury"]),
+                authority: *authority,
+                signer: *signer,
+                session_key,
+                gate_token_account: None,
+                title_claim: None,
+                price_update: None,
+                daily_prompt: None,
+                journal: None,
+                feed_head: None,
+                reply_parent: None,
+                memo_program: None,
+                instructions_sysvar: sysvar::instructions::ID,
+                payer: *signer,
+                system_program: system_program::ID,
+            },
+            ix::AddJournalEntry { title: title.to_string(), message: message.to_string() },
+        )
+    }
+
+    // Adds an entry signed by its authority and returns its address
+    pub fn add_entry(&mut self, authority: &Keypair, title: &str, message: &str) -> Pubkey {
+        let id = self.fetch::<journal::UserProfile>(&user_profile(&authority.pubkey())).entry_count;
+        let add = self.add_entry_instruction(&authority.pubkey(), &authority.pubkey(), None, title, message);
+        self.ok(add, &[authority]);
+        journal_entry(&authority.pubkey(), id)
+    }
+}
+
+pub fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction { program_id: journal::ID, accounts: accounts.to_account_metas(None), data: data.data() }
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &journal::ID).0
+}
+
+pub fn program_config() -> Pubkey {
+    pda(&[b"program_config"])
+}
+
+pub fn user_profile(authority: &Pubkey) -> Pubkey {
+    pda(&[b"user_profile", authority.as_ref()])
+}
+
+pub fn journal_entry(authority: &Pubkey, id: u64) -> Pubkey {
+    pda(&[b"journal_entry", authority.as_ref(), &id.to_le_bytes()])
+}
+
+// An ed25519 program instruction verifying `signer`'s signature over `message`, with the public key, signature
+// and message all inline, as add_entry_relayed expects the instruction before it to be
+pub fn ed25519_instruction(signer: &Keypair, message: &[u8]) -> Instruction {
+    const PUBKEY_OFFSET: u16 = 2 + 14; // After the header and the seven u16 offsets
+    const SIGNATURE_OFFSET: u16 = PUBKEY_OFFSET + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+    let signature = signer.sign_message(message);
+    // u16::MAX as an instruction index means this instruction
+    let offsets = [SIGNATURE_OFFSET, u16::MAX, PUBKEY_OFFSET, u16::MAX, MESSAGE_OFFSET, message.len() as u16, u16::MAX];
+    let mut data = vec![1, 0]; // One signature, then padding
+    for offset in offsets {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signature.as_ref());
+    data.extend_from_slice(message);
+    Instruction { program_id: ed25519_program::ID, accounts: Vec::new(), data }
+}
+
+// Panics unless the transaction failed with `error` (a JournalError, or one of Anchor's own ErrorCodes)
+pub fn assert_error(result: TransactionResult, error: impl Into<u32> + std::fmt::Debug) {
+    let failed = match result {
+        Ok(_) => panic!("expected {error:?}, but the transaction succeeded"),
+        Err(failed) => failed,
+    };
+    let expected = error.into();
+    match failed.err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) if code == expected => {}
+        other => panic!("expected error {expected}, got {other:?}\n{}", failed.meta.logs.join("\n")),
+    }
+}
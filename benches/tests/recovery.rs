@@ -0,0 +1,171 @@
+// Profile recovery: initiate_recovery, the challenge period, cancel_recovery and complete_recovery, then moving
+// the entries to the new wallet with migrate_entry.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::{assert_error, instruction, journal_entry, user_profile, Journal, SECONDS_PER_DAY};
+use journal::{accounts, instruction as ix, JournalEntry, JournalError, UserProfile};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk_ids::system_program;
+
+struct Recovery {
+    journal: Journal,
+    author: Keypair,
+    recovery_key: Keypair,
+    new_wallet: Keypair,
+    entry: Pubkey,
+}
+
+// An author with one entry and `recovery_key` registered with a one-day challenge period
+fn setup() -> Recovery {
+    let mut journal = Journal::new();
+    let author = journal.wallet();
+    let recovery_key = journal.wallet();
+    let new_wallet = journal.wallet();
+    journal.initialize_profile(&author);
+    let entry = journal.add_entry(&author, "Day one", "Written before the key was lost");
+    journal.ok(
+        instruction(
+            accounts::UpdateProfile {
+                program_config: common::program_config(),
+                user_profile: user_profile(&author.pubkey()),
+                authority: author.pubkey(),
+            },
+            ix::SetRecoveryAuthority { recovery_authority: Some(recovery_key.pubkey()), delay_seconds: SECONDS_PER_DAY },
+        ),
+        &[&author],
+    );
+    Recovery { journal, author, recovery_key, new_wallet, entry }
+}
+
+impl Recovery {
+    fn recovery_accounts(&self, signer: &Pubkey) -> accounts::InitiateRecovery {
+        accounts::InitiateRecovery {
+            program_config: common::program_config(),
+            user_profile: user_profile(&self.author.pubkey()),
+            recovery_authority: *signer,
+        }
+    }
+
+    fn initiate(&self, signer: &Pubkey) -> Instruction {
+        instruction(self.recovery_accounts(signer), ix::InitiateRecovery { new_authority: self.new_wallet.pubkey() })
+    }
+
+    fn complete(&self) -> Instruction {
+        instruction(self.recovery_accounts(&self.recovery_key.pubkey()), ix::CompleteRecovery {})
+    }
+
+    fn cancel(&self) -> Instruction {
+        instruction(
+            accounts::UpdateProfile {
+                program_config: common::program_config(),
+                user_profile: user_profile(&self.author.pubkey()),
+                authority: self.author.pubkey(),
+            },
+            ix::CancelRecovery {},
+        )
+    }
+
+    fn profile(&self) -> UserProfile {
+        self.journal.fetch(&user_profile(&self.author.pubkey()))
+    }
+}
+
+#[test]
+fn only_the_recovery_key_can_initiate() {
+    let mut recovery = setup();
+    let stranger = recovery.journal.wallet();
+    let initiate = recovery.initiate(&stranger.pubkey());
+    assert_error(recovery.journal.send(initiate, &[&stranger]), JournalError::Unauthorized);
+
+    let initiate = recovery.initiate(&recovery.recovery_key.pubkey());
+    recovery.journal.ok(initiate, &[&recovery.recovery_key]);
+    let profile = recovery.profile();
+    assert_eq!(profile.pending_recovery, Some(recovery.new_wallet.pubkey()));
+    assert_eq!(profile.recovery_unlock_at, recovery.journal.now() + SECONDS_PER_DAY);
+}
+
+#[test]
+fn completes_only_after_the_challenge_period() {
+    let mut recovery = setup();
+    let initiate = recovery.initiate(&recovery.recovery_key.pubkey());
+    recovery.journal.ok(initiate, &[&recovery.recovery_key]);
+
+    let complete = recovery.complete();
+    assert_error(recovery.journal.send(complete, &[&recovery.recovery_key]), JournalError::RecoveryStillLocked);
+    recovery.journal.warp(SECONDS_PER_DAY - 1);
+    let complete = recovery.complete();
+    assert_error(recovery.journal.send(complete, &[&recovery.recovery_key]), JournalError::RecoveryStillLocked);
+    assert_eq!(recovery.profile().authority, recovery.author.pubkey());
+
+    recovery.journal.warp(1);
+    let complete = recovery.complete();
+    recovery.journal.ok(complete, &[&recovery.recovery_key]);
+    let profile = recovery.profile();
+    assert_eq!(profile.authority, recovery.new_wallet.pubkey());
+    assert_eq!((profile.pending_recovery, profile.recovery_authority), (None, None));
+}
+
+#[test]
+fn the_author_can_cancel_during_the_challenge_period() {
+    let mut recovery = setup();
+    let cancel = recovery.cancel();
+    assert_error(recovery.journal.send(cancel, &[&recovery.author]), JournalError::NoRecoveryPending);
+
+    let initiate = recovery.initiate(&recovery.recovery_key.pubkey());
+    recovery.journal.ok(initiate, &[&recovery.recovery_key]);
+    let cancel = recovery.cancel();
+    recovery.journal.ok(cancel, &[&recovery.author]);
+    assert_eq!(recovery.profile().pending_recovery, None);
+
+    recovery.journal.warp(SECONDS_PER_DAY);
+    let complete = recovery.complete();
+    assert_error(recovery.journal.send(complete, &[&recovery.recovery_key]), JournalError::NoRecoveryPending);
+    assert_eq!(recovery.profile().authority, recovery.author.pubkey());
+}
+
+#[test]
+fn recovered_entries_move_to_the_new_wallet() {
+    let mut recovery = setup();
+    let initiate = recovery.initiate(&recovery.recovery_key.pubkey());
+    recovery.journal.ok(initiate, &[&recovery.recovery_key]);
+    recovery.journal.warp(SECONDS_PER_DAY);
+    let complete = recovery.complete();
+    recovery.journal.ok(complete, &[&recovery.recovery_key]);
+
+    // The old profile no longer answers to the old key
+    let cancel = recovery.cancel();
+    assert_error(recovery.journal.send(cancel, &[&recovery.author]), ErrorCode::ConstraintHasOne);
+
+    // The new wallet takes the entries over without the old key's signature
+    let new_wallet = recovery.new_wallet.insecure_clone();
+    recovery.journal.initialize_profile(&new_wallet);
+    let new_entry = journal_entry(&new_wallet.pubkey(), 0);
+    let mut migrate = instruction(
+        accounts::MigrateEntry {
+            program_config: common::program_config(),
+            old_profile: user_profile(&recovery.author.pubkey()),
+            new_profile: user_profile(&new_wallet.pubkey()),
+            old_authority: recovery.author.pubkey(),
+            new_authority: new_wallet.pubkey(),
+            system_program: system_program::ID,
+        },
+        ix::MigrateEntry {},
+    );
+    migrate.accounts.push(AccountMeta::new(recovery.entry, false));
+    migrate.accounts.push(AccountMeta::new(new_entry, false));
+    let balance_before = recovery.journal.svm.get_balance(&new_wallet.pubkey()).unwrap();
+    recovery.journal.ok(migrate, &[&new_wallet]);
+
+    assert!(!recovery.journal.exists(&recovery.entry));
+    let entry: JournalEntry = recovery.journal.fetch(&new_entry);
+    assert_eq!((entry.authority, entry.id, entry.rent_payer), (new_wallet.pubkey(), 0, new_wallet.pubkey()));
+    assert_eq!(entry.title, "Day one");
+    assert_eq!(recovery.journal.fetch::<UserProfile>(&user_profile(&new_wallet.pubkey())).entry_count, 1);
+    // The new entry's rent is paid from the old one's refund, less the transaction fee
+    let balance_after = recovery.journal.svm.get_balance(&new_wallet.pubkey()).unwrap();
+    assert!(balance_before.saturating_sub(balance_after) < 10_000);
+}
@@ -0,0 +1,127 @@
+// add_entry_relayed: a relayer submits an entry the author signed off-chain, verified through the ed25519
+// program instruction that precedes it, and pays for it.
+
+mod common;
+
+use common::{assert_error, ed25519_instruction, instruction, journal_entry, pda, user_profile, Journal};
+use journal::{accounts, instruction as ix, relayed_entry_message, JournalEntry, JournalError, UserProfile};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk_ids::{system_program, sysvar};
+
+struct Relay {
+    journal: Journal,
+    author: Keypair,
+    relayer: Keypair,
+}
+
+// An author with a profile and no entries, and a funded relayer
+fn setup() -> Relay {
+    let mut journal = Journal::new();
+    let author = journal.wallet();
+    let relayer = journal.wallet();
+    journal.initialize_profile(&author);
+    Relay { journal, author, relayer }
+}
+
+impl Relay {
+    fn profile(&self) -> UserProfile {
+        self.journal.fetch(&user_profile(&self.author.pubkey()))
+    }
+
+    // add_entry_relayed for the profile's next entry; the relayer is its only signer
+    fn add_entry_relayed(&self, nonce: u64, title: &str, message: &str) -> Instruction {
+        let author = self.author.pubkey();
+        instruction(
+            accounts::AddEntryRelayed {
+                program_config: common::program_config(),
+                user_profile: user_profile(&author),
+                journal_entry: journal_entry(&author, self.profile().entry_count),
+                treasury: pda(&[b"treasury"]),
+                authority: author,
+                gate_token_account: None,
+                title_claim: None,
+                relayer: self.relayer.pubkey(),
+                instructions_sysvar: sysvar::instructions::ID,
+                system_program: system_program::ID,
+            },
+            ix::AddEntryRelayed { nonce, title: title.to_string(), message: message.to_string() },
+        )
+    }
+
+    // The author's signature over the entry, then the relayed write of it
+    fn signed(&self, nonce: u64, title: &str, message: &str) -> [Instruction; 2] {
+        let signed_message = relayed_entry_message(&self.author.pubkey(), nonce, title, message);
+        [ed25519_instruction(&self.author, &signed_message), self.add_entry_relayed(nonce, title, message)]
+    }
+}
+
+#[test]
+fn the_relayer_pays_for_the_signed_entry() {
+    let mut relay = setup();
+    let author_balance = relay.journal.svm.get_balance(&relay.author.pubkey()).unwrap();
+    let signed = relay.signed(0, "From my phone", "No SOL needed");
+    let relayer = relay.relayer.insecure_clone();
+    relay.journal.send_all(&signed, &[&relayer]).expect("relayed write");
+
+    let entry: JournalEntry = relay.journal.fetch(&journal_entry(&relay.author.pubkey(), 0));
+    assert_eq!((entry.authority, entry.rent_payer), (relay.author.pubkey(), relay.relayer.pubkey()));
+    assert_eq!((entry.title.as_str(), entry.message.as_str()), ("From my phone", "No SOL needed"));
+    let profile = relay.profile();
+    assert_eq!((profile.entry_count, profile.write_nonce), (1, 1));
+    assert_eq!(relay.journal.svm.get_balance(&relay.author.pubkey()).unwrap(), author_balance);
+}
+
+#[test]
+fn rejects_content_other_than_what_was_signed() {
+    let mut relay = setup();
+    let signed_message = relayed_entry_message(&relay.author.pubkey(), 0, "What I wrote", "Signed");
+    let tampered = [
+        ed25519_instruction(&relay.author, &signed_message),
+        relay.add_entry_relayed(0, "What the relayer sent", "Signed"),
+    ];
+    let relayer = relay.relayer.insecure_clone();
+    let result = relay.journal.send_all(&tampered, &[&relayer]);
+    assert_error(result, JournalError::InvalidSignatureVerification);
+
+    // A valid signature by someone other than the author doesn't count either
+    let impostor = Keypair::new();
+    let signed_message = relayed_entry_message(&relay.author.pubkey(), 0, "Title", "Message");
+    let impersonated =
+        [ed25519_instruction(&impostor, &signed_message), relay.add_entry_relayed(0, "Title", "Message")];
+    let result = relay.journal.send_all(&impersonated, &[&relayer]);
+    assert_error(result, JournalError::InvalidSignatureVerification);
+    assert_eq!(relay.profile().entry_count, 0);
+}
+
+#[test]
+fn requires_the_ed25519_instruction() {
+    let mut relay = setup();
+    let relayer = relay.relayer.insecure_clone();
+    let unsigned = relay.add_entry_relayed(0, "Title", "Message");
+    assert_error(relay.journal.send(unsigned, &[&relayer]), JournalError::MissingSignatureVerification);
+
+    // It has to come before the relayed write, not after
+    let [ed25519, relayed] = relay.signed(0, "Title", "Message");
+    let result = relay.journal.send_all(&[relayed, ed25519], &[&relayer]);
+    assert_error(result, JournalError::MissingSignatureVerification);
+    assert_eq!(relay.profile().entry_count, 0);
+}
+
+#[test]
+fn a_signed_entry_cannot_be_replayed() {
+    let mut relay = setup();
+    let relayer = relay.relayer.insecure_clone();
+    let signed = relay.signed(0, "Once", "Only once");
+    relay.journal.send_all(&signed, &[&relayer]).expect("relayed write");
+
+    // The same signed payload again, aimed at the next entry address
+    let replayed = relay.signed(0, "Once", "Only once");
+    assert_error(relay.journal.send_all(&replayed, &[&relayer]), JournalError::InvalidWriteNonce);
+    let profile = relay.profile();
+    assert_eq!((profile.entry_count, profile.write_nonce), (1, 1));
+
+    let next = relay.signed(1, "Twice", "With the next nonce");
+    relay.journal.send_all(&next, &[&relayer]).expect("relayed write");
+    assert_eq!(relay.profile().write_nonce, 2);
+}
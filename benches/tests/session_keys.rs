@@ -0,0 +1,125 @@
+// Session keys: an ephemeral key acting for its authority until it expires, limited to its permission bits, and
+// revocable even while the program is paused.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::{assert_error, instruction, pda, Journal, SECONDS_PER_DAY};
+use journal::{accounts, instruction as ix, JournalError, PERMISSION_CREATE, PERMISSION_UPDATE};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk_ids::system_program;
+
+struct Session {
+    journal: Journal,
+    author: Keypair,
+    key: Keypair, // Funded, since it pays for the entries it writes
+}
+
+// An author with a profile, and a session key for it valid for a day with `permissions`
+fn setup(permissions: u8) -> Session {
+    let mut journal = Journal::new();
+    let author = journal.wallet();
+    let key = journal.wallet();
+    journal.initialize_profile(&author);
+    let expires_at = journal.now() + SECONDS_PER_DAY;
+    journal.ok(
+        instruction(
+            accounts::CreateSessionKey {
+                program_config: common::program_config(),
+                session_key: session_key(&author.pubkey(), &key.pubkey()),
+                authority: author.pubkey(),
+                system_program: system_program::ID,
+            },
+            ix::CreateSessionKey { session_pubkey: key.pubkey(), expires_at, permissions },
+        ),
+        &[&author],
+    );
+    Session { journal, author, key }
+}
+
+fn session_key(authority: &Pubkey, key: &Pubkey) -> Pubkey {
+    pda(&[b"session_key", authority.as_ref(), key.as_ref()])
+}
+
+impl Session {
+    fn add_entry(&self, title: &str) -> Instruction {
+        let (author, key) = (self.author.pubkey(), self.key.pubkey());
+        self.journal.add_entry_instruction(&author, &key, Some(session_key(&author, &key)), title, "From the app")
+    }
+
+    fn revoke(&self) -> Instruction {
+        instruction(
+            accounts::RevokeSessionKey {
+                session_key: session_key(&self.author.pubkey(), &self.key.pubkey()),
+                authority: self.author.pubkey(),
+            },
+            ix::RevokeSessionKey { _session_pubkey: self.key.pubkey() },
+        )
+    }
+}
+
+#[test]
+fn stops_working_when_it_expires() {
+    let mut session = setup(PERMISSION_CREATE);
+    let key = session.key.insecure_clone();
+    let add = session.add_entry("Before");
+    session.journal.ok(add, &[&key]);
+
+    session.journal.warp(SECONDS_PER_DAY - 1);
+    let add = session.add_entry("Just before");
+    session.journal.ok(add, &[&key]);
+
+    session.journal.warp(1);
+    let add = session.add_entry("After");
+    assert_error(session.journal.send(add, &[&key]), JournalError::SessionExpired);
+}
+
+#[test]
+fn cannot_create_one_that_has_already_expired() {
+    let mut journal = Journal::new();
+    let author = journal.wallet();
+    let key = Keypair::new();
+    let expires_at = journal.now();
+    let create = instruction(
+        accounts::CreateSessionKey {
+            program_config: common::program_config(),
+            session_key: session_key(&author.pubkey(), &key.pubkey()),
+            authority: author.pubkey(),
+            system_program: system_program::ID,
+        },
+        ix::CreateSessionKey { session_pubkey: key.pubkey(), expires_at, permissions: PERMISSION_CREATE },
+    );
+    assert_error(journal.send(create, &[&author]), JournalError::SessionExpired);
+}
+
+#[test]
+fn only_does_what_its_permissions_allow() {
+    let mut session = setup(PERMISSION_UPDATE);
+    let key = session.key.insecure_clone();
+    let add = session.add_entry("Not allowed");
+    assert_error(session.journal.send(add, &[&key]), JournalError::PermissionDenied);
+
+    // The author's own signature needs no permission bits
+    let author = session.author.insecure_clone();
+    session.journal.add_entry(&author, "Allowed", "Signed by the author");
+}
+
+#[test]
+fn can_be_revoked_while_paused() {
+    let mut session = setup(PERMISSION_CREATE);
+    session.journal.set_paused(true);
+    let (author, key) = (session.author.insecure_clone(), session.key.insecure_clone());
+    let add = session.add_entry("Paused");
+    assert_error(session.journal.send(add, &[&key]), JournalError::ProgramPaused);
+
+    let revoke = session.revoke();
+    session.journal.ok(revoke, &[&author]);
+    assert!(!session.journal.exists(&session_key(&author.pubkey(), &key.pubkey())));
+
+    // Once unpaused, the revoked key is no longer accepted
+    session.journal.set_paused(false);
+    let add = session.add_entry("Revoked");
+    assert_error(session.journal.send(add, &[&key]), ErrorCode::AccountNotInitialized);
+}
@@ -0,0 +1,275 @@
+// Byte-level test vectors for client implementers in other languages. A scripted run of each program (a profile
+// or counter, then one entry created, updated and deleted) is recorded in test-vectors.json: the PDAs involved,
+// and for every instruction its data and account metas, the data of the accounts it touched afterwards and the
+// events it emitted, all hex encoded. Wallets come from fixed seeds and the clock is pinned, so every byte is
+// reproducible.
+//
+// The first run writes the corpus; later runs fail if the programs no longer produce it. After an intended change,
+// rerun with UPDATE_TEST_VECTORS=1 and commit the rewritten corpus with it, like compute-units.json.
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use common::{instruction, Journal};
+use serde_json::{json, Value};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk_ids::{system_program, sysvar};
+
+const CORPUS_PATH: &str = "test-vectors.json";
+const NEW_CRUD_APP_SO: &str = "../project-3-pdas/target/deploy/new_crud_app.so";
+const TIMESTAMP: i64 = 1_700_000_000;
+
+struct Recorder {
+    journal: Journal,
+    author: Keypair,
+    pdas: Vec<Value>,
+    instructions: Vec<Value>,
+}
+
+impl Recorder {
+    fn pda(&mut self, name: &str, seeds: &[&[u8]], program_id: &Pubkey) -> Pubkey {
+        let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+        self.pdas.push(json!({
+            "name": name,
+            "program_id": program_id.to_string(),
+            "seeds": seeds.iter().map(|seed| hex(seed)).collect::<Vec<_>>(),
+            "address": address.to_string(),
+            "bump": bump,
+        }));
+        address
+    }
+
+    // Sends `instruction` signed by the author and records it, with the data of `touched` afterwards (null for a
+    // closed account)
+    fn record(&mut self, name: &str, instruction: Instruction, touched: &[Pubkey]) {
+        let author = self.author.insecure_clone();
+        let meta = self
+            .journal
+            .send(instruction.clone(), &[&author])
+            .unwrap_or_else(|failed| panic!("{name}: {:?}\n{}", failed.err, failed.meta.logs.join("\n")));
+        let events: Vec<String> = meta
+            .logs
+            .iter()
+            .filter_map(|log| log.strip_prefix("Program data: "))
+            .map(|data| hex(&BASE64.decode(data).expect("event data is base64")))
+            .collect();
+        let accounts_after: Vec<Value> = touched
+            .iter()
+            .map(|address| {
+                let account = self.journal.svm.get_account(address).filter(|account| account.lamports > 0);
+                json!({ "address": address.to_string(), "data": account.map(|account| hex(&account.data)) })
+            })
+            .collect();
+        self.instructions.push(json!({
+            "name": name,
+            "program_id": instruction.program_id.to_string(),
+            "data": hex(&instruction.data),
+            "accounts": instruction
+                .accounts
+                .iter()
+                .map(|meta| json!({
+                    "pubkey": meta.pubkey.to_string(),
+                    "is_signer": meta.is_signer,
+                    "is_writable": meta.is_writable,
+                }))
+                .collect::<Vec<_>>(),
+            "accounts_after": accounts_after,
+            "events": events,
+        }));
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn record_journal(recorder: &mut Recorder) {
+    use journal::{accounts, instruction as ix};
+
+    let author = recorder.author.pubkey();
+    let program_id = journal::ID;
+    let program_config = recorder.pda("program_config", &[b"program_config"], &program_id);
+    let treasury = recorder.pda("treasury", &[b"treasury"], &program_id);
+    let user_profile = recorder.pda("user_profile", &[b"user_profile", author.as_ref()], &program_id);
+    let journal_entry =
+        recorder.pda("journal_entry", &[b"journal_entry", author.as_ref(), &0u64.to_le_bytes()], &program_id);
+
+    recorder.record(
+        "journal.initialize_user_profile",
+        instruction(
+            accounts::InitializeUserProfile {
+                program_config,
+                user_profile,
+                authority: author,
+                payer: author,
+                system_program: system_program::ID,
+            },
+            ix::InitializeUserProfile {},
+        ),
+        &[user_profile],
+    );
+    recorder.record(
+        "journal.add_journal_entry",
+        instruction(
+            accounts::AddJournalEntry {
+                user_profile,
+                journal_entry,
+                program_config,
+                treasury,
+                authority: author,
+                signer: author,
+                session_key: None,
+                gate_token_account: None,
+                title_claim: None,
+                price_update: None,
+                daily_prompt: None,
+                journal: None,
+                feed_head: None,
+                reply_parent: None,
+                memo_program: None,
+                instructions_sysvar: sysvar::instructions::ID,
+                payer: author,
+                system_program: system_program::ID,
+            },
+            ix::AddJournalEntry { title: "Día uno".into(), message: "First entry ✍️".into() },
+        ),
+        &[user_profile, journal_entry],
+    );
+    recorder.record(
+        "journal.update_journal_entry",
+        instruction(
+            accounts::UpdateJournalEntry {
+                program_config,
+                user_profile,
+                journal_entry,
+                authority: author,
+                signer: author,
+                session_key: None,
+                memo_program: None,
+                system_program: system_program::ID,
+            },
+            ix::UpdateJournalEntry { _entry_id: 0, title: "Día uno".into(), message: "Edited".into() },
+        ),
+        &[user_profile, journal_entry],
+    );
+    recorder.record(
+        "journal.delete_journal_entry",
+        instruction(
+            accounts::DeleteJournalEntry {
+                program_config,
+                user_profile,
+                journal_entry,
+                rent_recipient: author,
+                authority: author,
+                signer: author,
+                session_key: None,
+                title_claim: None,
+                system_program: system_program::ID,
+            },
+            ix::DeleteJournalEntry { _entry_id: 0 },
+        ),
+        &[user_profile, journal_entry],
+    );
+}
+
+fn record_new_crud_app(recorder: &mut Recorder) {
+    use new_crud_app::{accounts, instruction as ix};
+
+    let elf = std::fs::read(NEW_CRUD_APP_SO)
+        .unwrap_or_else(|err| panic!("reading {NEW_CRUD_APP_SO}: {err} (run `anchor build` first)"));
+    recorder.journal.svm.add_program(new_crud_app::ID, &elf);
+    let owner = recorder.author.pubkey();
+    let program_id = new_crud_app::ID;
+    let user_journal_counter = recorder.pda("user_journal_counter", &[b"counter", owner.as_ref()], &program_id);
+    let journal_entry =
+        recorder.pda("journal_entry_state", &[owner.as_ref(), b"journal", &0u64.to_le_bytes()], &program_id);
+    let send = |accounts: &dyn ToAccountMetas, data: Vec<u8>| Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data,
+    };
+    recorder.record(
+        "new_crud_app.initialize_user_journal_counter",
+        send(
+            &accounts::InitializeUserJournalCounter { user_journal_counter, owner, system_program: system_program::ID },
+            ix::InitializeUserJournalCounter {}.data(),
+        ),
+        &[user_journal_counter],
+    );
+    recorder.record(
+        "new_crud_app.create_journal_entry",
+        send(
+            &accounts::CreateEntry {
+                user_journal_counter,
+                journal_entry,
+                previous_entry: None,
+                idempotency_record: None,
+                owner,
+                system_program: system_program::ID,
+            },
+            ix::CreateJournalEntry {
+                expected_index: 0,
+                title: "Día uno".into(),
+                message: "First entry ✍️".into(),
+                idempotency_key: None,
+            }
+            .data(),
+        ),
+        &[user_journal_counter, journal_entry],
+    );
+    recorder.record(
+        "new_crud_app.update_journal_entry",
+        send(
+            &accounts::UpdateEntry { journal_entry, owner, system_program: system_program::ID },
+            ix::UpdateJournalEntry {
+                _entry_index: 0,
+                _expected_edit_count: 0,
+                new_title: "Día uno".into(),
+                new_message: "Edited".into(),
+            }
+            .data(),
+        ),
+        &[journal_entry],
+    );
+    recorder.record(
+        "new_crud_app.delete_journal_entry",
+        send(
+            &accounts::DeleteEntry { journal_entry, owner, system_program: system_program::ID },
+            ix::DeleteJournalEntry { _entry_index: 0 }.data(),
+        ),
+        &[journal_entry],
+    );
+}
+
+#[test]
+fn matches_the_committed_corpus() {
+    let mut journal = Journal::new();
+    let author = Keypair::new_from_array([1; 32]);
+    journal.svm.airdrop(&author.pubkey(), 10_000_000_000).expect("airdrop");
+    let now = journal.now();
+    journal.warp(TIMESTAMP - now);
+    let mut recorder = Recorder { journal, author, pdas: Vec::new(), instructions: Vec::new() };
+    record_journal(&mut recorder);
+    record_new_crud_app(&mut recorder);
+    let corpus = json!({
+        "author": recorder.author.pubkey().to_string(),
+        "unix_timestamp": TIMESTAMP,
+        "pdas": recorder.pdas,
+        "instructions": recorder.instructions,
+    });
+
+    let committed = std::fs::read_to_string(CORPUS_PATH).ok();
+    if committed.is_none() || std::env::var_os("UPDATE_TEST_VECTORS").is_some() {
+        std::fs::write(CORPUS_PATH, serde_json::to_string_pretty(&corpus).unwrap() + "\n").expect("writing");
+        return;
+    }
+    let committed: Value = serde_json::from_str(&committed.unwrap()).expect("parsing the corpus");
+    assert!(
+        committed == corpus,
+        "the programs no longer produce {CORPUS_PATH}; if that's intended, rerun with UPDATE_TEST_VECTORS=1"
+    );
+}
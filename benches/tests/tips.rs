@@ -0,0 +1,132 @@
+// Tip jars: tips stay withdrawable after their entry is deleted, and after a recovery moves the profile to a new
+// wallet, since withdraw_tips finds the jar from the entry's address rather than the entry account.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::{assert_error, instruction, journal_entry, pda, user_profile, Journal, SECONDS_PER_DAY};
+use journal::{accounts, instruction as ix};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk_ids::system_program;
+
+const TIP: u64 = 1_000_000_000;
+
+fn tip_jar(entry: &Pubkey) -> Pubkey {
+    pda(&[b"tip_jar", entry.as_ref()])
+}
+
+// An author with one entry, tipped TIP lamports by someone else
+fn tipped() -> (Journal, Keypair, Pubkey) {
+    let mut journal = Journal::new();
+    let author = journal.wallet();
+    let tipper = journal.wallet();
+    journal.initialize_profile(&author);
+    let entry = journal.add_entry(&author, "Day one", "Tip jar open");
+    journal.ok(
+        instruction(
+            accounts::TipEntry {
+                program_config: common::program_config(),
+                journal_entry: entry,
+                tip_jar: tip_jar(&entry),
+                tipper: tipper.pubkey(),
+                system_program: system_program::ID,
+            },
+            ix::TipEntry { amount: TIP },
+        ),
+        &[&tipper],
+    );
+    (journal, author, entry)
+}
+
+fn delete(author: &Keypair, entry: &Pubkey) -> Instruction {
+    instruction(
+        accounts::DeleteJournalEntry {
+            program_config: common::program_config(),
+            user_profile: user_profile(&author.pubkey()),
+            journal_entry: *entry,
+            rent_recipient: author.pubkey(),
+            authority: author.pubkey(),
+            signer: author.pubkey(),
+            session_key: None,
+            title_claim: None,
+            system_program: system_program::ID,
+        },
+        ix::DeleteJournalEntry { _entry_id: 0 },
+    )
+}
+
+// Withdraws entry 0's tips, written under `entry_authority`, to `authority`
+fn withdraw(entry_authority: &Pubkey, authority: &Pubkey) -> Instruction {
+    let entry = journal_entry(entry_authority, 0);
+    instruction(
+        accounts::WithdrawTips {
+            program_config: common::program_config(),
+            entry_authority: *entry_authority,
+            user_profile: user_profile(entry_authority),
+            journal_entry: entry,
+            tip_jar: tip_jar(&entry),
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        ix::WithdrawTips { entry_id: 0 },
+    )
+}
+
+#[test]
+fn tips_outlive_a_deleted_entry() {
+    let (mut journal, author, entry) = tipped();
+    journal.ok(delete(&author, &entry), &[&author]);
+    assert!(!journal.exists(&entry));
+
+    let stranger = journal.wallet();
+    let withdraw_as_stranger = withdraw(&author.pubkey(), &stranger.pubkey());
+    assert_error(journal.send(withdraw_as_stranger, &[&stranger]), ErrorCode::ConstraintHasOne);
+
+    let balance_before = journal.svm.get_balance(&author.pubkey()).unwrap();
+    journal.ok(withdraw(&author.pubkey(), &author.pubkey()), &[&author]);
+    let balance_after = journal.svm.get_balance(&author.pubkey()).unwrap();
+    assert!(balance_after + 10_000 > balance_before + TIP);
+    assert!(!journal.exists(&tip_jar(&entry)));
+}
+
+#[test]
+fn a_recovered_wallet_withdraws_the_old_entries_tips() {
+    let (mut journal, author, entry) = tipped();
+    let recovery_key = journal.wallet();
+    let new_wallet = journal.wallet();
+    journal.ok(
+        instruction(
+            accounts::UpdateProfile {
+                program_config: common::program_config(),
+                user_profile: user_profile(&author.pubkey()),
+                authority: author.pubkey(),
+            },
+            ix::SetRecoveryAuthority {
+                recovery_authority: Some(recovery_key.pubkey()),
+                delay_seconds: SECONDS_PER_DAY,
+            },
+        ),
+        &[&author],
+    );
+    let recovery_accounts = || accounts::InitiateRecovery {
+        program_config: common::program_config(),
+        user_profile: user_profile(&author.pubkey()),
+        recovery_authority: recovery_key.pubkey(),
+    };
+    journal.ok(
+        instruction(recovery_accounts(), ix::InitiateRecovery { new_authority: new_wallet.pubkey() }),
+        &[&recovery_key],
+    );
+    journal.warp(SECONDS_PER_DAY);
+    journal.ok(instruction(recovery_accounts(), ix::CompleteRecovery {}), &[&recovery_key]);
+
+    // The lost key no longer reaches them; the wallet the profile was recovered to does
+    assert_error(journal.send(withdraw(&author.pubkey(), &author.pubkey()), &[&author]), ErrorCode::ConstraintHasOne);
+    let balance_before = journal.svm.get_balance(&new_wallet.pubkey()).unwrap();
+    journal.ok(withdraw(&author.pubkey(), &new_wallet.pubkey()), &[&new_wallet]);
+    let balance_after = journal.svm.get_balance(&new_wallet.pubkey()).unwrap();
+    assert!(balance_after + 10_000 > balance_before + TIP);
+    assert!(!journal.exists(&tip_jar(&entry)));
+}
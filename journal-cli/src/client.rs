@@ -0,0 +1,251 @@
+// The subcommands' view of either program: entries by id, and the writes each subcommand makes. The journal
+// program numbers entries by its UserProfile's entry_count, journal_pda_optimized by its UserJournalCounter; both
+// call the number an id here.
+
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use journal_sdk::accounts::JournalEntry;
+use journal_sdk::pda_optimized::{self, CreateRetryConfig, JournalEntryState};
+use journal_sdk::reader::JournalReader;
+use journal_sdk::sender::{SenderConfig, Sent, TransactionSender};
+use journal_sdk::{instructions, offline, pda};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_hash::Hash;
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_signer::Signer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Program {
+    /// The journal program (project-1)
+    Journal,
+    /// journal_pda_optimized (project-3)
+    PdaOptimized,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub id: u64,
+    pub address: Pubkey,
+    pub title: String,
+    pub message: String,
+    pub updated_at: Option<i64>, // Unix seconds of the last create or update; journal program only
+    pub is_public: Option<bool>, // journal program only
+    pub edit_count: Option<u32>, // journal_pda_optimized only
+    pub rent_payer: Option<Pubkey>, // Who a delete refunds; journal program only
+}
+
+impl Entry {
+    fn from_journal(address: Pubkey, entry: JournalEntry) -> Self {
+        Entry {
+            id: entry.id,
+            address,
+            title: entry.title,
+            message: entry.message,
+            updated_at: Some(entry.timestamp),
+            is_public: Some(entry.is_public),
+            edit_count: None,
+            rent_payer: Some(entry.rent_payer),
+        }
+    }
+
+    fn from_pda_optimized(address: Pubkey, entry: JournalEntryState) -> Self {
+        Entry {
+            id: entry.entry_index,
+            address,
+            title: entry.title,
+            message: entry.message,
+            updated_at: None,
+            is_public: None,
+            edit_count: Some(entry.edit_count),
+            rent_payer: None,
+        }
+    }
+}
+
+pub struct JournalClient {
+    program: Program,
+    rpc: Arc<RpcClient>,
+    reader: JournalReader,
+    sender: TransactionSender,
+    keypair: Keypair,
+}
+
+impl JournalClient {
+    // `keypair` is the authority of the entries, and pays for everything
+    pub fn new(program: Program, rpc: Arc<RpcClient>, keypair: Keypair) -> Self {
+        JournalClient {
+            program,
+            reader: JournalReader::new(rpc.clone()),
+            sender: TransactionSender::new(rpc.clone(), SenderConfig::default()),
+            rpc,
+            keypair,
+        }
+    }
+
+    pub fn authority(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    // Creates the account that numbers the authority's entries
+    pub async fn init(&self) -> Result<String> {
+        let authority = self.authority();
+        let instruction = match self.program {
+            Program::Journal => instructions::initialize_user_profile(&authority, &authority),
+            Program::PdaOptimized => pda_optimized::initialize_user_journal_counter(&authority),
+        };
+        self.send(&[instruction]).await
+    }
+
+    // The new entry's id and the transaction signature
+    pub async fn add(&self, title: &str, message: &str) -> Result<(u64, String)> {
+        match self.program {
+            Program::Journal => {
+                let id = self.next_id().await?;
+                let instruction = self.create_instruction(id, title.to_string(), message.to_string());
+                Ok((id, self.send(&[instruction]).await?))
+            }
+            Program::PdaOptimized => {
+                let retry = CreateRetryConfig::default();
+                let (id, sent) =
+                    pda_optimized::create_entry_safe(&self.sender, &self.keypair, title, message, &retry).await?;
+                Ok((id, landed(sent)))
+            }
+        }
+    }
+
+    // Every entry in the current account layout, by id
+    pub async fn list(&self) -> Result<Vec<Entry>> {
+        let authority = self.authority();
+        let entries = match self.program {
+            Program::Journal => self
+                .reader
+                .fetch_all_entries_by_owner(&authority)
+                .await?
+                .into_iter()
+                .map(|(address, entry)| Entry::from_journal(address, entry))
+                .collect(),
+            Program::PdaOptimized => pda_optimized::fetch_entry_states_by_owner(&self.rpc, &authority)
+                .await?
+                .into_iter()
+                .map(|(address, entry)| Entry::from_pda_optimized(address, entry))
+                .collect(),
+        };
+        Ok(entries)
+    }
+
+    // None for a deleted or never created id
+    pub async fn show(&self, id: u64) -> Result<Option<Entry>> {
+        let authority = self.authority();
+        let entry = match self.program {
+            Program::Journal => self
+                .fetch_journal_entry(id)
+                .await?
+                .map(|entry| Entry::from_journal(pda::find_journal_entry_pda(&authority, id).0, entry)),
+            Program::PdaOptimized => pda_optimized::fetch_entry_state(&self.rpc, &authority, id)
+                .await?
+                .map(|entry| Entry::from_pda_optimized(pda::find_journal_entry_state_pda(&authority, id).0, entry)),
+        };
+        Ok(entry)
+    }
+
+    // Leaves the title or message as it is where None
+    pub async fn edit(&self, id: u64, title: Option<String>, message: Option<String>) -> Result<String> {
+        let authority = self.authority();
+        let Some(entry) = self.show(id).await? else {
+            bail!("entry {id} does not exist");
+        };
+        let title = title.unwrap_or(entry.title);
+        let message = message.unwrap_or(entry.message);
+        let instruction = match self.program {
+            Program::Journal => instructions::update_journal_entry(&authority, id, title, message),
+            Program::PdaOptimized => {
+                let edit_count = entry.edit_count.unwrap_or_default();
+                pda_optimized::update_journal_entry(&authority, id, edit_count, title, message)
+            }
+        };
+        self.send(&[instruction]).await
+    }
+
+    pub async fn delete(&self, id: u64) -> Result<String> {
+        let entry = self.show(id).await?.with_context(|| format!("entry {id} does not exist"))?;
+        self.send(&[self.delete_instruction(&entry)]).await
+    }
+
+    // Deletes `entry`, refunding its rent to whoever paid it
+    pub fn delete_instruction(&self, entry: &Entry) -> Instruction {
+        let authority = self.authority();
+        match self.program {
+            Program::Journal => {
+                let rent_payer = entry.rent_payer.unwrap_or(authority);
+                instructions::delete_journal_entry(&authority, entry.id, &rent_payer)
+            }
+            Program::PdaOptimized => pda_optimized::delete_journal_entry(&authority, entry.id),
+        }
+    }
+
+    // The id the next created entry gets
+    pub async fn next_id(&self) -> Result<u64> {
+        self.fetch_next_id().await?.context(NOT_INITIALIZED)
+    }
+
+    // None until init has run
+    pub async fn fetch_next_id(&self) -> Result<Option<u64>> {
+        let authority = self.authority();
+        let next_id = match self.program {
+            Program::Journal => self.reader.fetch_user_profile(&authority).await?.map(|profile| profile.entry_count),
+            Program::PdaOptimized => pda_optimized::fetch_user_journal_counter(&self.rpc, &authority)
+                .await?
+                .map(|counter| counter.last_entry_index),
+        };
+        Ok(next_id)
+    }
+
+    // Creates entry `id`, which must be next_id, or follow the creates before it in the same transaction
+    pub fn create_instruction(&self, id: u64, title: String, message: String) -> Instruction {
+        let authority = self.authority();
+        match self.program {
+            Program::Journal => instructions::add_journal_entry(&authority, &authority, id, title, message),
+            Program::PdaOptimized => pda_optimized::create_journal_entry(&authority, id, title, message, None),
+        }
+    }
+
+    // Sends `instructions` as one transaction, paid for and signed by the keypair
+    pub async fn send(&self, instructions: &[Instruction]) -> Result<String> {
+        let sent = self.sender.send(instructions, &self.authority(), &[&self.keypair]).await?;
+        Ok(landed(sent))
+    }
+
+    async fn fetch_journal_entry(&self, id: u64) -> Result<Option<JournalEntry>> {
+        Ok(self.reader.fetch_entries(&self.authority(), id..id + 1).await?.pop().flatten())
+    }
+}
+
+// The signature of a transaction that landed, after warning on stderr about the deprecated instructions it ran
+fn landed(sent: Sent) -> String {
+    for notice in &sent.deprecations {
+        eprintln!(
+            "warning: {} is deprecated and stops working in program version {}; use {} instead",
+            notice.instruction, notice.removed_in_version, notice.replacement
+        );
+    }
+    sent.outcome.signature().to_string()
+}
+
+// Whether `instructions` fit in one transaction as TransactionSender sends them, compute budget included
+pub fn fits(instructions: &[Instruction], payer: &Pubkey) -> bool {
+    let mut priced =
+        vec![ComputeBudgetInstruction::set_compute_unit_limit(0), ComputeBudgetInstruction::set_compute_unit_price(0)];
+    priced.extend_from_slice(instructions);
+    let transaction = offline::unsigned_transaction(&priced, payer, Hash::default());
+    bincode::serialized_size(&transaction).is_ok_and(|size| size <= PACKET_DATA_SIZE)
+}
+
+// The most a serialized transaction may be
+const PACKET_DATA_SIZE: u64 = 1232;
+
+const NOT_INITIALIZED: &str = "no journal for this keypair yet; run `journal init` first";
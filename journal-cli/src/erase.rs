@@ -0,0 +1,114 @@
+// `journal erase-all --confirm`: deletes every entry of the keypair's journal, as many per transaction as fit, and
+// writes a JSON report of which ids each transaction deleted and its signature. The report is rewritten after each
+// transaction confirms, so a run that fails partway still records what it erased; running it again deletes the
+// rest and adds to the same report.
+//
+// Deleting closes the entry accounts and refunds their rent. It does not reach the transactions that wrote them,
+// which stay in the ledger's history, or copies kept by explorers and other indexers. The CLI keeps no cache of
+// its own to purge.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_instruction::Instruction;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::client::{fits, Entry, JournalClient};
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Report {
+    authority: String,
+    transactions: Vec<Erased>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Erased {
+    signature: String,
+    ids: Vec<u64>,
+    confirmed_at: String, // RFC 3339, UTC, as this client saw it
+}
+
+// Returns how many entries were deleted. Without `confirm` nothing is sent.
+pub async fn erase_all(client: &JournalClient, report_path: &Path, confirm: bool) -> Result<usize> {
+    let entries = client.list().await?;
+    if entries.is_empty() {
+        println!("No entries to erase");
+        return Ok(0);
+    }
+    if !confirm {
+        bail!("this deletes all {} entries of {} for good; rerun with --confirm", entries.len(), client.authority());
+    }
+
+    let mut report = read_report(report_path, &client.authority().to_string())?;
+    let mut remaining = &entries[..];
+    while !remaining.is_empty() {
+        let batch = pack(client, remaining);
+        let (erased, rest) = remaining.split_at(batch.len());
+        let signature = client
+            .send(&batch)
+            .await
+            .with_context(|| format!("deleting entry {} failed; rerun erase-all to carry on after it", erased[0].id))?;
+        let ids: Vec<u64> = erased.iter().map(|entry| entry.id).collect();
+        println!("Deleted entries {ids:?} ({signature})");
+        let confirmed_at = OffsetDateTime::now_utc().format(&Rfc3339)?;
+        report.transactions.push(Erased { signature, ids, confirmed_at });
+        fs::write(report_path, serde_json::to_string_pretty(&report)? + "\n")
+            .with_context(|| format!("could not write {}", report_path.display()))?;
+        remaining = rest;
+    }
+    println!("Wrote the erasure report to {}", report_path.display());
+    Ok(entries.len())
+}
+
+// The delete instructions of as many of `entries`, from the first, as fit in one transaction
+fn pack(client: &JournalClient, entries: &[Entry]) -> Vec<Instruction> {
+    let mut batch = Vec::new();
+    for entry in entries {
+        batch.push(client.delete_instruction(entry));
+        if batch.len() > 1 && !fits(&batch, &client.authority()) {
+            batch.pop();
+            break;
+        }
+    }
+    batch
+}
+
+// The report an earlier run left at `path`, to add to, or a new one
+fn read_report(path: &Path, authority: &str) -> Result<Report> {
+    let json = match fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Ok(Report { authority: authority.to_string(), transactions: Vec::new() })
+        }
+        Err(err) => return Err(err).with_context(|| format!("could not read {}", path.display())),
+    };
+    let report: Report = serde_json::from_str(&json).with_context(|| format!("could not parse {}", path.display()))?;
+    if report.authority != authority {
+        bail!("{} is the report of {}; pass another --report", path.display(), report.authority);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reruns_add_to_the_report() {
+        let path = std::env::temp_dir().join(format!("journal-erasure-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut report = read_report(&path, "author").unwrap();
+        assert!(report.transactions.is_empty());
+
+        let erased = Erased { signature: "sig".into(), ids: vec![0, 1], confirmed_at: "1970-01-01T00:00:00Z".into() };
+        report.transactions.push(erased);
+        fs::write(&path, serde_json::to_string_pretty(&report).unwrap()).unwrap();
+        assert_eq!(read_report(&path, "author").unwrap(), report);
+        assert!(read_report(&path, "someone else").is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+// `journal export`: one file per entry, named by its zero-padded id so the files list in id order. Markdown files
+// are YAML frontmatter followed by the message; JSON files hold the same fields with the message alongside.
+// Deleted ids have no account, so they leave a gap in the numbering rather than an empty file.
+//
+// Neither program stores tags. The frontmatter still carries an empty `tags` list, so exported files can be
+// tagged by hand and every file has the same fields.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::client::Entry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Json,
+    Md,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Md => "md",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Frontmatter {
+    pub id: u64,
+    pub title: String,
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>, // RFC 3339, UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_public: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit_count: Option<u32>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+    #[serde(flatten)]
+    frontmatter: Frontmatter,
+    message: &'a str,
+}
+
+// Writes every entry into `out`, creating it if needed; returns how many files were written
+pub fn write_entries(entries: &[Entry], format: Format, out: &Path) -> Result<usize> {
+    fs::create_dir_all(out).with_context(|| format!("could not create {}", out.display()))?;
+    for entry in entries {
+        let contents = match format {
+            Format::Json => json(entry)?,
+            Format::Md => markdown(entry)?,
+        };
+        let path = out.join(format!("{:06}.{}", entry.id, format.extension()));
+        fs::write(&path, contents).with_context(|| format!("could not write {}", path.display()))?;
+    }
+    Ok(entries.len())
+}
+
+fn frontmatter(entry: &Entry) -> Result<Frontmatter> {
+    let updated_at = match entry.updated_at {
+        Some(seconds) => Some(OffsetDateTime::from_unix_timestamp(seconds)?.format(&Rfc3339)?),
+        None => None,
+    };
+    Ok(Frontmatter {
+        id: entry.id,
+        title: entry.title.clone(),
+        address: entry.address.to_string(),
+        updated_at,
+        is_public: entry.is_public,
+        edit_count: entry.edit_count,
+        tags: Vec::new(),
+    })
+}
+
+fn markdown(entry: &Entry) -> Result<String> {
+    let frontmatter = serde_yaml::to_string(&frontmatter(entry)?)?;
+    Ok(format!("---\n{frontmatter}---\n\n{}\n", entry.message))
+}
+
+fn json(entry: &Entry) -> Result<String> {
+    let entry = JsonEntry { frontmatter: frontmatter(entry)?, message: &entry.message };
+    Ok(serde_json::to_string_pretty(&entry)? + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_pubkey::Pubkey;
+
+    use super::*;
+
+    #[test]
+    fn markdown_layout() {
+        let entry = Entry {
+            id: 4,
+            address: Pubkey::new_unique(),
+            title: "Day: one".into(),
+            message: "Hello\n\nworld".into(),
+            updated_at: Some(86_400),
+            is_public: Some(false),
+            edit_count: None,
+            rent_payer: None,
+        };
+        let markdown = markdown(&entry).unwrap();
+        let (frontmatter, message) = markdown.strip_prefix("---\n").unwrap().split_once("---\n\n").unwrap();
+        assert_eq!(message, "Hello\n\nworld\n");
+        let frontmatter: Frontmatter = serde_yaml::from_str(frontmatter).unwrap();
+        assert_eq!(frontmatter.title, "Day: one");
+        assert_eq!(frontmatter.updated_at.as_deref(), Some("1970-01-02T00:00:00Z"));
+        assert_eq!(frontmatter.edit_count, None);
+        assert!(frontmatter.tags.is_empty());
+    }
+}
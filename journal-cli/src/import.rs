@@ -0,0 +1,329 @@
+// `journal import DIR`: one new entry per Markdown file under DIR, subdirectories included, in path order, so an
+// Obsidian vault or a `journal export --format md` directory can be loaded in one go. Frontmatter is optional;
+// its `title` is used when present, the file name otherwise, and every other key is ignored (imported entries get
+// new ids). Hidden files and directories, such as .obsidian, are skipped.
+//
+// Every file is read and checked against the program's length limits before anything is sent. Entries are then
+// created as many per transaction as fit, and once a transaction confirms, the files it created are appended to
+// the resume file. After a failure, running the same import again skips what the resume file lists.
+//
+// A transaction can also land after the client gave up waiting for it. So before each one is sent, the ids it
+// creates and the notes it creates them from are written to a pending file next to the resume file. The next run
+// reads the entries at those ids: if they hold those notes, the files are recorded as imported instead of being
+// created a second time.
+
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_instruction::Instruction;
+
+use crate::client::{fits, Entry, JournalClient, Program};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    pub path: PathBuf, // Relative to the imported directory; what the resume file records
+    pub title: String,
+    pub message: String,
+}
+
+// A transaction sent but not known to have confirmed: it creates `notes` as entries first_id, first_id + 1, ...
+#[derive(Serialize, Deserialize)]
+struct Pending {
+    first_id: u64,
+    notes: Vec<Note>,
+}
+
+#[derive(Deserialize)]
+struct Frontmatter {
+    title: Option<String>,
+}
+
+struct Limits {
+    title_chars: usize,
+    title_bytes: usize,
+    message_chars: usize,
+    message_bytes: usize,
+}
+
+// The journal program's are the default locale limits of its ProgramConfig, which an admin can change (and which
+// are halved for CJK profiles); the program has the last word. journal_pda_optimized sizes entries in bytes.
+fn limits(program: Program) -> Limits {
+    match program {
+        Program::Journal => Limits { title_chars: 50, title_bytes: 100, message_chars: 280, message_bytes: 560 },
+        Program::PdaOptimized => Limits { title_chars: 50, title_bytes: 50, message_chars: 280, message_bytes: 280 },
+    }
+}
+
+// Returns how many entries were created, or would be with `dry_run`
+pub async fn import(
+    client: &JournalClient,
+    program: Program,
+    dir: &Path,
+    resume: &Path,
+    dry_run: bool,
+) -> Result<usize> {
+    let pending_path = pending_path(resume);
+    if dry_run {
+        if pending_path.exists() {
+            println!("The last transaction of an earlier run may not have confirmed; a real run checks it first");
+        }
+    } else {
+        settle_pending(client, &pending_path, resume).await?;
+    }
+
+    let done = read_resume(resume)?;
+    let notes = read_notes(dir)?;
+    let skipped = notes.iter().filter(|note| done.contains(&note.path)).count();
+    let pending: Vec<Note> = notes.into_iter().filter(|note| !done.contains(&note.path)).collect();
+    if skipped > 0 {
+        println!("Skipping {skipped} files already imported (listed in {})", resume.display());
+    }
+
+    let problems: Vec<String> = pending.iter().flat_map(|note| problems(note, program)).collect();
+    if !problems.is_empty() {
+        bail!("{} problems, nothing imported:\n  {}", problems.len(), problems.join("\n  "));
+    }
+
+    if dry_run {
+        let mut remaining = &pending[..];
+        let mut transactions = 0;
+        while !remaining.is_empty() {
+            // Ids don't change a transaction's size, so any will do for packing
+            let batch = pack(client, remaining, 0)?;
+            transactions += 1;
+            println!("Transaction {transactions}:");
+            for note in &remaining[..batch.len()] {
+                println!("  {}  {}", note.path.display(), note.title);
+            }
+            remaining = &remaining[batch.len()..];
+        }
+        println!("Would create {} entries in {transactions} transactions", pending.len());
+        return Ok(pending.len());
+    }
+
+    let mut resume_file = open_resume(resume)?;
+    let mut remaining = &pending[..];
+    while !remaining.is_empty() {
+        // Read for every transaction, since the previous one moved it and other writers may have too
+        let first_id = client.next_id().await?;
+        let batch = pack(client, remaining, first_id)?;
+        let (created, rest) = remaining.split_at(batch.len());
+        let unconfirmed = Pending { first_id, notes: created.to_vec() };
+        fs::write(&pending_path, serde_json::to_string(&unconfirmed)?)
+            .with_context(|| format!("could not write {}", pending_path.display()))?;
+        let signature = client.send(&batch).await.with_context(|| {
+            format!("creating {} failed; rerun the import to carry on after it", created[0].path.display())
+        })?;
+        record_imported(&mut resume_file, created)?;
+        fs::remove_file(&pending_path)?;
+        let last_id = first_id + created.len() as u64 - 1;
+        println!("Created entries {first_id}..={last_id} from {} files ({signature})", created.len());
+        remaining = rest;
+    }
+    Ok(pending.len())
+}
+
+// Settles the transaction a previous run sent without seeing it confirm. Its entries either all exist or none do;
+// if they do, their files are recorded as imported. Either way the pending file is removed.
+async fn settle_pending(client: &JournalClient, pending_path: &Path, resume: &Path) -> Result<()> {
+    let json = match fs::read_to_string(pending_path) {
+        Ok(json) => json,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err).with_context(|| format!("could not read {}", pending_path.display())),
+    };
+    let Pending { first_id, notes } =
+        serde_json::from_str(&json).with_context(|| format!("could not parse {}", pending_path.display()))?;
+    let mut entries = Vec::with_capacity(notes.len());
+    for id in (first_id..).take(notes.len()) {
+        entries.push(client.show(id).await?);
+    }
+    if created_from(&entries, &notes) {
+        record_imported(&mut open_resume(resume)?, &notes)?;
+        let last_id = first_id + notes.len() as u64 - 1;
+        println!("Entries {first_id}..={last_id} from the last run were created after all; not creating them again");
+    }
+    fs::remove_file(pending_path).with_context(|| format!("could not remove {}", pending_path.display()))
+}
+
+// Whether `entries`, read at a pending transaction's ids, are the ones it created from `notes`
+fn created_from(entries: &[Option<Entry>], notes: &[Note]) -> bool {
+    entries.len() == notes.len()
+        && entries.iter().zip(notes).all(|(entry, note)| {
+            entry.as_ref().is_some_and(|entry| entry.title == note.title && entry.message == note.message)
+        })
+}
+
+fn open_resume(resume: &Path) -> Result<fs::File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(resume)
+        .with_context(|| format!("could not open {}", resume.display()))
+}
+
+fn record_imported(resume_file: &mut fs::File, notes: &[Note]) -> Result<()> {
+    for note in notes {
+        writeln!(resume_file, "{}", note.path.display())?;
+    }
+    resume_file.flush()?;
+    Ok(())
+}
+
+// The pending file for `resume`: the same name with .pending appended
+fn pending_path(resume: &Path) -> PathBuf {
+    let mut path = resume.as_os_str().to_owned();
+    path.push(".pending");
+    path.into()
+}
+
+// The create instructions of as many of `notes`, from the first, as fit in one transaction
+fn pack(client: &JournalClient, notes: &[Note], first_id: u64) -> Result<Vec<Instruction>> {
+    let mut batch = Vec::new();
+    for (id, note) in (first_id..).zip(notes) {
+        batch.push(client.create_instruction(id, note.title.clone(), note.message.clone()));
+        if !fits(&batch, &client.authority()) {
+            batch.pop();
+            break;
+        }
+    }
+    if batch.is_empty() {
+        bail!("{} is too large for a transaction", notes[0].path.display());
+    }
+    Ok(batch)
+}
+
+fn problems(note: &Note, program: Program) -> Vec<String> {
+    let limits = limits(program);
+    let path = note.path.display();
+    let mut problems = Vec::new();
+    for (field, text, max_chars, max_bytes) in [
+        ("title", &note.title, limits.title_chars, limits.title_bytes),
+        ("message", &note.message, limits.message_chars, limits.message_bytes),
+    ] {
+        let chars = text.chars().count();
+        if chars > max_chars {
+            problems.push(format!("{path}: the {field} is {chars} characters; at most {max_chars} fit"));
+        } else if text.len() > max_bytes {
+            problems.push(format!("{path}: the {field} is {} bytes; at most {max_bytes} fit", text.len()));
+        }
+    }
+    if note.title.trim().is_empty() {
+        problems.push(format!("{path}: the title is empty"));
+    }
+    problems
+}
+
+pub fn read_notes(dir: &Path) -> Result<Vec<Note>> {
+    let mut paths = Vec::new();
+    markdown_files(dir, Path::new(""), &mut paths).with_context(|| format!("could not read {}", dir.display()))?;
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let text =
+                fs::read_to_string(dir.join(&path)).with_context(|| format!("could not read {}", path.display()))?;
+            parse_note(path, &text)
+        })
+        .collect()
+}
+
+fn markdown_files(dir: &Path, relative: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for item in fs::read_dir(dir.join(relative))? {
+        let item = item?;
+        let name = item.file_name();
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = relative.join(&name);
+        if item.file_type()?.is_dir() {
+            markdown_files(dir, &path, paths)?;
+        } else if path.extension().is_some_and(|extension| extension == "md") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+pub fn parse_note(path: PathBuf, text: &str) -> Result<Note> {
+    let text = text.replace("\r\n", "\n");
+    let (frontmatter, body) = match text.strip_prefix("---\n").and_then(|rest| rest.split_once("\n---\n")) {
+        Some((yaml, body)) => {
+            let frontmatter: Option<Frontmatter> =
+                serde_yaml::from_str(yaml).with_context(|| format!("{}: bad frontmatter", path.display()))?;
+            (frontmatter, body)
+        }
+        None => (None, text.as_str()),
+    };
+    let title = match frontmatter.and_then(|frontmatter| frontmatter.title) {
+        Some(title) => title,
+        None => path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+    };
+    let message = body.trim_start_matches('\n').trim_end().to_string();
+    Ok(Note { path, title, message })
+}
+
+fn read_resume(path: &Path) -> Result<HashSet<PathBuf>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(text.lines().filter(|line| !line.is_empty()).map(PathBuf::from).collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(err) => Err(err).with_context(|| format!("could not read {}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_pubkey::Pubkey;
+
+    use super::*;
+
+    #[test]
+    fn frontmatter_title_or_file_name() {
+        let note =
+            parse_note("a/Day one.md".into(), "---\ntitle: 'Day: one'\ntags: [x]\n---\n\nHello\n\nworld\n").unwrap();
+        assert_eq!((note.title.as_str(), note.message.as_str()), ("Day: one", "Hello\n\nworld"));
+
+        let note = parse_note("a/Day one.md".into(), "Hello\r\n").unwrap();
+        assert_eq!((note.title.as_str(), note.message.as_str()), ("Day one", "Hello"));
+
+        let note = parse_note("b.md".into(), "---\naliases: []\n---\nHello").unwrap();
+        assert_eq!(note.title, "b");
+    }
+
+    #[test]
+    fn pending_entries_match_their_notes() {
+        let notes = vec![
+            Note { path: "a.md".into(), title: "A".into(), message: "first".into() },
+            Note { path: "b.md".into(), title: "B".into(), message: "second".into() },
+        ];
+        let entry = |id, note: &Note| Entry {
+            id,
+            address: Pubkey::new_unique(),
+            title: note.title.clone(),
+            message: note.message.clone(),
+            updated_at: None,
+            is_public: None,
+            edit_count: None,
+            rent_payer: None,
+        };
+        let landed = vec![Some(entry(3, &notes[0])), Some(entry(4, &notes[1]))];
+        assert!(created_from(&landed, &notes));
+
+        // Never landed, or another writer took the ids first
+        assert!(!created_from(&[None, None], &notes));
+        let other = Note { message: "someone else's".into(), ..notes[1].clone() };
+        assert!(!created_from(&[Some(entry(3, &notes[0])), Some(entry(4, &other))], &notes));
+
+        assert_eq!(pending_path(Path::new("vault/.journal-import")), Path::new("vault/.journal-import.pending"));
+    }
+
+    #[test]
+    fn lengths_per_program() {
+        let note = Note { path: "n.md".into(), title: "é".repeat(30), message: "m".into() };
+        assert!(problems(&note, Program::Journal).is_empty());
+        assert_eq!(problems(&note, Program::PdaOptimized).len(), 1);
+    }
+}
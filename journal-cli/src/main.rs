@@ -0,0 +1,208 @@
+// journal: a command-line client for the journal program (project-1) and journal_pda_optimized (project-3).
+//
+//   journal init
+//   journal add "A title" "The message"
+//   journal list
+//   journal query "tag:travel AND after:2024-01-01 AND words>200"
+//   journal show 3
+//   journal edit 3 --message "A better message"
+//   journal delete 3
+//   journal export --format md --out backup
+//   journal import ~/vault --dry-run
+//   journal erase-all --confirm
+//   journal --url devnet quickstart
+//
+// --url takes an RPC URL or a cluster name; --keypair is the journal's authority, which also pays. Ids are the
+// entry numbers each program assigns, from 0.
+
+mod client;
+mod erase;
+mod export;
+mod import;
+mod query;
+mod quickstart;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use solana_commitment_config::CommitmentConfig;
+use solana_keypair::read_keypair_file;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+
+use crate::client::{Entry, JournalClient, Program};
+use crate::export::Format;
+use crate::query::Query;
+
+#[derive(Parser)]
+#[command(name = "journal", version, about = "Write and read journal entries on Solana")]
+struct Cli {
+    /// RPC URL, or localhost, devnet, testnet or mainnet-beta
+    #[arg(long, short = 'u', env = "JOURNAL_URL", default_value = "localhost", global = true)]
+    url: String,
+    /// Keypair of the journal's authority [default: ~/.config/solana/id.json]
+    #[arg(long, short = 'k', env = "JOURNAL_KEYPAIR", global = true)]
+    keypair: Option<PathBuf>,
+    #[arg(long, value_enum, default_value = "journal", global = true)]
+    program: Program,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the account that numbers your entries; run once per keypair
+    Init,
+    /// Write a new entry
+    Add { title: String, message: String },
+    /// List your entries
+    List,
+    /// List your entries that match a filter, e.g. "tag:travel AND after:2024-01-01 AND words>200"
+    Query { filter: String },
+    /// Print one entry
+    Show { id: u64 },
+    /// Change an entry's title, message or both
+    Edit {
+        id: u64,
+        #[arg(long)]
+        title: Option<String>,
+        #[arg(long)]
+        message: Option<String>,
+    },
+    /// Delete an entry and get its rent back
+    Delete { id: u64 },
+    /// Write every entry to its own file
+    Export {
+        #[arg(long, value_enum, default_value = "md")]
+        format: Format,
+        /// Directory to write into; created if missing
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Create an entry from each Markdown file in a directory and its subdirectories
+    Import {
+        dir: PathBuf,
+        /// Check the files and show the transactions without sending anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Lists the files already imported, which later runs skip [default: DIR/.journal-import]
+        #[arg(long)]
+        resume: Option<PathBuf>,
+    },
+    /// Create a keypair, fund it from the faucet, initialize its journal and write a first entry
+    Quickstart,
+    /// Delete every entry and write a report of the transactions that did it
+    EraseAll {
+        /// Required; without it nothing is deleted
+        #[arg(long)]
+        confirm: bool,
+        /// Where to write the report; a rerun adds to it
+        #[arg(long, default_value = "journal-erasure.json")]
+        report: PathBuf,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let keypair_path = match cli.keypair {
+        Some(path) => path,
+        None => default_keypair_path()?,
+    };
+    if matches!(cli.command, Command::Quickstart) {
+        quickstart::ensure_keypair(&keypair_path)?;
+    }
+    let keypair = read_keypair_file(&keypair_path)
+        .map_err(|err| anyhow!("could not read keypair {}: {err}", keypair_path.display()))?;
+    let rpc = Arc::new(RpcClient::new_with_commitment(cluster_url(&cli.url), CommitmentConfig::confirmed()));
+    let client = JournalClient::new(cli.program, rpc.clone(), keypair);
+
+    match cli.command {
+        Command::Init => {
+            let signature = client.init().await?;
+            println!("Initialized the journal of {} ({signature})", client.authority());
+        }
+        Command::Add { title, message } => {
+            let (id, signature) = client.add(&title, &message).await?;
+            println!("Added entry {id} ({signature})");
+        }
+        Command::List => {
+            for entry in client.list().await? {
+                println!("{:>6}  {}", entry.id, entry.title);
+            }
+        }
+        Command::Query { filter } => {
+            let query = Query::parse(&filter)?;
+            for entry in client.list().await?.iter().filter(|entry| query.matches(entry)) {
+                println!("{:>6}  {}", entry.id, entry.title);
+            }
+        }
+        Command::Show { id } => match client.show(id).await? {
+            Some(entry) => print_entry(&entry),
+            None => println!("Entry {id} does not exist"),
+        },
+        Command::Edit { id, title, message } => {
+            if title.is_none() && message.is_none() {
+                return Err(anyhow!("nothing to change; pass --title, --message or both"));
+            }
+            let signature = client.edit(id, title, message).await?;
+            println!("Updated entry {id} ({signature})");
+        }
+        Command::Delete { id } => {
+            let signature = client.delete(id).await?;
+            println!("Deleted entry {id} ({signature})");
+        }
+        Command::Export { format, out } => {
+            let written = export::write_entries(&client.list().await?, format, &out)?;
+            println!("Exported {written} entries to {}", out.display());
+        }
+        Command::Import { dir, dry_run, resume } => {
+            let resume = resume.unwrap_or_else(|| dir.join(".journal-import"));
+            let created = import::import(&client, cli.program, &dir, &resume, dry_run).await?;
+            if !dry_run {
+                println!("Imported {created} entries");
+            }
+        }
+        Command::Quickstart => quickstart::quickstart(&client, &rpc).await?,
+        Command::EraseAll { confirm, report } => {
+            let erased = erase::erase_all(&client, &report, confirm).await?;
+            println!("Erased {erased} entries");
+        }
+    }
+    Ok(())
+}
+
+fn print_entry(entry: &Entry) {
+    println!("id:       {}", entry.id);
+    println!("address:  {}", entry.address);
+    if let Some(updated_at) = entry.updated_at {
+        println!("updated:  {updated_at}");
+    }
+    if let Some(is_public) = entry.is_public {
+        println!("public:   {is_public}");
+    }
+    if let Some(edit_count) = entry.edit_count {
+        println!("edits:    {edit_count}");
+    }
+    println!("title:    {}", entry.title);
+    println!();
+    println!("{}", entry.message);
+}
+
+// The Solana CLI's cluster names, and its short forms of them
+fn cluster_url(url: &str) -> String {
+    match url {
+        "localhost" | "l" => "http://127.0.0.1:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "testnet" | "t" => "https://api.testnet.solana.com",
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        url => url,
+    }
+    .to_string()
+}
+
+fn default_keypair_path() -> Result<PathBuf> {
+    let home = std::env::var_os("HOME").ok_or_else(|| anyhow!("HOME is not set; pass --keypair"))?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
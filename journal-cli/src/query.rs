@@ -0,0 +1,309 @@
+// `journal query "tag:travel AND after:2024-01-01 AND words>200"`: the entries that match a filter, read the same
+// way as `journal list`. A filter is terms joined by AND, OR and NOT, with parentheses to group them; NOT binds
+// tightest, then AND, then OR, and terms side by side are ANDed. Terms are:
+//
+//   tag:travel         a #travel hashtag in the title or message (neither program stores tags; hashtags stand in)
+//   title:paris        the title contains "paris"
+//   after:2024-01-01   last written after that day, UTC
+//   before:2024-01-01  last written before that day, UTC
+//   words>200          the message has more than 200 words; also >=, <, <= and =
+//   public:true        the entry is public (or false: private)
+//   lisbon             the title or message contains "lisbon"
+//
+// Text comparisons ignore case, and double quotes keep spaces in a term: title:"day one". journal_pda_optimized
+// entries record neither when they were written nor whether they are public, so dates and public: never match them.
+
+use std::ops::Range;
+
+use anyhow::{bail, Context, Result};
+use time::{Date, Month};
+
+use crate::client::Entry;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Term(Term),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Term {
+    Text(String), // Lowercased, like the other text terms
+    Tag(String),
+    Title(String),
+    After(i64),  // Unix seconds: written at or after
+    Before(i64), // Unix seconds: written before
+    Words(Range<usize>),
+    Public(bool),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Term(String),
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Query> {
+        let mut parser = Parser { tokens: tokenize(query)?, position: 0 };
+        let parsed = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.position) {
+            bail!("unexpected {} in {query:?}", token.text());
+        }
+        Ok(parsed)
+    }
+
+    pub fn matches(&self, entry: &Entry) -> bool {
+        match self {
+            Query::And(left, right) => left.matches(entry) && right.matches(entry),
+            Query::Or(left, right) => left.matches(entry) || right.matches(entry),
+            Query::Not(query) => !query.matches(entry),
+            Query::Term(term) => term.matches(entry),
+        }
+    }
+}
+
+impl Term {
+    fn matches(&self, entry: &Entry) -> bool {
+        match self {
+            Term::Text(text) => {
+                entry.title.to_lowercase().contains(text) || entry.message.to_lowercase().contains(text)
+            }
+            Term::Tag(tag) => hashtags(&entry.title).chain(hashtags(&entry.message)).any(|found| found == *tag),
+            Term::Title(text) => entry.title.to_lowercase().contains(text),
+            Term::After(since) => entry.updated_at.is_some_and(|updated_at| updated_at >= *since),
+            Term::Before(until) => entry.updated_at.is_some_and(|updated_at| updated_at < *until),
+            Term::Words(range) => range.contains(&entry.message.split_whitespace().count()),
+            Term::Public(is_public) => entry.is_public == Some(*is_public),
+        }
+    }
+
+    fn parse(word: &str) -> Result<Term> {
+        if let Some(comparison) = word.strip_prefix("words") {
+            return Ok(Term::Words(word_range(comparison)?));
+        }
+        let Some((key, value)) = word.split_once(':') else {
+            return Ok(Term::Text(word.to_lowercase()));
+        };
+        let term = match key {
+            "tag" => Term::Tag(value.trim_start_matches('#').to_lowercase()),
+            "title" => Term::Title(value.to_lowercase()),
+            "after" => Term::After(day_start(value)? + SECONDS_PER_DAY),
+            "before" => Term::Before(day_start(value)?),
+            "public" => Term::Public(value.parse().with_context(|| format!("public:{value}: expected true or false"))?),
+            _ => bail!("unknown filter {key}:; the filters are tag:, title:, after:, before:, public: and words"),
+        };
+        Ok(term)
+    }
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match self {
+            Token::Open => "(",
+            Token::Close => ")",
+            Token::And => "AND",
+            Token::Or => "OR",
+            Token::Not => "NOT",
+            Token::Term(word) => word,
+        }
+    }
+}
+
+// Recursive descent, one function per precedence level
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn or(&mut self) -> Result<Query> {
+        let mut query = self.and()?;
+        while self.eat(&Token::Or) {
+            query = Query::Or(Box::new(query), Box::new(self.and()?));
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Query> {
+        let mut query = self.not()?;
+        loop {
+            let implicit = matches!(self.tokens.get(self.position), Some(Token::Open | Token::Not | Token::Term(_)));
+            if !self.eat(&Token::And) && !implicit {
+                return Ok(query);
+            }
+            query = Query::And(Box::new(query), Box::new(self.not()?));
+        }
+    }
+
+    fn not(&mut self) -> Result<Query> {
+        if self.eat(&Token::Not) {
+            return Ok(Query::Not(Box::new(self.not()?)));
+        }
+        match self.tokens.get(self.position) {
+            Some(Token::Open) => {
+                self.position += 1;
+                let query = self.or()?;
+                if !self.eat(&Token::Close) {
+                    bail!("a ( is never closed");
+                }
+                Ok(query)
+            }
+            Some(Token::Term(word)) => {
+                let term = Term::parse(word)?;
+                self.position += 1;
+                Ok(Query::Term(term))
+            }
+            Some(token) => bail!("expected a term before {}", token.text()),
+            None => bail!("the query ends where a term should be"),
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.tokens.get(self.position) == Some(token);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '(' | ')' => {
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+                chars.next();
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            _ => {
+                let mut word = String::new();
+                let mut quoted = false;
+                while let Some(&c) = chars.peek() {
+                    if !quoted && (c.is_whitespace() || c == '(' || c == ')') {
+                        break;
+                    }
+                    if c == '"' {
+                        quoted = !quoted;
+                    } else {
+                        word.push(c);
+                    }
+                    chars.next();
+                }
+                if quoted {
+                    bail!("a \" is never closed");
+                }
+                tokens.push(match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Term(word),
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// The word counts `comparison` (what follows "words", e.g. ">200") accepts
+fn word_range(comparison: &str) -> Result<Range<usize>> {
+    let operator_len = comparison.chars().take_while(|c| "<>=".contains(*c)).count();
+    let (operator, count) = comparison.split_at(operator_len);
+    let count: usize = count.parse().with_context(|| format!("words{comparison}: expected a number of words"))?;
+    let range = match operator {
+        ">" => count.saturating_add(1)..usize::MAX,
+        ">=" => count..usize::MAX,
+        "<" => 0..count,
+        "<=" => 0..count.saturating_add(1),
+        "=" => count..count.saturating_add(1),
+        _ => bail!("words{comparison}: compare with >, >=, <, <= or ="),
+    };
+    Ok(range)
+}
+
+// Unix seconds at the start of `date`, a YYYY-MM-DD day in UTC
+fn day_start(date: &str) -> Result<i64> {
+    let parse = || {
+        let mut parts = date.split('-');
+        let year = parts.next()?.parse().ok()?;
+        let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+        let day = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Date::from_calendar_date(year, month, day).ok()?.midnight().assume_utc().unix_timestamp())
+    };
+    parse().with_context(|| format!("{date} is not a date; write it as YYYY-MM-DD"))
+}
+
+// The #hashtags in `text`, lowercased and without the # or trailing punctuation
+fn hashtags(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .map(|tag| tag.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '-').to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_pubkey::Pubkey;
+
+    use super::*;
+
+    fn entry(title: &str, message: &str, updated_at: i64) -> Entry {
+        Entry {
+            id: 0,
+            address: Pubkey::new_unique(),
+            title: title.into(),
+            message: message.into(),
+            updated_at: Some(updated_at),
+            is_public: Some(false),
+            edit_count: None,
+            rent_payer: None,
+        }
+    }
+
+    #[test]
+    fn precedence_and_grouping() {
+        let term = |word| Box::new(Query::Term(Term::Text(String::from(word))));
+        assert_eq!(
+            Query::parse("a b OR NOT c").unwrap(),
+            Query::Or(Box::new(Query::And(term("a"), term("b"))), Box::new(Query::Not(term("c"))))
+        );
+        assert_eq!(
+            Query::parse("a AND (b OR c)").unwrap(),
+            Query::And(term("a"), Box::new(Query::Or(term("b"), term("c"))))
+        );
+        assert_eq!(Query::parse("title:\"Day One\"").unwrap(), Query::Term(Term::Title("day one".into())));
+
+        for bad in ["a AND", "(a", "a)", "OR a", "words~3", "after:2024-13-01", "mood:good", "\"a"] {
+            assert!(Query::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn filters_entries() {
+        let query = Query::parse("tag:travel AND after:2024-01-01 AND words>2").unwrap();
+        let jan_2 = day_start("2024-01-02").unwrap();
+        assert!(query.matches(&entry("Lisbon", "Three whole words #Travel.", jan_2)));
+        assert!(!query.matches(&entry("Lisbon", "Three whole words #Travel.", jan_2 - 1)));
+        assert!(!query.matches(&entry("Lisbon", "Short #travel", jan_2)));
+        assert!(!query.matches(&entry("Lisbon", "Three words #travelling", jan_2)));
+
+        let query = Query::parse("before:2024-01-01 OR public:true OR words=0").unwrap();
+        assert!(query.matches(&entry("Old", "a b", day_start("2023-12-31").unwrap())));
+        assert!(query.matches(&entry("Empty", "", jan_2)));
+        assert!(!query.matches(&entry("New", "a b", jan_2)));
+    }
+}
@@ -0,0 +1,102 @@
+// `journal quickstart`: everything between installing the CLI and a first entry, for trying it on devnet
+// (`journal --url devnet quickstart`). Each step is skipped when it's already done, so a failed run can be
+// rerun, and a keypair that already has a journal only gets a balance check:
+//
+//   1. create the keypair file, if there's none
+//   2. airdrop SOL while the balance is under MIN_BALANCE. Faucets rate-limit and sometimes drop requests, so a
+//      refused or unconfirmed airdrop is retried, waiting twice as long before each retry
+//   3. `journal init`, if the journal doesn't exist
+//   4. add a first entry, if the journal has none
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use solana_keypair::{write_keypair_file, Keypair};
+use solana_pubkey::Pubkey;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_signer::Signer;
+use tokio::time::sleep;
+
+use crate::client::JournalClient;
+
+const MIN_BALANCE: u64 = 50_000_000; // 0.05 SOL: the profile's rent, a few entries' and the fees
+const AIRDROP_LAMPORTS: u64 = 1_000_000_000;
+const AIRDROP_ATTEMPTS: u32 = 5;
+const FIRST_BACKOFF: Duration = Duration::from_secs(2);
+const CONFIRM_SECONDS: u32 = 30;
+
+const FIRST_TITLE: &str = "Hello, journal";
+const FIRST_MESSAGE: &str = "My first entry, written by `journal quickstart`.";
+
+// Writes a new keypair to `path` unless there's a file there already
+pub fn ensure_keypair(path: &Path) -> Result<()> {
+    if path.exists() {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let keypair = Keypair::new();
+    write_keypair_file(&keypair, path).map_err(|err| anyhow!("could not write {}: {err}", path.display()))?;
+    println!("Created keypair {} in {}", keypair.pubkey(), path.display());
+    Ok(())
+}
+
+pub async fn quickstart(client: &JournalClient, rpc: &RpcClient) -> Result<()> {
+    let authority = client.authority();
+    if rpc.get_balance(&authority).await? < MIN_BALANCE {
+        airdrop(rpc, &authority).await?;
+    }
+    if client.fetch_next_id().await?.is_none() {
+        let signature = client.init().await?;
+        println!("Initialized the journal of {authority} ({signature})");
+    }
+    if client.next_id().await? == 0 {
+        let (id, signature) = client.add(FIRST_TITLE, FIRST_MESSAGE).await?;
+        println!("Added entry {id} ({signature})");
+    }
+    println!("Ready; `journal list` with the same --url shows your entries");
+    Ok(())
+}
+
+async fn airdrop(rpc: &RpcClient, to: &Pubkey) -> Result<()> {
+    let mut attempt = 1;
+    loop {
+        match request_airdrop(rpc, to).await {
+            Ok(signature) => {
+                println!("Airdropped {} SOL to {to} ({signature})", AIRDROP_LAMPORTS / 1_000_000_000);
+                return Ok(());
+            }
+            Err(err) if attempt == AIRDROP_ATTEMPTS => {
+                return Err(err.context(format!(
+                    "the airdrop failed {AIRDROP_ATTEMPTS} times; fund {to} another way, e.g. at \
+                     https://faucet.solana.com, then rerun quickstart"
+                )));
+            }
+            Err(err) => {
+                let backoff = FIRST_BACKOFF * 2u32.pow(attempt - 1);
+                println!("Airdrop attempt {attempt} failed ({err:#}); retrying in {}s", backoff.as_secs());
+                sleep(backoff).await;
+                // An airdrop that didn't confirm in time may still have landed
+                if rpc.get_balance(to).await? >= MIN_BALANCE {
+                    return Ok(());
+                }
+                attempt += 1;
+            }
+        }
+    }
+}
+
+// Requests an airdrop and waits for it to confirm; returns its signature
+async fn request_airdrop(rpc: &RpcClient, to: &Pubkey) -> Result<String> {
+    let signature = rpc.request_airdrop(to, AIRDROP_LAMPORTS).await?;
+    for _ in 0..CONFIRM_SECONDS {
+        if rpc.confirm_transaction(&signature).await? {
+            return Ok(signature.to_string());
+        }
+        sleep(Duration::from_secs(1)).await;
+    }
+    bail!("airdrop {signature} did not confirm within {CONFIRM_SECONDS}s")
+}
@@ -0,0 +1,24 @@
+// Typed bindings for the journal program (project-1), generated by declare_program! from idls/journal.json.
+// `accounts` holds the account types, which decode with AccountDeserialize, `events` the emit! payloads and
+// `constants` the program's #[constant]s, PROGRAM_VERSION among them;
+// `client::accounts` and `client::args` are the accounts and arguments of each instruction, which `instruction`
+// turns into an Instruction ready to sign and send.
+//
+// idls/journal.json is the IDL `anchor build` writes to project-1/anchor/target/idl/journal.json. Copy it over
+// whenever the program's instructions or accounts change.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+declare_program!(journal);
+
+pub use journal::{accounts, client, constants, events, program::JournalProgram, types, ID};
+
+pub fn instruction(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
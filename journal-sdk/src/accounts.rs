@@ -0,0 +1,70 @@
+// Decoders for journal program account data as returned by getAccountInfo or getProgramAccounts. They read
+// the current layout only; entries and profiles still in an older one fail to decode until migrated.
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::borsh;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+pub use journal_client::accounts::{JournalEntry, KeyEnvelope, ProgramConfig, ReactionTally, UserProfile, ViewTally};
+use solana_pubkey::Pubkey;
+
+// Bytes of an entry that hold an EntrySummary, however long its title: discriminator, authority, id, version,
+// then a title of up to 100 bytes with its length prefix
+pub const ENTRY_SUMMARY_LEN: usize = 8 + 32 + 8 + 1 + 4 + 100;
+
+// The leading fields of a JournalEntry, for list views that fetch only the first ENTRY_SUMMARY_LEN bytes of each
+#[derive(Debug, Clone, PartialEq, Eq, AnchorDeserialize)]
+pub struct EntrySummary {
+    pub authority: Pubkey,
+    pub id: u64,
+    pub version: u8,
+    pub title: String,
+}
+
+pub fn journal_entry(data: &[u8]) -> anchor_lang::Result<JournalEntry> {
+    JournalEntry::try_deserialize(&mut &data[..])
+}
+
+pub fn program_config(data: &[u8]) -> anchor_lang::Result<ProgramConfig> {
+    ProgramConfig::try_deserialize(&mut &data[..])
+}
+
+pub fn user_profile(data: &[u8]) -> anchor_lang::Result<UserProfile> {
+    UserProfile::try_deserialize(&mut &data[..])
+}
+
+pub fn view_tally(data: &[u8]) -> anchor_lang::Result<ViewTally> {
+    ViewTally::try_deserialize(&mut &data[..])
+}
+
+pub fn reaction_tally(data: &[u8]) -> anchor_lang::Result<ReactionTally> {
+    ReactionTally::try_deserialize(&mut &data[..])
+}
+
+pub fn key_envelope(data: &[u8]) -> anchor_lang::Result<KeyEnvelope> {
+    KeyEnvelope::try_deserialize(&mut &data[..])
+}
+
+// From a full entry or its first ENTRY_SUMMARY_LEN bytes
+pub fn entry_summary(data: &[u8]) -> anchor_lang::Result<EntrySummary> {
+    let body = data.strip_prefix(JournalEntry::DISCRIMINATOR).ok_or(ErrorCode::AccountDiscriminatorMismatch)?;
+    EntrySummary::deserialize(&mut &body[..]).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_from_a_slice() {
+        let authority = Pubkey::new_unique();
+        let mut data = JournalEntry::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(authority.as_ref());
+        data.extend_from_slice(&4u64.to_le_bytes());
+        data.push(3);
+        data.extend_from_slice(&5u32.to_le_bytes());
+        data.extend_from_slice(b"hello");
+        // Whatever follows the title in the slice is ignored
+        data.resize(ENTRY_SUMMARY_LEN, 0);
+        assert_eq!(entry_summary(&data).unwrap(), EntrySummary { authority, id: 4, version: 3, title: "hello".into() });
+    }
+}
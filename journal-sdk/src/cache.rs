@@ -0,0 +1,215 @@
+// A read cache for JournalReader, keyed by account address, so views that re-read the same profile and its
+// entries don't fetch hundreds of unchanged accounts every time. Each cached account remembers the slot it was
+// read at. It is served until the cluster is max_age_slots past that slot; the reader reads the current slot
+// once per call, which costs one getSlot where it saves a getMultipleAccounts per hundred accounts. Feeding the
+// cache journal events (`apply_event`) drops what they touch right away, so only changes that emit no event,
+// such as tips and visibility, wait out the age.
+//
+// An event that arrives while the account it touches is being fetched can be overtaken by that fetch; the stale
+// copy then lives out its max_age_slots like any other.
+//
+// The store is pluggable: MemoryStore for one process, SledStore (feature "sled") to keep the cache on disk
+// across restarts.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use solana_pubkey::Pubkey;
+
+use crate::events::{EventNotification, JournalEvent};
+use crate::pda::find_user_profile_pda;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedAccount {
+    pub slot: u64,
+    pub data: Option<Vec<u8>>, // None: there was no account at `slot`, which is worth remembering too
+}
+
+// Stores are best effort: one that can't read or write an account treats it as not cached
+pub trait CacheStore: Send + Sync {
+    fn get(&self, address: &Pubkey) -> Option<CachedAccount>;
+    fn insert(&self, address: &Pubkey, account: CachedAccount);
+    fn remove(&self, address: &Pubkey);
+    fn clear(&self);
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    accounts: Mutex<HashMap<Pubkey, CachedAccount>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, address: &Pubkey) -> Option<CachedAccount> {
+        self.accounts.lock().unwrap().get(address).cloned()
+    }
+
+    fn insert(&self, address: &Pubkey, account: CachedAccount) {
+        self.accounts.lock().unwrap().insert(*address, account);
+    }
+
+    fn remove(&self, address: &Pubkey) {
+        self.accounts.lock().unwrap().remove(address);
+    }
+
+    fn clear(&self) {
+        self.accounts.lock().unwrap().clear();
+    }
+}
+
+#[cfg(feature = "sled")]
+pub use sled_store::SledStore;
+
+#[cfg(feature = "sled")]
+mod sled_store {
+    use solana_pubkey::Pubkey;
+
+    use super::{CacheStore, CachedAccount};
+
+    // Values are the slot (little-endian u64), a byte that is 1 when the account existed, then its data
+    pub struct SledStore {
+        tree: sled::Tree,
+    }
+
+    impl SledStore {
+        pub fn new(tree: sled::Tree) -> Self {
+            SledStore { tree }
+        }
+
+        pub fn open(path: impl AsRef<std::path::Path>) -> sled::Result<Self> {
+            Ok(SledStore::new(sled::open(path)?.open_tree("journal_accounts")?))
+        }
+    }
+
+    impl CacheStore for SledStore {
+        fn get(&self, address: &Pubkey) -> Option<CachedAccount> {
+            let value = self.tree.get(address).ok()??;
+            let (slot, rest) = value.split_first_chunk::<8>()?;
+            let (exists, data) = rest.split_first()?;
+            Some(CachedAccount { slot: u64::from_le_bytes(*slot), data: (*exists == 1).then(|| data.to_vec()) })
+        }
+
+        fn insert(&self, address: &Pubkey, account: CachedAccount) {
+            let mut value = account.slot.to_le_bytes().to_vec();
+            value.push(u8::from(account.data.is_some()));
+            value.extend_from_slice(account.data.as_deref().unwrap_or_default());
+            let _ = self.tree.insert(address, value);
+        }
+
+        fn remove(&self, address: &Pubkey) {
+            let _ = self.tree.remove(address);
+        }
+
+        fn clear(&self) {
+            let _ = self.tree.clear();
+        }
+    }
+}
+
+pub struct AccountCache {
+    store: Box<dyn CacheStore>,
+    max_age_slots: u64,
+    latest_slot: AtomicU64,
+}
+
+impl AccountCache {
+    // About 150 slots is a minute
+    pub fn new(store: impl CacheStore + 'static, max_age_slots: u64) -> Self {
+        AccountCache { store: Box::new(store), max_age_slots, latest_slot: AtomicU64::new(0) }
+    }
+
+    // The latest slot the cluster is known to be at; cached accounts age against it
+    pub fn observe_slot(&self, slot: u64) {
+        self.latest_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    // Some(data) on a hit, data being None for an account known not to exist; None when the account isn't
+    // cached or has aged out
+    pub fn get(&self, address: &Pubkey) -> Option<Option<Vec<u8>>> {
+        let cached = self.store.get(address)?;
+        if cached.slot.saturating_add(self.max_age_slots) < self.latest_slot.load(Ordering::Relaxed) {
+            self.store.remove(address);
+            return None;
+        }
+        Some(cached.data)
+    }
+
+    pub fn insert(&self, address: &Pubkey, slot: u64, data: Option<Vec<u8>>) {
+        self.observe_slot(slot);
+        self.store.insert(address, CachedAccount { slot, data });
+    }
+
+    pub fn invalidate(&self, address: &Pubkey) {
+        self.store.remove(address);
+    }
+
+    pub fn clear(&self) {
+        self.store.clear();
+    }
+
+    // Drops the accounts an event changed: the entry, and for creates and deletes the author's profile, whose
+    // entry count moved. Pass it every notification of a JournalEvents stream.
+    pub fn apply_event(&self, notification: &EventNotification) {
+        self.observe_slot(notification.slot);
+        match &notification.event {
+            JournalEvent::EntryCreated(event) => {
+                self.invalidate(&event.entry);
+                self.invalidate(&find_user_profile_pda(&event.authority).0);
+            }
+            JournalEvent::EntryUpdated(event) => self.invalidate(&event.entry),
+            JournalEvent::EntryDeleted(event) => {
+                self.invalidate(&event.entry);
+                self.invalidate(&find_user_profile_pda(&event.authority).0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_signature::Signature;
+
+    use super::*;
+    use crate::events::EntryUpdated;
+
+    #[test]
+    fn ages_out_by_slot() {
+        let cache = AccountCache::new(MemoryStore::new(), 10);
+        let present = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+        cache.insert(&present, 100, Some(vec![1, 2, 3]));
+        cache.insert(&missing, 100, None);
+
+        cache.observe_slot(110);
+        assert_eq!(cache.get(&present), Some(Some(vec![1, 2, 3])));
+        assert_eq!(cache.get(&missing), Some(None));
+        cache.observe_slot(111);
+        assert_eq!(cache.get(&present), None);
+        assert_eq!(cache.get(&missing), None);
+    }
+
+    #[test]
+    fn events_invalidate_what_they_touch() {
+        let cache = AccountCache::new(MemoryStore::new(), 1_000);
+        let authority = Pubkey::new_unique();
+        let entry = Pubkey::new_unique();
+        let profile = find_user_profile_pda(&authority).0;
+        cache.insert(&entry, 5, Some(vec![1]));
+        cache.insert(&profile, 5, Some(vec![2]));
+
+        let event = EntryUpdated { authority, entry, id: 0, chars_written: 4 };
+        cache.apply_event(&EventNotification {
+            signature: Signature::default(),
+            slot: 6,
+            event: JournalEvent::EntryUpdated(event),
+        });
+        assert_eq!(cache.get(&entry), None);
+        assert_eq!(cache.get(&profile), Some(Some(vec![2])));
+    }
+}
@@ -0,0 +1,170 @@
+// Client-side encryption of entry messages; the chain is public, so anything meant to stay private is encrypted
+// before it is submitted. Each entry gets a random content key. The message is encrypted under it with
+// XChaCha20-Poly1305 and stored in the entry's message field as ENCRYPTED_PREFIX followed by
+// base64(nonce || ciphertext); entries have no content type, so the prefix is what marks them. The content key is
+// wrapped to every reader's X25519 key, the author's own included, and stored as a KeyEnvelope account
+// (instructions::grant_key_envelope): an ephemeral X25519 exchange, HKDF-SHA256 over the shared secret, then
+// XChaCha20-Poly1305 over the key.
+//
+// A wallet's X25519 key pair is derived from its ed25519 one (`x25519_secret` and `x25519_public_key`), so keys
+// can be wrapped to any wallet address. The encrypted message is longer than the plaintext (`encrypted_len`), and
+// it is the encrypted length that counts towards the program's message limit.
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use hkdf::Hkdf;
+use sha2::{Digest, Sha256, Sha512};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+use crate::accounts::KeyEnvelope;
+use crate::error::SdkError;
+
+pub const ENCRYPTED_PREFIX: &str = "enc1:";
+
+const NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+const KEY_ENVELOPE_INFO: &[u8] = b"journal key envelope v1";
+
+// Keep it if the entry should stay editable without unwrapping an envelope first
+#[derive(Clone)]
+pub struct ContentKey(Key);
+
+impl ContentKey {
+    pub fn generate() -> Self {
+        ContentKey(XChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        ContentKey(bytes.into())
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.into()
+    }
+}
+
+// The arguments grant_key_envelope takes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedKey {
+    pub ephemeral_public_key: [u8; 32],
+    pub nonce: [u8; NONCE_LEN],
+    pub wrapped_key: [u8; 48],
+}
+
+// Length of encrypt_message's output for a message of `message_len` bytes
+pub fn encrypted_len(message_len: usize) -> usize {
+    ENCRYPTED_PREFIX.len() + (NONCE_LEN + message_len + TAG_LEN).div_ceil(3) * 4
+}
+
+pub fn is_encrypted(message: &str) -> bool {
+    message.starts_with(ENCRYPTED_PREFIX)
+}
+
+pub fn encrypt_message(key: &ContentKey, message: &str) -> String {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext =
+        XChaCha20Poly1305::new(&key.0).encrypt(&nonce, message.as_bytes()).expect("messages fit in one encryption");
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    format!("{ENCRYPTED_PREFIX}{}", BASE64_STANDARD.encode(sealed))
+}
+
+pub fn decrypt_message(key: &ContentKey, message: &str) -> Result<String, SdkError> {
+    let encoded = message.strip_prefix(ENCRYPTED_PREFIX).ok_or(SdkError::Decrypt("message is not encrypted"))?;
+    let sealed = BASE64_STANDARD.decode(encoded).map_err(|_| SdkError::Decrypt("message is not valid base64"))?;
+    if sealed.len() < NONCE_LEN {
+        return Err(SdkError::Decrypt("message is too short"));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let plaintext = XChaCha20Poly1305::new(&key.0)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| SdkError::Decrypt("wrong key, or the message was altered"))?;
+    String::from_utf8(plaintext).map_err(|_| SdkError::Decrypt("message is not UTF-8"))
+}
+
+pub fn wrap_key(key: &ContentKey, reader: &PublicKey) -> WrappedKey {
+    let ephemeral = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_public_key = PublicKey::from(&ephemeral);
+    let cipher = envelope_cipher(ephemeral.diffie_hellman(reader).as_bytes(), &ephemeral_public_key, reader);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let wrapped_key = cipher.encrypt(&nonce, key.0.as_slice()).expect("a 32-byte key encrypts");
+    WrappedKey {
+        ephemeral_public_key: ephemeral_public_key.to_bytes(),
+        nonce: nonce.into(),
+        wrapped_key: wrapped_key.try_into().expect("32 key bytes plus a 16-byte tag"),
+    }
+}
+
+pub fn unwrap_key(envelope: &KeyEnvelope, secret: &StaticSecret) -> Result<ContentKey, SdkError> {
+    let ephemeral_public_key = PublicKey::from(envelope.ephemeral_public_key);
+    let reader = PublicKey::from(secret);
+    let shared_secret = secret.diffie_hellman(&ephemeral_public_key);
+    let cipher = envelope_cipher(shared_secret.as_bytes(), &ephemeral_public_key, &reader);
+    let key = cipher
+        .decrypt(XNonce::from_slice(&envelope.nonce), envelope.wrapped_key.as_slice())
+        .map_err(|_| SdkError::Decrypt("the envelope is not for this key"))?;
+    Ok(ContentKey(Key::clone_from_slice(&key)))
+}
+
+// Both public keys go into the salt, so an envelope only opens for the exchange it was made with
+fn envelope_cipher(shared_secret: &[u8; 32], ephemeral: &PublicKey, reader: &PublicKey) -> XChaCha20Poly1305 {
+    let salt = Sha256::new().chain_update(ephemeral.as_bytes()).chain_update(reader.as_bytes()).finalize();
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(&salt), shared_secret).expand(KEY_ENVELOPE_INFO, &mut key).expect("32 bytes expand");
+    XChaCha20Poly1305::new(&key.into())
+}
+
+// The X25519 secret of a wallet keypair, as libsodium's crypto_sign_ed25519_sk_to_curve25519 derives it
+pub fn x25519_secret(keypair: &Keypair) -> StaticSecret {
+    let hash = Sha512::digest(&keypair.to_bytes()[..32]);
+    StaticSecret::from(<[u8; 32]>::try_from(&hash[..32]).expect("SHA-512 is 64 bytes"))
+}
+
+// The X25519 public key matching `x25519_secret` for a wallet address; None for addresses that aren't ed25519
+// public keys, such as PDAs
+pub fn x25519_public_key(wallet: &Pubkey) -> Option<PublicKey> {
+    let point = CompressedEdwardsY(wallet.to_bytes()).decompress()?;
+    Some(PublicKey::from(point.to_montgomery().to_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_signer::Signer;
+
+    use super::*;
+
+    #[test]
+    fn round_trip_through_an_envelope() {
+        let author = Keypair::new();
+        let key = ContentKey::generate();
+        let message = encrypt_message(&key, "dear diary");
+        assert!(is_encrypted(&message));
+
+        let wrapped = wrap_key(&key, &x25519_public_key(&author.pubkey()).unwrap());
+        let envelope = KeyEnvelope {
+            journal_entry: Pubkey::new_unique(),
+            reader: author.pubkey(),
+            ephemeral_public_key: wrapped.ephemeral_public_key,
+            nonce: wrapped.nonce,
+            wrapped_key: wrapped.wrapped_key,
+            bump: 255,
+        };
+        let unwrapped = unwrap_key(&envelope, &x25519_secret(&author)).unwrap();
+        assert_eq!(decrypt_message(&unwrapped, &message).unwrap(), "dear diary");
+
+        let stranger = Keypair::new();
+        assert!(matches!(unwrap_key(&envelope, &x25519_secret(&stranger)), Err(SdkError::Decrypt(_))));
+        assert!(matches!(decrypt_message(&ContentKey::generate(), &message), Err(SdkError::Decrypt(_))));
+    }
+
+    #[test]
+    fn encrypted_length() {
+        for len in [0, 1, 100, 101, 102] {
+            assert_eq!(encrypt_message(&ContentKey::generate(), &"a".repeat(len)).len(), encrypted_len(len));
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use solana_pubkey::Pubkey;
+#[cfg(feature = "rpc")]
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClientError;
+#[cfg(feature = "rpc")]
+use solana_rpc_client_api::client_error::{Error as ClientError, ErrorKind as ClientErrorKind};
+#[cfg(feature = "rpc")]
+use solana_rpc_client_api::request::{RpcError, RpcResponseErrorData};
+use solana_signature::Signature;
+use solana_signer::SignerError;
+#[cfg(feature = "rpc")]
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+
+use crate::program_error::JournalError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SdkError {
+    // Boxed because the RPC client's error is several hundred bytes
+    #[cfg(feature = "rpc")]
+    #[error("rpc request failed: {0}")]
+    Rpc(Box<ClientError>),
+    #[error("account {address} could not be decoded: {source}")]
+    Decode { address: Pubkey, source: anchor_lang::error::Error },
+    #[cfg(feature = "rpc")]
+    #[error("websocket subscription failed: {0}")]
+    Pubsub(Box<PubsubClientError>),
+    #[error("event in transaction {signature} could not be decoded: {source}")]
+    EventDecode { signature: Signature, source: std::io::Error },
+    #[error("transaction {signature} failed: {err}")]
+    Transaction { signature: Signature, err: TransactionError },
+    // A Transaction error raised by one of the journal programs
+    #[error("transaction {signature} failed: {err}")]
+    Program { signature: Signature, err: JournalError },
+    #[error("transaction simulation failed: {err}")]
+    Simulation { err: TransactionError, logs: Vec<String> },
+    #[error("signing failed: {0}")]
+    Signer(#[from] SignerError),
+    #[error("{0} is not a required signer of the transaction")]
+    NotASigner(Pubkey),
+    #[error("signature from {0} does not match the transaction message")]
+    InvalidSignature(Pubkey),
+    #[error("transaction is missing signatures from {0:?}")]
+    MissingSignatures(Vec<Pubkey>),
+    #[error("transaction could not be decoded: {0}")]
+    TransactionDecode(String),
+    #[error("{address} is not a usable nonce account: {reason}")]
+    NonceAccount { address: Pubkey, reason: &'static str },
+    #[error("nonce account {0} was advanced before the transaction landed")]
+    NonceAdvanced(Pubkey),
+    #[error("could not decrypt: {0}")]
+    Decrypt(&'static str),
+    #[error("the journal program deployed is version {deployed}, but this client encodes version {supported}")]
+    UnsupportedProgramVersion { deployed: u16, supported: u16 },
+    #[error("blockhash expired before the transaction landed ({attempts} attempts)")]
+    BlockhashExpired { attempts: usize },
+    #[cfg(feature = "jito")]
+    #[error("bundle not sent: {0}")]
+    Bundle(String),
+}
+
+impl SdkError {
+    // The journal program error behind this one, if any: from the error itself, or parsed from the logs of a
+    // failed simulation or preflight check
+    pub fn journal_error(&self) -> Option<JournalError> {
+        match self {
+            SdkError::Program { err, .. } => Some(*err),
+            SdkError::Simulation { logs, .. } => JournalError::from_logs(logs),
+            #[cfg(feature = "rpc")]
+            SdkError::Rpc(err) => match err.kind() {
+                ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                    data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+                    ..
+                }) => JournalError::from_logs(result.logs.as_deref().unwrap_or_default()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "rpc")]
+    pub(crate) fn transaction_failed(transaction: &Transaction, err: TransactionError) -> Self {
+        let signature = transaction.signatures[0];
+        match JournalError::from_transaction_error(&err, transaction) {
+            Some(err) => SdkError::Program { signature, err },
+            None => SdkError::Transaction { signature, err },
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl From<ClientError> for SdkError {
+    fn from(err: ClientError) -> Self {
+        SdkError::Rpc(Box::new(err))
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl From<PubsubClientError> for SdkError {
+    fn from(err: PubsubClientError) -> Self {
+        SdkError::Pubsub(Box::new(err))
+    }
+}
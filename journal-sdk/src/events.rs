@@ -0,0 +1,225 @@
+// Live journal program events over a websocket logsSubscribe, for activity feeds that would otherwise poll
+// getProgramAccounts. emit! logs an event as "Program data: <base64>", the event's discriminator followed by its
+// borsh encoding, while the emitting program is the one executing.
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::prelude::{Engine, BASE64_STANDARD};
+#[cfg(feature = "rpc")]
+use futures::channel::{mpsc, oneshot};
+use futures::stream::BoxStream;
+#[cfg(feature = "rpc")]
+use futures::stream::StreamExt;
+#[cfg(feature = "rpc")]
+use solana_commitment_config::CommitmentConfig;
+#[cfg(feature = "rpc")]
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+#[cfg(feature = "rpc")]
+use solana_rpc_client_api::config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_signature::Signature;
+
+pub use journal_client::events::{EntryCreated, EntryDeleted, EntryUpdated};
+pub use journal_client::types::DeprecationNotice;
+
+use journal_client::events::InstructionDeprecated;
+
+use crate::error::SdkError;
+use crate::pda::JOURNAL_PROGRAM_ID;
+
+#[derive(Debug, Clone)]
+pub enum JournalEvent {
+    EntryCreated(EntryCreated),
+    EntryUpdated(EntryUpdated),
+    EntryDeleted(EntryDeleted),
+}
+
+#[derive(Debug, Clone)]
+pub struct EventNotification {
+    pub signature: Signature,
+    pub slot: u64,
+    pub event: JournalEvent,
+}
+
+pub type EventStream = BoxStream<'static, Result<EventNotification, SdkError>>;
+
+#[cfg(feature = "rpc")]
+pub struct JournalEvents;
+
+#[cfg(feature = "rpc")]
+impl JournalEvents {
+    // Entry events from confirmed, successful transactions, in the order the node reports them. The stream ends
+    // when the websocket closes; the subscription is dropped at the next notification after the stream is.
+    pub async fn subscribe(ws_url: &str) -> Result<EventStream, SdkError> {
+        let client = PubsubClient::new(ws_url).await?;
+        let (subscribed_sender, subscribed) = oneshot::channel();
+        let (sender, receiver) = mpsc::unbounded();
+
+        // The notification stream borrows the client, so both live in a task that forwards what it decodes
+        tokio::spawn(async move {
+            let filter = RpcTransactionLogsFilter::Mentions(vec![JOURNAL_PROGRAM_ID.to_string()]);
+            let config = RpcTransactionLogsConfig { commitment: Some(CommitmentConfig::confirmed()) };
+            let (mut notifications, unsubscribe) = match client.logs_subscribe(filter, config).await {
+                Ok(subscription) => {
+                    let _ = subscribed_sender.send(Ok(()));
+                    subscription
+                }
+                Err(err) => {
+                    let _ = subscribed_sender.send(Err(err));
+                    return;
+                }
+            };
+
+            'notifications: while let Some(notification) = notifications.next().await {
+                // A failed transaction's events never happened
+                if notification.value.err.is_some() {
+                    continue;
+                }
+                let Ok(signature) = notification.value.signature.parse::<Signature>() else {
+                    continue;
+                };
+                for event in parse_events(&notification.value.logs) {
+                    let event = event
+                        .map(|event| EventNotification { signature, slot: notification.context.slot, event })
+                        .map_err(|source| SdkError::EventDecode { signature, source });
+                    if sender.unbounded_send(event).is_err() {
+                        break 'notifications;
+                    }
+                }
+            }
+            unsubscribe().await;
+        });
+
+        subscribed.await.expect("the subscription task reports before it exits")?;
+        Ok(receiver.boxed())
+    }
+}
+
+// The entry events the journal program emitted directly, in log order. For logs from anywhere, e.g.
+// getTransaction's logMessages.
+pub fn parse_events(logs: &[String]) -> Vec<Result<JournalEvent, std::io::Error>> {
+    journal_program_data(logs).into_iter().filter_map(decode_event).collect()
+}
+
+// What the deprecated journal instructions among those that ran noted: each still works, but names its replacement
+// and the version that removes it. TransactionSender::send reads them from its simulation.
+pub fn parse_deprecations(logs: &[String]) -> Vec<DeprecationNotice> {
+    journal_program_data(logs)
+        .into_iter()
+        .filter_map(|data| {
+            let data = BASE64_STANDARD.decode(data).ok()?;
+            let body = data.strip_prefix(InstructionDeprecated::DISCRIMINATOR)?;
+            InstructionDeprecated::try_from_slice(body).ok()
+        })
+        .map(|event| event.notice)
+        .collect()
+}
+
+// The base64 of each emit! the journal program made itself; data logged by other programs it calls, or by
+// programs calling it, is skipped
+fn journal_program_data(logs: &[String]) -> Vec<&str> {
+    let journal_program = JOURNAL_PROGRAM_ID.to_string();
+    let mut invocations: Vec<&str> = Vec::new();
+    let mut data = Vec::new();
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(logged) = rest.strip_prefix("data: ") {
+            if invocations.last() == Some(&journal_program.as_str()) {
+                data.push(logged);
+            }
+            continue;
+        }
+        // "<id> invoke [depth]", then "<id> success" or "<id> failed: <error>"
+        let mut words = rest.split(' ');
+        match (words.next(), words.next()) {
+            (Some(program_id), Some("invoke")) => invocations.push(program_id),
+            (Some(_), Some("success" | "failed:")) => {
+                invocations.pop();
+            }
+            _ => {}
+        }
+    }
+    data
+}
+
+// None for data that isn't one of the entry events
+fn decode_event(data: &str) -> Option<Result<JournalEvent, std::io::Error>> {
+    let data = BASE64_STANDARD.decode(data).ok()?;
+    let (discriminator, body) = data.split_at_checked(8)?;
+    let event = if discriminator == EntryCreated::DISCRIMINATOR {
+        EntryCreated::try_from_slice(body).map(JournalEvent::EntryCreated)
+    } else if discriminator == EntryUpdated::DISCRIMINATOR {
+        EntryUpdated::try_from_slice(body).map(JournalEvent::EntryUpdated)
+    } else if discriminator == EntryDeleted::DISCRIMINATOR {
+        EntryDeleted::try_from_slice(body).map(JournalEvent::EntryDeleted)
+    } else {
+        return None;
+    };
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Event;
+    use solana_pubkey::Pubkey;
+
+    use super::*;
+
+    fn data_line(event: &impl Event) -> String {
+        format!("Program data: {}", BASE64_STANDARD.encode(event.data()))
+    }
+
+    #[test]
+    fn only_entry_events_emitted_by_the_journal_program() {
+        let updated = EntryUpdated {
+            authority: Pubkey::new_from_array([1; 32]),
+            entry: Pubkey::new_from_array([2; 32]),
+            id: 3,
+            chars_written: 40,
+        };
+        let other_program = Pubkey::new_from_array([7; 32]);
+        let logs = [
+            format!("Program {JOURNAL_PROGRAM_ID} invoke [1]"),
+            "Program log: Instruction: UpdateJournalEntry".to_string(),
+            format!("Program {other_program} invoke [2]"),
+            data_line(&updated),
+            format!("Program {other_program} success"),
+            data_line(&updated),
+            format!("Program {JOURNAL_PROGRAM_ID} consumed 21000 of 200000 compute units"),
+            format!("Program {JOURNAL_PROGRAM_ID} success"),
+            data_line(&updated),
+        ];
+
+        let events = parse_events(&logs);
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            Ok(JournalEvent::EntryUpdated(event)) => assert_eq!((event.id, event.chars_written), (3, 40)),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn deprecation_notices() {
+        let notice = DeprecationNotice {
+            instruction: "update_config".into(),
+            replacement: "set_fees".into(),
+            removed_in_version: 3,
+        };
+        let deprecated = InstructionDeprecated { notice };
+        let logs = [
+            format!("Program {JOURNAL_PROGRAM_ID} invoke [1]"),
+            "Program log: DEPRECATED: update_config will be removed in version 3, use set_fees instead".to_string(),
+            data_line(&deprecated),
+            format!("Program {JOURNAL_PROGRAM_ID} success"),
+            data_line(&deprecated),
+        ];
+
+        let notices = parse_deprecations(&logs);
+        assert_eq!(notices.len(), 1);
+        assert_eq!(
+            (notices[0].instruction.as_str(), notices[0].replacement.as_str(), notices[0].removed_in_version),
+            ("update_config", "set_fees", 3)
+        );
+        assert!(parse_events(&logs).is_empty());
+    }
+}
@@ -0,0 +1,72 @@
+// getProgramAccounts filters for the journal accounts. Discriminators are the first 8 bytes of
+// sha256("account:<Name>"), as Anchor derives them; offsets and sizes include the discriminator.
+//
+// Discriminator filters match every layout version of an account. The authority filters rely on offsets that
+// the programs keep stable; the size filters only match accounts already in the current layout.
+
+use solana_pubkey::Pubkey;
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+
+// journal program
+pub const JOURNAL_ENTRY_DISCRIMINATOR: [u8; 8] = [255, 74, 177, 178, 227, 112, 46, 152];
+pub const USER_PROFILE_DISCRIMINATOR: [u8; 8] = [32, 37, 119, 205, 179, 180, 13, 194];
+pub const JOURNAL_ENTRY_SIZE: u64 = 996;
+pub const USER_PROFILE_SIZE: u64 = 1167;
+pub const ENTRY_AUTHORITY_OFFSET: usize = 8;
+pub const ENTRY_ID_OFFSET: usize = 40;
+pub const USER_PROFILE_AUTHORITY_OFFSET: usize = 8;
+
+// journal_pda_optimized program
+pub use crate::pda_optimized::JOURNAL_ENTRY_STATE_DISCRIMINATOR;
+pub const JOURNAL_ENTRY_STATE_SIZE: u64 = 424;
+pub const ENTRY_OWNER_OFFSET: usize = 8;
+pub const ENTRY_INDEX_OFFSET: usize = 40;
+
+fn discriminator(discriminator: [u8; 8]) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, discriminator.to_vec()))
+}
+
+fn pubkey_at(offset: usize, pubkey: &Pubkey) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, pubkey.to_bytes().to_vec()))
+}
+
+pub fn journal_entries() -> Vec<RpcFilterType> {
+    vec![discriminator(JOURNAL_ENTRY_DISCRIMINATOR)]
+}
+
+pub fn journal_entries_by_authority(authority: &Pubkey) -> Vec<RpcFilterType> {
+    vec![discriminator(JOURNAL_ENTRY_DISCRIMINATOR), pubkey_at(ENTRY_AUTHORITY_OFFSET, authority)]
+}
+
+pub fn journal_entry_size() -> RpcFilterType {
+    RpcFilterType::DataSize(JOURNAL_ENTRY_SIZE)
+}
+
+pub fn user_profiles() -> Vec<RpcFilterType> {
+    vec![discriminator(USER_PROFILE_DISCRIMINATOR)]
+}
+
+// Only matches profiles in the current layout; look up a single profile by its PDA instead where possible
+pub fn user_profiles_by_authority(authority: &Pubkey) -> Vec<RpcFilterType> {
+    vec![
+        discriminator(USER_PROFILE_DISCRIMINATOR),
+        pubkey_at(USER_PROFILE_AUTHORITY_OFFSET, authority),
+        RpcFilterType::DataSize(USER_PROFILE_SIZE),
+    ]
+}
+
+pub fn user_profile_size() -> RpcFilterType {
+    RpcFilterType::DataSize(USER_PROFILE_SIZE)
+}
+
+pub fn journal_entry_states() -> Vec<RpcFilterType> {
+    vec![discriminator(JOURNAL_ENTRY_STATE_DISCRIMINATOR)]
+}
+
+pub fn journal_entry_states_by_owner(owner: &Pubkey) -> Vec<RpcFilterType> {
+    vec![discriminator(JOURNAL_ENTRY_STATE_DISCRIMINATOR), pubkey_at(ENTRY_OWNER_OFFSET, owner)]
+}
+
+pub fn journal_entry_state_size() -> RpcFilterType {
+    RpcFilterType::DataSize(JOURNAL_ENTRY_STATE_SIZE)
+}
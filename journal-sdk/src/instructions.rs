@@ -0,0 +1,143 @@
+// Instruction builders for the journal program. The authority signs for itself (no session key) and the
+// optional accounts are left out, which is what a plain write needs; build the journal_client structs directly
+// for session keys, notebooks, replies and the like.
+
+use crate::encryption::WrappedKey;
+use crate::pda::{
+    find_feed_head_pda, find_journal_entry_pda, find_key_envelope_pda, find_program_config_pda, find_treasury_pda,
+    find_user_profile_pda, find_view_receipt_pda, find_view_tally_pda,
+};
+use journal_client::client::{accounts, args};
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_sdk_ids::{system_program, sysvar};
+
+pub fn initialize_user_profile(authority: &Pubkey, payer: &Pubkey) -> Instruction {
+    journal_client::instruction(
+        accounts::InitializeUserProfile {
+            program_config: find_program_config_pda().0,
+            user_profile: find_user_profile_pda(authority).0,
+            authority: *authority,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        args::InitializeUserProfile {},
+    )
+}
+
+// `entry_id` must be the profile's current entry_count, which the program assigns to the new entry
+pub fn add_journal_entry(
+    authority: &Pubkey,
+    payer: &Pubkey,
+    entry_id: u64,
+    title: String,
+    message: String,
+) -> Instruction {
+    journal_client::instruction(
+        accounts::AddJournalEntry {
+            user_profile: find_user_profile_pda(authority).0,
+            journal_entry: find_journal_entry_pda(authority, entry_id).0,
+            program_config: find_program_config_pda().0,
+            treasury: find_treasury_pda().0,
+            authority: *authority,
+            signer: *authority,
+            session_key: None,
+            gate_token_account: None,
+            title_claim: None,
+            price_update: None,
+            daily_prompt: None,
+            journal: None,
+            feed_head: None,
+            reply_parent: None,
+            memo_program: None,
+            instructions_sysvar: sysvar::instructions::ID,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        args::AddJournalEntry { title, message },
+    )
+}
+
+pub fn update_journal_entry(authority: &Pubkey, entry_id: u64, title: String, message: String) -> Instruction {
+    journal_client::instruction(
+        accounts::UpdateJournalEntry {
+            program_config: find_program_config_pda().0,
+            user_profile: find_user_profile_pda(authority).0,
+            journal_entry: find_journal_entry_pda(authority, entry_id).0,
+            authority: *authority,
+            signer: *authority,
+            session_key: None,
+            memo_program: None,
+            system_program: system_program::ID,
+        },
+        args::UpdateJournalEntry { _entry_id: entry_id, title, message },
+    )
+}
+
+// `rent_recipient` is the entry's rent_payer: the authority, or the sponsor pool for sponsored entries
+pub fn delete_journal_entry(authority: &Pubkey, entry_id: u64, rent_recipient: &Pubkey) -> Instruction {
+    journal_client::instruction(
+        accounts::DeleteJournalEntry {
+            program_config: find_program_config_pda().0,
+            user_profile: find_user_profile_pda(authority).0,
+            journal_entry: find_journal_entry_pda(authority, entry_id).0,
+            rent_recipient: *rent_recipient,
+            authority: *authority,
+            signer: *authority,
+            session_key: None,
+            title_claim: None,
+            system_program: system_program::ID,
+        },
+        args::DeleteJournalEntry { _entry_id: entry_id },
+    )
+}
+
+pub fn set_entry_visibility(authority: &Pubkey, entry_id: u64, is_public: bool) -> Instruction {
+    journal_client::instruction(
+        accounts::SetEntryVisibility {
+            program_config: find_program_config_pda().0,
+            journal_entry: find_journal_entry_pda(authority, entry_id).0,
+            feed_head: find_feed_head_pda().0,
+            authority: *authority,
+            signer: *authority,
+            session_key: None,
+        },
+        args::SetEntryVisibility { _entry_id: entry_id, is_public },
+    )
+}
+
+pub fn record_view(viewer: &Pubkey, authority: &Pubkey, entry_id: u64) -> Instruction {
+    let entry = find_journal_entry_pda(authority, entry_id).0;
+    journal_client::instruction(
+        accounts::RecordView {
+            program_config: find_program_config_pda().0,
+            journal_entry: entry,
+            view_receipt: find_view_receipt_pda(&entry, viewer).0,
+            view_tally: find_view_tally_pda(&entry).0,
+            viewer: *viewer,
+            system_program: system_program::ID,
+        },
+        args::RecordView {},
+    )
+}
+
+// Stores the entry's content key wrapped to `reader` (encryption::wrap_key); the author grants themselves one too
+pub fn grant_key_envelope(authority: &Pubkey, entry_id: u64, reader: &Pubkey, wrapped: &WrappedKey) -> Instruction {
+    let entry = find_journal_entry_pda(authority, entry_id).0;
+    journal_client::instruction(
+        accounts::GrantKeyEnvelope {
+            program_config: find_program_config_pda().0,
+            journal_entry: entry,
+            key_envelope: find_key_envelope_pda(&entry, reader).0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        args::GrantKeyEnvelope {
+            _entry_id: entry_id,
+            reader: *reader,
+            ephemeral_public_key: wrapped.ephemeral_public_key,
+            nonce: wrapped.nonce,
+            wrapped_key: wrapped.wrapped_key,
+        },
+    )
+}
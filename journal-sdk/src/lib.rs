@@ -0,0 +1,28 @@
+// Client helpers for the journal program (project-1) and journal_pda_optimized (project-3), for Rust services
+// that talk to them over RPC. Instruction and account types for the journal program come from journal-client.
+//
+// The RPC pieces (reader, sender, event subscriptions) are behind the default "rpc" feature. Without it the crate
+// builds for wasm32-unknown-unknown, and the "wasm" feature exports it to JavaScript (crate::wasm).
+
+pub mod accounts;
+pub mod cache;
+pub mod encryption;
+pub mod error;
+pub mod events;
+#[cfg(feature = "rpc")]
+pub mod filters;
+pub mod instructions;
+pub mod nonce;
+pub mod offline;
+pub mod pda;
+pub mod pda_optimized;
+pub mod program_error;
+pub mod render;
+#[cfg(feature = "rpc")]
+pub mod reader;
+#[cfg(feature = "rpc")]
+pub mod sender;
+pub mod signer;
+pub mod version;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,93 @@
+// Durable nonce transactions, which stay valid until their nonce account is advanced rather than for the minute
+// or so a recent blockhash lasts: entries signed now and broadcast hours later, or signed on a hardware wallet at
+// its own pace. Such a transaction uses the nonce account's stored nonce as its blockhash and must begin by
+// advancing that nonce, which the nonce authority signs for. Landing it advances the nonce, so each nonce value
+// signs exactly one transaction.
+//
+// The nonce account itself is created with the System Program's create_nonce_account instructions.
+
+use solana_hash::Hash;
+use solana_instruction::Instruction;
+use solana_nonce::state::{Data, State};
+use solana_nonce::versions::Versions;
+use solana_pubkey::Pubkey;
+#[cfg(feature = "rpc")]
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_system_interface::instruction::advance_nonce_account;
+use solana_transaction::Transaction;
+
+use crate::error::SdkError;
+use crate::offline;
+
+pub use solana_nonce::state::Data as NonceData;
+
+// Unsigned, for signing with crate::offline or Transaction::sign; `nonce` is the account's current
+// NonceData::blockhash(). The nonce authority is one of the signers.
+pub fn nonce_transaction(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    nonce_authority: &Pubkey,
+    nonce: Hash,
+) -> Transaction {
+    let mut with_advance = Vec::with_capacity(instructions.len() + 1);
+    with_advance.push(advance_nonce_account(nonce_account, nonce_authority));
+    with_advance.extend_from_slice(instructions);
+    offline::unsigned_transaction(&with_advance, payer, nonce)
+}
+
+pub fn nonce_data(address: &Pubkey, data: &[u8]) -> Result<Data, SdkError> {
+    let invalid = |reason| SdkError::NonceAccount { address: *address, reason };
+    match bincode::deserialize(data).map_err(|_| invalid("not a nonce account"))? {
+        Versions::Current(state) => match *state {
+            State::Initialized(data) => Ok(data),
+            State::Uninitialized => Err(invalid("not initialized")),
+        },
+        // Nonces stored before the durable nonce domain was separated from blockhashes can't sign; advancing the
+        // account once upgrades it
+        Versions::Legacy(_) => Err(invalid("legacy nonce, advance it once before use")),
+    }
+}
+
+#[cfg(feature = "rpc")]
+pub async fn fetch_nonce_data(rpc: &RpcClient, nonce_account: &Pubkey) -> Result<Data, SdkError> {
+    let account = rpc.get_account_with_commitment(nonce_account, rpc.commitment()).await?.value;
+    let account = account.ok_or(SdkError::NonceAccount { address: *nonce_account, reason: "account not found" })?;
+    nonce_data(nonce_account, &account.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_nonce::state::DurableNonce;
+
+    use super::*;
+
+    #[test]
+    fn advances_the_nonce_first() {
+        let payer = Pubkey::new_from_array([1; 32]);
+        let nonce_account = Pubkey::new_from_array([2; 32]);
+        let entry = crate::instructions::add_journal_entry(&payer, &payer, 0, "title".into(), "message".into());
+        let nonce = Hash::new_from_array([3; 32]);
+
+        let transaction = nonce_transaction(&[entry], &payer, &nonce_account, &payer, nonce);
+        assert_eq!(transaction.message.recent_blockhash, nonce);
+        let advance = &transaction.message.instructions[0];
+        let account_keys = &transaction.message.account_keys;
+        assert_eq!(account_keys[usize::from(advance.program_id_index)], solana_sdk_ids::system_program::ID);
+        assert_eq!(account_keys[usize::from(advance.accounts[0])], nonce_account);
+    }
+
+    #[test]
+    fn decodes_initialized_nonces_only() {
+        let address = Pubkey::new_from_array([2; 32]);
+        let durable_nonce = DurableNonce::from_blockhash(&Hash::new_from_array([4; 32]));
+        let state = State::new_initialized(&Pubkey::new_from_array([1; 32]), durable_nonce, 5000);
+
+        let current = bincode::serialize(&Versions::new(state.clone())).unwrap();
+        assert_eq!(nonce_data(&address, &current).unwrap().blockhash(), *durable_nonce.as_hash());
+        let legacy = bincode::serialize(&Versions::Legacy(Box::new(state))).unwrap();
+        assert!(matches!(nonce_data(&address, &legacy), Err(SdkError::NonceAccount { .. })));
+        let uninitialized = bincode::serialize(&Versions::new(State::Uninitialized)).unwrap();
+        assert!(matches!(nonce_data(&address, &uninitialized), Err(SdkError::NonceAccount { .. })));
+    }
+}
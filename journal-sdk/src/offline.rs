@@ -0,0 +1,107 @@
+// Transactions authored on one machine and signed on another, such as a cold wallet that never holds an online
+// Keypair. Nothing here talks to the network: the blockhash is supplied by the caller, and a transaction travels
+// between machines as base64 (the wire encoding sendTransaction takes). The usual flow:
+//
+//   1. build the instructions (crate::instructions) and `unsigned_transaction` them with a recent blockhash
+//   2. hand `message_bytes` (or the `encode`d transaction) to each signer
+//   3. `add_signature` what they return, then send it with TransactionSender::send_signed
+//
+// A recent blockhash expires after about a minute; flows that take longer need a durable nonce (crate::nonce).
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use solana_hash::Hash;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_transaction::Transaction;
+
+use crate::error::SdkError;
+
+// Compute budget instructions aren't added here, since they can't be sized offline; include them in
+// `instructions` if the transaction needs them
+pub fn unsigned_transaction(instructions: &[Instruction], payer: &Pubkey, blockhash: Hash) -> Transaction {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(payer));
+    transaction.message.recent_blockhash = blockhash;
+    transaction
+}
+
+// The bytes each signer signs
+pub fn message_bytes(transaction: &Transaction) -> Vec<u8> {
+    transaction.message_data()
+}
+
+// In signature order; the fee payer is first
+pub fn required_signers(transaction: &Transaction) -> &[Pubkey] {
+    &transaction.message.account_keys[..usize::from(transaction.message.header.num_required_signatures)]
+}
+
+// Signers that haven't signed yet
+pub fn missing_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    required_signers(transaction)
+        .iter()
+        .zip(&transaction.signatures)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(signer, _)| *signer)
+        .collect()
+}
+
+// Checks `signature` against the message before placing it, so a signature over a different message (another
+// blockhash, say) is rejected here rather than by the cluster
+pub fn add_signature(transaction: &mut Transaction, signer: &Pubkey, signature: Signature) -> Result<(), SdkError> {
+    let position = required_signers(transaction)
+        .iter()
+        .position(|required| required == signer)
+        .ok_or(SdkError::NotASigner(*signer))?;
+    if !signature.verify(signer.as_ref(), &transaction.message_data()) {
+        return Err(SdkError::InvalidSignature(*signer));
+    }
+    transaction.signatures[position] = signature;
+    Ok(())
+}
+
+pub fn encode(transaction: &Transaction) -> String {
+    BASE64_STANDARD.encode(bincode::serialize(transaction).expect("transactions serialize"))
+}
+
+pub fn decode(encoded: &str) -> Result<Transaction, SdkError> {
+    let bytes = BASE64_STANDARD.decode(encoded.trim()).map_err(|err| SdkError::TransactionDecode(err.to_string()))?;
+    bincode::deserialize(&bytes).map_err(|err| SdkError::TransactionDecode(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_keypair::Keypair;
+    use solana_signer::Signer;
+
+    use super::*;
+    use crate::instructions;
+
+    #[test]
+    fn sign_elsewhere_and_assemble() {
+        let authority = Keypair::new();
+        let payer = Keypair::new();
+        let instruction =
+            instructions::add_journal_entry(&authority.pubkey(), &payer.pubkey(), 0, "title".into(), "message".into());
+        let unsigned = unsigned_transaction(&[instruction], &payer.pubkey(), Hash::new_from_array([9; 32]));
+        assert_eq!(missing_signers(&unsigned), [payer.pubkey(), authority.pubkey()]);
+
+        // The cold side only ever sees the encoded transaction
+        let cold_copy = decode(&encode(&unsigned)).unwrap();
+        let authority_signature = authority.sign_message(&message_bytes(&cold_copy));
+
+        let mut transaction = unsigned;
+        let stranger = Keypair::new();
+        assert!(matches!(
+            add_signature(&mut transaction, &stranger.pubkey(), authority_signature),
+            Err(SdkError::NotASigner(_))
+        ));
+        assert!(matches!(
+            add_signature(&mut transaction, &payer.pubkey(), authority_signature),
+            Err(SdkError::InvalidSignature(_))
+        ));
+        add_signature(&mut transaction, &authority.pubkey(), authority_signature).unwrap();
+        let payer_signature = payer.sign_message(&message_bytes(&transaction));
+        add_signature(&mut transaction, &payer.pubkey(), payer_signature).unwrap();
+        assert!(missing_signers(&transaction).is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+// PDA derivation for both programs. Their entry seeds differ in order as well as content: the journal program
+// puts its prefix first ([b"journal_entry", authority, id]), journal_pda_optimized puts the owner first
+// ([owner, b"journal", entry_index]). Entry ids and indices are little-endian u64s in both.
+
+use solana_pubkey::{pubkey, Pubkey};
+
+pub const JOURNAL_PROGRAM_ID: Pubkey = journal_client::ID;
+pub const JOURNAL_PDA_OPTIMIZED_PROGRAM_ID: Pubkey = pubkey!("94L2mJxVu6ZMmHaGsCHRQ65Kk2mea6aTnwWjSdfSsmBC");
+
+// journal program
+const PROGRAM_CONFIG_SEED: &[u8] = b"program_config";
+const TREASURY_SEED: &[u8] = b"treasury";
+const USER_PROFILE_SEED: &[u8] = b"user_profile";
+const JOURNAL_ENTRY_SEED: &[u8] = b"journal_entry";
+const FEED_HEAD_SEED: &[u8] = b"feed_head";
+const VIEW_RECEIPT_SEED: &[u8] = b"view_receipt";
+const VIEW_TALLY_SEED: &[u8] = b"view_tally";
+const KEY_ENVELOPE_SEED: &[u8] = b"key_envelope";
+const REACTION_TALLY_SEED: &[u8] = b"reaction_tally";
+
+// journal_pda_optimized program
+const USER_JOURNAL_COUNTER_SEED: &[u8] = b"counter";
+const JOURNAL_ENTRY_STATE_SEED: &[u8] = b"journal";
+const IDEMPOTENCY_SEED: &[u8] = b"idempotency";
+
+pub fn find_program_config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROGRAM_CONFIG_SEED], &JOURNAL_PROGRAM_ID)
+}
+
+pub fn find_treasury_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED], &JOURNAL_PROGRAM_ID)
+}
+
+pub fn find_feed_head_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEED_HEAD_SEED], &JOURNAL_PROGRAM_ID)
+}
+
+pub fn find_user_profile_pda(authority: &Pubkey) -> (Pubkey, u8) {
+    user_profile_pda(authority, &JOURNAL_PROGRAM_ID)
+}
+
+pub fn find_journal_entry_pda(authority: &Pubkey, id: u64) -> (Pubkey, u8) {
+    journal_entry_pda(authority, id, &JOURNAL_PROGRAM_ID)
+}
+
+pub fn find_view_receipt_pda(journal_entry: &Pubkey, viewer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VIEW_RECEIPT_SEED, journal_entry.as_ref(), viewer.as_ref()], &JOURNAL_PROGRAM_ID)
+}
+
+pub fn find_view_tally_pda(journal_entry: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VIEW_TALLY_SEED, journal_entry.as_ref()], &JOURNAL_PROGRAM_ID)
+}
+
+pub fn find_reaction_tally_pda(journal_entry: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REACTION_TALLY_SEED, journal_entry.as_ref()], &JOURNAL_PROGRAM_ID)
+}
+
+pub fn find_key_envelope_pda(journal_entry: &Pubkey, reader: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[KEY_ENVELOPE_SEED, journal_entry.as_ref(), reader.as_ref()], &JOURNAL_PROGRAM_ID)
+}
+
+pub fn find_user_journal_counter_pda(owner: &Pubkey) -> (Pubkey, u8) {
+    user_journal_counter_pda(owner, &JOURNAL_PDA_OPTIMIZED_PROGRAM_ID)
+}
+
+pub fn find_journal_entry_state_pda(owner: &Pubkey, entry_index: u64) -> (Pubkey, u8) {
+    journal_entry_state_pda(owner, entry_index, &JOURNAL_PDA_OPTIMIZED_PROGRAM_ID)
+}
+
+pub fn find_idempotency_record_pda(owner: &Pubkey, idempotency_key: &[u8; 16]) -> (Pubkey, u8) {
+    idempotency_record_pda(owner, idempotency_key, &JOURNAL_PDA_OPTIMIZED_PROGRAM_ID)
+}
+
+// The seeds live in these so the tests can pin them under a fixed program id, independent of deployments
+
+fn user_profile_pda(authority: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[USER_PROFILE_SEED, authority.as_ref()], program_id)
+}
+
+fn journal_entry_pda(authority: &Pubkey, id: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[JOURNAL_ENTRY_SEED, authority.as_ref(), &id.to_le_bytes()], program_id)
+}
+
+fn user_journal_counter_pda(owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[USER_JOURNAL_COUNTER_SEED, owner.as_ref()], program_id)
+}
+
+fn journal_entry_state_pda(owner: &Pubkey, entry_index: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[owner.as_ref(), JOURNAL_ENTRY_STATE_SEED, &entry_index.to_le_bytes()], program_id)
+}
+
+fn idempotency_record_pda(owner: &Pubkey, idempotency_key: &[u8; 16], program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[IDEMPOTENCY_SEED, owner.as_ref(), idempotency_key], program_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Vectors computed outside this crate (sha256 over the seeds, bump and program id, first off-curve bump),
+    // for program id [7; 32] and authority/owner [1; 32]
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+    const AUTHORITY: Pubkey = Pubkey::new_from_array([1; 32]);
+
+    #[test]
+    fn user_profile() {
+        assert_eq!(
+            user_profile_pda(&AUTHORITY, &PROGRAM_ID),
+            (pubkey!("Hk38kXoksbi5GEp7533pi9AjQStLfJ2afsVszyVZa22a"), 250)
+        );
+    }
+
+    #[test]
+    fn journal_entry() {
+        assert_eq!(
+            journal_entry_pda(&AUTHORITY, 3, &PROGRAM_ID),
+            (pubkey!("6JHPYLc1jZqzU3nvAMsPU31D8Eydg4Yt7NRcDsrUv8QL"), 255)
+        );
+    }
+
+    #[test]
+    fn user_journal_counter() {
+        assert_eq!(
+            user_journal_counter_pda(&AUTHORITY, &PROGRAM_ID),
+            (pubkey!("Bi8gJna1jojZgnnAAK8M9w9yqFpmALRygGbyGzNcnKYh"), 254)
+        );
+    }
+
+    #[test]
+    fn journal_entry_state() {
+        assert_eq!(
+            journal_entry_state_pda(&AUTHORITY, 3, &PROGRAM_ID),
+            (pubkey!("8p8J7DfWDMv1n2LRkKd2kGdqW7KVoiSXKGgPqnPLHBeU"), 254)
+        );
+    }
+
+    #[test]
+    fn idempotency_record() {
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+        assert_eq!(
+            idempotency_record_pda(&AUTHORITY, &key, &PROGRAM_ID),
+            (pubkey!("6CA1sEDz2QEib223kkZrScZvSaa42NS9Qrk93jctWmwH"), 255)
+        );
+    }
+}
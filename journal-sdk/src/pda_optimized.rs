@@ -0,0 +1,164 @@
+// journal_pda_optimized (project-3), which has no IDL crate: its accounts and instructions are written out here,
+// with Anchor's discriminators (sha256 of "account:<Name>" and "global:<instruction>", first 8 bytes).
+//
+// Entries are numbered by the owner's UserJournalCounter. A create names the index it derived the entry address
+// from, and fails with StaleCounter when another writer took that index first; create_entry_safe re-reads the
+// counter and tries again.
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::borsh;
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+use crate::pda::{
+    find_idempotency_record_pda, find_journal_entry_state_pda, find_user_journal_counter_pda,
+    JOURNAL_PDA_OPTIMIZED_PROGRAM_ID,
+};
+
+#[cfg(feature = "rpc")]
+mod rpc;
+#[cfg(feature = "rpc")]
+pub use rpc::{
+    create_entry_safe, fetch_entry_state, fetch_entry_states_by_owner, fetch_user_journal_counter, CreateRetryConfig,
+};
+
+pub const USER_JOURNAL_COUNTER_DISCRIMINATOR: [u8; 8] = [118, 197, 176, 5, 254, 47, 153, 189];
+pub const JOURNAL_ENTRY_STATE_DISCRIMINATOR: [u8; 8] = [113, 86, 110, 124, 140, 14, 58, 66];
+const INITIALIZE_USER_JOURNAL_COUNTER_DISCRIMINATOR: [u8; 8] = [15, 82, 13, 172, 239, 103, 217, 23];
+const CREATE_JOURNAL_ENTRY_DISCRIMINATOR: [u8; 8] = [48, 65, 201, 186, 25, 41, 127, 0];
+const UPDATE_JOURNAL_ENTRY_DISCRIMINATOR: [u8; 8] = [113, 164, 49, 62, 43, 83, 194, 172];
+const DELETE_JOURNAL_ENTRY_DISCRIMINATOR: [u8; 8] = [156, 50, 93, 5, 157, 97, 188, 114];
+
+#[derive(Debug, Clone, PartialEq, Eq, AnchorDeserialize)]
+pub struct UserJournalCounter {
+    pub owner: Pubkey,
+    pub last_entry_index: u64, // The index the next entry gets
+    pub bump: u8,
+}
+
+// Current layout (version 1); older entries decode once migrate_entry has rewritten them
+#[derive(Debug, Clone, PartialEq, Eq, AnchorDeserialize)]
+pub struct JournalEntryState {
+    pub owner: Pubkey,
+    pub entry_index: u64,
+    pub version: u8,
+    pub edit_count: u32, // Pass it back to update_journal_entry
+    pub content_hash: [u8; 32],
+    pub title: String,
+    pub message: String,
+    pub bump: u8,
+}
+
+pub fn user_journal_counter(data: &[u8]) -> anchor_lang::Result<UserJournalCounter> {
+    decode(data, &USER_JOURNAL_COUNTER_DISCRIMINATOR)
+}
+
+pub fn journal_entry_state(data: &[u8]) -> anchor_lang::Result<JournalEntryState> {
+    decode(data, &JOURNAL_ENTRY_STATE_DISCRIMINATOR)
+}
+
+fn decode<T: AnchorDeserialize>(data: &[u8], discriminator: &[u8; 8]) -> anchor_lang::Result<T> {
+    let body = data.strip_prefix(discriminator).ok_or(ErrorCode::AccountDiscriminatorMismatch)?;
+    T::deserialize(&mut &body[..]).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+pub fn initialize_user_journal_counter(owner: &Pubkey) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(find_user_journal_counter_pda(owner).0, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    Instruction {
+        program_id: JOURNAL_PDA_OPTIMIZED_PROGRAM_ID,
+        accounts,
+        data: INITIALIZE_USER_JOURNAL_COUNTER_DISCRIMINATOR.to_vec(),
+    }
+}
+
+// The owner pays for the entry and signs. With an `idempotency_key`, a create that already went through under
+// that key succeeds again without creating another entry.
+pub fn create_journal_entry(
+    owner: &Pubkey,
+    expected_index: u64,
+    title: String,
+    message: String,
+    idempotency_key: Option<[u8; 16]>,
+) -> Instruction {
+    // Optional accounts that are left out are passed as the program id
+    let idempotency_record = match idempotency_key {
+        Some(key) => AccountMeta::new(find_idempotency_record_pda(owner, &key).0, false),
+        None => AccountMeta::new_readonly(JOURNAL_PDA_OPTIMIZED_PROGRAM_ID, false),
+    };
+    let accounts = vec![
+        AccountMeta::new(find_user_journal_counter_pda(owner).0, false),
+        AccountMeta::new(find_journal_entry_state_pda(owner, expected_index).0, false),
+        AccountMeta::new_readonly(JOURNAL_PDA_OPTIMIZED_PROGRAM_ID, false), // previous_entry
+        idempotency_record,
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let mut data = CREATE_JOURNAL_ENTRY_DISCRIMINATOR.to_vec();
+    (expected_index, title, message, idempotency_key).serialize(&mut data).expect("writes to a Vec succeed");
+    Instruction { program_id: JOURNAL_PDA_OPTIMIZED_PROGRAM_ID, accounts, data }
+}
+
+// `expected_edit_count` is the entry's edit_count as last read; the update fails with ConcurrentModification if
+// someone edited it since
+pub fn update_journal_entry(
+    owner: &Pubkey,
+    entry_index: u64,
+    expected_edit_count: u32,
+    title: String,
+    message: String,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(find_journal_entry_state_pda(owner, entry_index).0, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let mut data = UPDATE_JOURNAL_ENTRY_DISCRIMINATOR.to_vec();
+    (entry_index, expected_edit_count, title, message).serialize(&mut data).expect("writes to a Vec succeed");
+    Instruction { program_id: JOURNAL_PDA_OPTIMIZED_PROGRAM_ID, accounts, data }
+}
+
+// The entry's rent goes back to the owner
+pub fn delete_journal_entry(owner: &Pubkey, entry_index: u64) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(find_journal_entry_state_pda(owner, entry_index).0, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let mut data = DELETE_JOURNAL_ENTRY_DISCRIMINATOR.to_vec();
+    entry_index.serialize(&mut data).expect("writes to a Vec succeed");
+    Instruction { program_id: JOURNAL_PDA_OPTIMIZED_PROGRAM_ID, accounts, data }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_the_counter() {
+        let owner = Pubkey::new_unique();
+        let mut data = USER_JOURNAL_COUNTER_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.push(254);
+        assert_eq!(user_journal_counter(&data).unwrap(), UserJournalCounter { owner, last_entry_index: 7, bump: 254 });
+        data[0] ^= 1;
+        assert!(user_journal_counter(&data).is_err());
+    }
+
+    #[test]
+    fn create_layout() {
+        let owner = Pubkey::new_unique();
+        let instruction = create_journal_entry(&owner, 3, "t".into(), "m".into(), None);
+        assert_eq!(instruction.accounts[1].pubkey, find_journal_entry_state_pda(&owner, 3).0);
+        assert!(instruction.accounts[4].is_signer);
+        // discriminator, index, two one-byte strings, None
+        assert_eq!(instruction.data.len(), 8 + 8 + 5 + 5 + 1);
+        assert_eq!(instruction.data[8..16], 3u64.to_le_bytes());
+    }
+}
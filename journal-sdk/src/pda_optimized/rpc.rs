@@ -0,0 +1,117 @@
+// Reads and the retrying create, over RPC
+
+use std::time::Duration;
+
+use anchor_lang::error::ErrorCode;
+use solana_pubkey::Pubkey;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+
+use super::{create_journal_entry, journal_entry_state, user_journal_counter, JournalEntryState, UserJournalCounter};
+use crate::error::SdkError;
+use crate::filters;
+use crate::pda::{find_journal_entry_state_pda, find_user_journal_counter_pda, JOURNAL_PDA_OPTIMIZED_PROGRAM_ID};
+use crate::program_error::{JournalError, PdaOptimizedError};
+use crate::sender::{Sent, TransactionSender};
+use crate::signer::JournalSigner;
+
+#[derive(Debug, Clone)]
+pub struct CreateRetryConfig {
+    pub max_attempts: usize,
+    pub initial_backoff: Duration, // Doubled after every StaleCounter
+}
+
+impl Default for CreateRetryConfig {
+    fn default() -> Self {
+        CreateRetryConfig { max_attempts: 5, initial_backoff: Duration::from_millis(200) }
+    }
+}
+
+// Creates an entry at whatever index the owner's counter is at when it lands: reads the counter, sends the create
+// for that index, and on StaleCounter (another writer got there first) waits, re-reads and tries again, up to
+// max_attempts creates in all. Returns the entry's index. The owner's counter must already be initialized.
+pub async fn create_entry_safe(
+    sender: &TransactionSender,
+    owner: &dyn JournalSigner,
+    title: &str,
+    message: &str,
+    retry: &CreateRetryConfig,
+) -> Result<(u64, Sent), SdkError> {
+    let owner_pubkey = owner.pubkey();
+    let counter_address = find_user_journal_counter_pda(&owner_pubkey).0;
+    let mut backoff = retry.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        let counter = fetch_user_journal_counter(sender.rpc(), &owner_pubkey).await?;
+        let counter = counter
+            .ok_or(SdkError::Decode { address: counter_address, source: ErrorCode::AccountNotInitialized.into() })?;
+        let index = counter.last_entry_index;
+        let create = create_journal_entry(&owner_pubkey, index, title.to_string(), message.to_string(), None);
+        match sender.send(&[create], &owner_pubkey, &[owner]).await {
+            Ok(sent) => return Ok((index, sent)),
+            Err(err) if attempt < retry.max_attempts && is_stale_counter(&err) => {
+                tokio::time::sleep(backoff).await;
+                backoff = backoff.saturating_mul(2);
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+// None until initialize_user_journal_counter has run for `owner`
+pub async fn fetch_user_journal_counter(
+    rpc: &RpcClient,
+    owner: &Pubkey,
+) -> Result<Option<UserJournalCounter>, SdkError> {
+    let address = find_user_journal_counter_pda(owner).0;
+    let account = rpc.get_account_with_commitment(&address, rpc.commitment()).await?.value;
+    account
+        .map(|account| user_journal_counter(&account.data).map_err(|source| SdkError::Decode { address, source }))
+        .transpose()
+}
+
+// None for an index without an entry: deleted, or never created
+pub async fn fetch_entry_state(
+    rpc: &RpcClient,
+    owner: &Pubkey,
+    entry_index: u64,
+) -> Result<Option<JournalEntryState>, SdkError> {
+    let address = find_journal_entry_state_pda(owner, entry_index).0;
+    let account = rpc.get_account_with_commitment(&address, rpc.commitment()).await?.value;
+    account
+        .map(|account| journal_entry_state(&account.data).map_err(|source| SdkError::Decode { address, source }))
+        .transpose()
+}
+
+// Every current-layout entry of `owner` in one getProgramAccounts call, by index
+pub async fn fetch_entry_states_by_owner(
+    rpc: &RpcClient,
+    owner: &Pubkey,
+) -> Result<Vec<(Pubkey, JournalEntryState)>, SdkError> {
+    let mut filters = filters::journal_entry_states_by_owner(owner);
+    filters.push(filters::journal_entry_state_size());
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(solana_account_decoder_client_types::UiAccountEncoding::Base64),
+            commitment: Some(rpc.commitment()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let accounts = rpc.get_program_accounts_with_config(&JOURNAL_PDA_OPTIMIZED_PROGRAM_ID, config).await?;
+    let mut entries = accounts
+        .into_iter()
+        .map(|(address, account)| {
+            let entry = journal_entry_state(&account.data).map_err(|source| SdkError::Decode { address, source })?;
+            Ok((address, entry))
+        })
+        .collect::<Result<Vec<_>, SdkError>>()?;
+    entries.sort_by_key(|(_, entry)| entry.entry_index);
+    Ok(entries)
+}
+
+fn is_stale_counter(err: &SdkError) -> bool {
+    err.journal_error() == Some(JournalError::PdaOptimized(PdaOptimizedError::StaleCounter))
+}
@@ -0,0 +1,190 @@
+// The custom errors of both programs, so callers can match on a typed error instead of on
+// "custom program error: 0x1771" strings. Anchor numbers an #[error_code] enum's variants from 6000 in declaration
+// order, and both programs start there, so a code means nothing without the program that returned it.
+//
+// Keep these in step with the programs' #[error_code] enums: JournalProgramError with the `errors` of
+// journal-client/idls/journal.json, PdaOptimizedError with project-3-pdas/programs/new-crud-app/src/lib.rs.
+
+use solana_instruction::error::InstructionError;
+use solana_pubkey::Pubkey;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+
+use crate::pda::{JOURNAL_PDA_OPTIMIZED_PROGRAM_ID, JOURNAL_PROGRAM_ID};
+
+macro_rules! program_errors {
+    ($name:ident { $($variant:ident = $code:literal => $msg:literal,)* }) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+        #[repr(u32)]
+        pub enum $name {
+            $(#[error($msg)] $variant = $code,)*
+        }
+
+        impl $name {
+            pub fn from_code(code: u32) -> Option<Self> {
+                match code {
+                    $($code => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+
+            pub fn code(self) -> u32 {
+                self as u32
+            }
+        }
+    };
+}
+
+program_errors!(JournalProgramError {
+        TitleTooLong = 6000 => "Title exceeds maximum character limit.",
+        MessageTooLong = 6001 => "Message exceeds maximum character limit.",
+        Overflow = 6002 => "An overflow occurred.",
+        InvalidTipAmount = 6003 => "Tip amount must be greater than zero.",
+        NoTipsToWithdraw = 6004 => "There are no tips to withdraw.",
+        Unauthorized = 6005 => "Signer is not authorized to perform this action.",
+        InsufficientTreasuryBalance = 6006 => "Treasury balance is insufficient for this withdrawal.",
+        ProgramPaused = 6007 => "The program is paused.",
+        DailyLimitReached = 6008 => "Daily entry limit reached.",
+        NoDepositToReclaim = 6009 => "There is no deposit to reclaim.",
+        DepositLocked = 6010 => "The deposit is still locked.",
+        MissingSignatureVerification = 6011 =>
+            "Expected an ed25519 signature verification instruction before this one.",
+        InvalidSignatureVerification = 6012 => "The ed25519 signature verification does not match this entry.",
+        InvalidWriteNonce = 6013 => "Relayed write nonce does not match the profile's write_nonce.",
+        SessionExpired = 6014 => "The session key has expired.",
+        PermissionDenied = 6015 => "The delegate does not hold the permission this instruction requires.",
+        UpdateTimelocked = 6016 => "This entry is timelocked; use propose_update and execute_update.",
+        UpdateNotTimelocked = 6017 => "This entry does not require timelocked updates.",
+        UpdateDelayDecrease = 6018 => "The update delay can only be increased.",
+        UpdateStillLocked = 6019 => "The proposed update is still within its delay.",
+        WriteGateNotMet = 6020 => "The writer does not hold enough of the journal's gate token.",
+        NftMetadataTooLong = 6021 => "NFT symbol or URI exceeds the Metaplex length limit.",
+        InvalidPermissions = 6022 => "Permissions contain unknown bits.",
+        NftAlreadyMinted = 6023 => "This entry has already been minted as an NFT.",
+        CnftTreeNotConfigured = 6024 => "The merkle tree is not the one configured for compressed NFTs.",
+        ProfileMetadataTooLong = 6025 => "Display name, bio or avatar URI exceeds its maximum length.",
+        InvalidHandle = 6026 => "Handles must be 3-32 lowercase letters, digits or underscores.",
+        HandleTaken = 6027 => "This handle is already claimed.",
+        InvalidSlug = 6028 => "Slugs must be 1-64 lowercase letters, digits or hyphens.",
+        SlugTaken = 6029 => "This slug is already used by another of your entries.",
+        TitleClaimRequired = 6030 => "This profile requires unique titles; pass the title claim account.",
+        DuplicateTitle = 6031 => "Another entry already uses this title.",
+        TitleLocked = 6032 => "Titles can't be changed while unique titles are enabled.",
+        TitleStillInUse = 6033 => "The claimed entry still uses this title.",
+        ReplyParentNotPublic = 6034 => "Replies can only be posted to public entries.",
+        CommentTooLong = 6035 => "Comment exceeds maximum character limit.",
+        EntryNotPublic = 6036 => "This entry is not public.",
+        InvalidEmojiCode = 6037 => "Unknown emoji code.",
+        AlreadyReacted = 6038 => "You have already reacted to this entry.",
+        InvalidJournalName = 6039 => "Journal names must be 1-32 characters.",
+        JournalDescriptionTooLong = 6040 => "Journal description exceeds maximum character limit.",
+        FeedHeadRequired = 6041 => "Publishing an entry requires the feed head account.",
+        AlreadyPinned = 6042 => "This entry is already pinned.",
+        TooManyPinnedEntries = 6043 => "The profile already has the maximum number of pinned entries.",
+        NotPinned = 6044 => "This entry is not pinned.",
+        InvalidMigrationAccounts = 6045 => "Migration expects (old entry, new entry) account pairs in order.",
+        SponsoredEntryMigration = 6046 => "Sponsored entries can't be migrated.",
+        RecoveryDelayTooShort = 6047 => "The recovery delay is shorter than the minimum challenge period.",
+        NoRecoveryPending = 6048 => "There is no recovery in progress.",
+        RecoveryStillLocked = 6049 => "The recovery is still within its challenge period.",
+        InvalidPrompt = 6050 => "Prompts must be 1-140 characters.",
+        TooManyPrompts = 6051 => "The prompt list is full.",
+        PromptAlreadyDrawn = 6052 => "Today's prompt has already been drawn.",
+        PromptDrawPending = 6053 => "A prompt draw is already committed and waiting to be revealed.",
+        InvalidRandomness = 6054 => "The randomness account is not valid for this draw.",
+        RandomnessNotRevealed = 6055 => "The randomness has not been revealed in this slot.",
+        PromptExpired = 6056 => "The daily prompt is not from today.",
+        InvalidPriceUpdate = 6057 => "The price account is not a fully verified SOL/USD Pyth price update.",
+        StalePrice = 6058 => "The Pyth price is too old.",
+        InvalidLocation = 6059 => "Latitude or longitude is out of range.",
+        InvalidLocale = 6060 => "Locale must be a zero-padded ASCII language tag, or the locale family is unknown.",
+        InvalidCompressedEntryIndex = 6061 => "Compressed entry index is out of range.",
+        EntryHistoryFull = 6062 => "The profile's entry history tree is full.",
+        NotEntryReader = 6063 => "Only the entry's designated reader can mark it read.",
+        InvalidPseudonymProof = 6064 => "The signer and salt don't match the pseudonym's commitment.",
+        TooManyCpiCallers = 6065 => "Too many programs in the CPI allowlist.",
+        CpiCallerNotAllowed = 6066 => "The calling program is not allowed to invoke this instruction through CPI.",
+        AccountAlreadyCurrent = 6067 => "The account already uses the current layout.",
+        InvalidUtf8 = 6068 => "Entry text is not valid UTF-8.",
+        MessagePatchOutOfRange = 6069 => "The message patch starts past the end of the message or overflows it.",
+});
+
+program_errors!(PdaOptimizedError {
+        AccountAlreadyCurrent = 6000 => "The account already uses the current layout.",
+        StaleCounter = 6001 => "The entry counter has moved past the expected index; re-fetch it and retry.",
+        ConcurrentModification = 6002 => "The entry was edited since it was read; re-fetch it and retry.",
+        DuplicateContent = 6003 => "The entry has the same title and message as the previous one.",
+        IdempotencyKeyMismatch = 6004 => "Pass both the idempotency key and its record account, or neither.",
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum JournalError {
+    #[error("journal program: {0}")]
+    Journal(JournalProgramError),
+    #[error("journal_pda_optimized program: {0}")]
+    PdaOptimized(PdaOptimizedError),
+}
+
+impl JournalError {
+    // None for programs other than these two, and for codes that aren't theirs (Anchor's own constraint errors
+    // are below 6000)
+    pub fn from_code(program_id: &Pubkey, code: u32) -> Option<Self> {
+        if *program_id == JOURNAL_PROGRAM_ID {
+            JournalProgramError::from_code(code).map(JournalError::Journal)
+        } else if *program_id == JOURNAL_PDA_OPTIMIZED_PROGRAM_ID {
+            PdaOptimizedError::from_code(code).map(JournalError::PdaOptimized)
+        } else {
+            None
+        }
+    }
+
+    // `err` as returned for `transaction`, whose instructions say which program failed
+    pub fn from_transaction_error(err: &TransactionError, transaction: &Transaction) -> Option<Self> {
+        let TransactionError::InstructionError(index, InstructionError::Custom(code)) = err else {
+            return None;
+        };
+        Self::from_code(transaction.message.program_id(usize::from(*index))?, *code)
+    }
+
+    // From the runtime's "Program <id> failed: custom program error: 0x<code>" line in simulation or preflight
+    // logs. The first such line is the program that raised the error; callers up the CPI chain repeat it.
+    pub fn from_logs(logs: &[String]) -> Option<Self> {
+        logs.iter().find_map(|line| {
+            let (program_id, code) =
+                line.strip_prefix("Program ")?.split_once(" failed: custom program error: 0x")?;
+            Self::from_code(&program_id.parse().ok()?, u32::from_str_radix(code, 16).ok()?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_follow_declaration_order() {
+        assert_eq!(JournalProgramError::from_code(6000), Some(JournalProgramError::TitleTooLong));
+        assert_eq!(JournalProgramError::from_code(6029), Some(JournalProgramError::SlugTaken));
+        assert_eq!(JournalProgramError::MessagePatchOutOfRange.code(), 6069);
+        assert_eq!(PdaOptimizedError::from_code(6001), Some(PdaOptimizedError::StaleCounter));
+        assert_eq!(PdaOptimizedError::from_code(6005), None);
+    }
+
+    #[test]
+    fn from_logs() {
+        let logs = [
+            format!("Program {JOURNAL_PDA_OPTIMIZED_PROGRAM_ID} invoke [1]"),
+            "Program log: AnchorError occurred. Error Code: StaleCounter. Error Number: 6001.".to_string(),
+            format!("Program {JOURNAL_PDA_OPTIMIZED_PROGRAM_ID} consumed 5012 of 200000 compute units"),
+            format!("Program {JOURNAL_PDA_OPTIMIZED_PROGRAM_ID} failed: custom program error: 0x1771"),
+        ];
+        assert_eq!(JournalError::from_logs(&logs), Some(JournalError::PdaOptimized(PdaOptimizedError::StaleCounter)));
+        assert_eq!(JournalError::from_logs(&logs[..3]), None);
+    }
+
+    #[test]
+    fn other_programs_are_not_mapped() {
+        assert_eq!(JournalError::from_code(&Pubkey::new_from_array([7; 32]), 6000), None);
+        assert_eq!(JournalError::from_code(&JOURNAL_PROGRAM_ID, 2006), None);
+    }
+}
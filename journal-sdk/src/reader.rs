@@ -0,0 +1,187 @@
+// Batched reads of journal program accounts over RPC, through an AccountCache when the reader has one. The first
+// read checks that the deployed program is the version journal-client encodes (see crate::version).
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use solana_account_decoder_client_types::{UiAccountEncoding, UiDataSliceConfig};
+use solana_pubkey::Pubkey;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::request::MAX_MULTIPLE_ACCOUNTS;
+
+use crate::accounts::{self, EntrySummary, JournalEntry, KeyEnvelope, UserProfile, ENTRY_SUMMARY_LEN};
+use crate::cache::AccountCache;
+use crate::error::SdkError;
+use crate::filters;
+use crate::pda::{find_journal_entry_pda, find_key_envelope_pda, find_user_profile_pda, JOURNAL_PROGRAM_ID};
+use crate::version::VersionCheck;
+
+pub type EntryStream<'a> = BoxStream<'a, Result<JournalEntry, SdkError>>;
+
+pub struct JournalReader {
+    rpc: Arc<RpcClient>,
+    cache: Option<Arc<AccountCache>>,
+    version: VersionCheck,
+}
+
+impl JournalReader {
+    pub fn new(rpc: Arc<RpcClient>) -> Self {
+        JournalReader { rpc, cache: None, version: VersionCheck::default() }
+    }
+
+    // Shares `cache` with whatever else keeps it current, e.g. a task applying JournalEvents to it
+    pub fn with_cache(rpc: Arc<RpcClient>, cache: Arc<AccountCache>) -> Self {
+        JournalReader { rpc, cache: Some(cache), version: VersionCheck::default() }
+    }
+
+    pub async fn fetch_user_profile(&self, authority: &Pubkey) -> Result<Option<UserProfile>, SdkError> {
+        let address = find_user_profile_pda(authority).0;
+        let data = self.fetch_accounts(&[address]).await?.pop().flatten();
+        decode(&address, data, accounts::user_profile)
+    }
+
+    // The content key of an encrypted entry as wrapped to `reader`; open it with encryption::unwrap_key
+    pub async fn fetch_key_envelope(
+        &self,
+        journal_entry: &Pubkey,
+        reader: &Pubkey,
+    ) -> Result<Option<KeyEnvelope>, SdkError> {
+        let address = find_key_envelope_pda(journal_entry, reader).0;
+        let data = self.fetch_accounts(&[address]).await?.pop().flatten();
+        decode(&address, data, accounts::key_envelope)
+    }
+
+    // Every entry of `authority`, by id, from 0 up to the profile's entry_count when the stream is first polled.
+    // Ids without an entry account (deleted entries, zero-copy entries) are skipped; an authority without a
+    // profile has no entries. Entries are fetched a page of MAX_MULTIPLE_ACCOUNTS at a time as the stream is read.
+    pub fn entries(&self, authority: Pubkey) -> EntryStream<'_> {
+        let pages = stream::try_unfold((0, None), move |(next_id, entry_count)| {
+            self.next_entry_page(authority, next_id, entry_count)
+        });
+        pages.map_ok(|page| stream::iter(page.into_iter().map(Ok))).try_flatten().boxed()
+    }
+
+    // Existing entries from `next_id` up to a page further, and the state for the following page
+    async fn next_entry_page(
+        &self,
+        authority: Pubkey,
+        next_id: u64,
+        entry_count: Option<u64>,
+    ) -> Result<Option<(Vec<JournalEntry>, (u64, Option<u64>))>, SdkError> {
+        let entry_count = match entry_count {
+            Some(entry_count) => entry_count,
+            None => self.fetch_user_profile(&authority).await?.map_or(0, |profile| profile.entry_count),
+        };
+        if next_id >= entry_count {
+            return Ok(None);
+        }
+        let end = entry_count.min(next_id.saturating_add(MAX_MULTIPLE_ACCOUNTS as u64));
+        let page = self.fetch_entries(&authority, next_id..end).await?;
+        Ok(Some((page.into_iter().flatten().collect(), (end, Some(entry_count)))))
+    }
+
+    // One item per id in `range`, in order; None where there is no account, i.e. the entry was deleted or never
+    // created. Addresses go out MAX_MULTIPLE_ACCOUNTS (100) per getMultipleAccounts call.
+    pub async fn fetch_entries(
+        &self,
+        authority: &Pubkey,
+        range: Range<u64>,
+    ) -> Result<Vec<Option<JournalEntry>>, SdkError> {
+        let addresses: Vec<Pubkey> = range.map(|id| find_journal_entry_pda(authority, id).0).collect();
+        let accounts = self.fetch_accounts(&addresses).await?;
+        addresses.iter().zip(accounts).map(|(address, data)| decode(address, data, accounts::journal_entry)).collect()
+    }
+
+    // Every entry of `owner` (the entries' authority) in a single getProgramAccounts call, with their addresses,
+    // by id. The quickest way to read a whole profile, but it bypasses the cache, and entries still in an older
+    // layout are left out rather than failing the call.
+    pub async fn fetch_all_entries_by_owner(&self, owner: &Pubkey) -> Result<Vec<(Pubkey, JournalEntry)>, SdkError> {
+        let mut entries = self.fetch_entries_by_owner(owner, None, accounts::journal_entry).await?;
+        entries.sort_by_key(|(_, entry)| entry.id);
+        Ok(entries)
+    }
+
+    // fetch_all_entries_by_owner for list views: only the first ENTRY_SUMMARY_LEN bytes of each entry are sent
+    pub async fn fetch_entry_summaries_by_owner(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<(Pubkey, EntrySummary)>, SdkError> {
+        let slice = UiDataSliceConfig { offset: 0, length: ENTRY_SUMMARY_LEN };
+        let mut summaries = self.fetch_entries_by_owner(owner, Some(slice), accounts::entry_summary).await?;
+        summaries.sort_by_key(|(_, summary)| summary.id);
+        Ok(summaries)
+    }
+
+    async fn fetch_entries_by_owner<T>(
+        &self,
+        owner: &Pubkey,
+        data_slice: Option<UiDataSliceConfig>,
+        decoder: fn(&[u8]) -> anchor_lang::Result<T>,
+    ) -> Result<Vec<(Pubkey, T)>, SdkError> {
+        self.version.ensure(&self.rpc).await?;
+        let mut filters = filters::journal_entries_by_authority(owner);
+        filters.push(filters::journal_entry_size());
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice,
+                commitment: Some(self.rpc.commitment()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self.rpc.get_program_accounts_with_config(&JOURNAL_PROGRAM_ID, config).await?;
+        accounts
+            .into_iter()
+            .map(|(address, account)| {
+                let decoded = decoder(&account.data).map_err(|source| SdkError::Decode { address, source })?;
+                Ok((address, decoded))
+            })
+            .collect()
+    }
+
+    // The data of each account in `addresses`, in order. Cached accounts are served from the cache as long as it
+    // is current at the cluster's slot; the rest are fetched and cached at the slot they were read at.
+    async fn fetch_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Vec<u8>>>, SdkError> {
+        self.version.ensure(&self.rpc).await?;
+        let commitment = self.rpc.commitment();
+        let mut accounts = vec![None; addresses.len()];
+        let mut misses = Vec::new();
+        match &self.cache {
+            Some(cache) => {
+                cache.observe_slot(self.rpc.get_slot().await?);
+                for (index, address) in addresses.iter().enumerate() {
+                    match cache.get(address) {
+                        Some(data) => accounts[index] = data,
+                        None => misses.push(index),
+                    }
+                }
+            }
+            None => misses.extend(0..addresses.len()),
+        }
+
+        for batch in misses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let batch_addresses: Vec<Pubkey> = batch.iter().map(|&index| addresses[index]).collect();
+            let response = self.rpc.get_multiple_accounts_with_commitment(&batch_addresses, commitment).await?;
+            for (&index, account) in batch.iter().zip(response.value) {
+                let data = account.map(|account| account.data);
+                if let Some(cache) = &self.cache {
+                    cache.insert(&addresses[index], response.context.slot, data.clone());
+                }
+                accounts[index] = data;
+            }
+        }
+        Ok(accounts)
+    }
+}
+
+fn decode<T>(
+    address: &Pubkey,
+    data: Option<Vec<u8>>,
+    decoder: fn(&[u8]) -> anchor_lang::Result<T>,
+) -> Result<Option<T>, SdkError> {
+    data.map(|data| decoder(&data).map_err(|source| SdkError::Decode { address: *address, source })).transpose()
+}
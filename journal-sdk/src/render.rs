@@ -0,0 +1,131 @@
+// Plain-text renderings of entries for screen readers and other assistive clients. The title and message come
+// first, with labels. Next is when the entry was last written, spelled out in UTC. Reactions follow, as counts
+// with names. A title or message of only emoji is labeled as such, so it isn't read out without context.
+//
+// The program stores reactions as emoji codes into a palette that front-ends choose. Callers therefore pass the
+// palette's names, indexed by code. A code with no name is read as "reaction <code>".
+
+use time::OffsetDateTime;
+
+use crate::accounts::{JournalEntry, ReactionTally};
+use crate::encryption;
+
+pub fn render_entry(entry: &JournalEntry, reactions: Option<&ReactionTally>, reaction_names: &[&str]) -> String {
+    let mut lines = vec![labeled("Title", &entry.title)];
+    if encryption::is_encrypted(&entry.message) {
+        lines.push("The message is encrypted.".to_string());
+    } else {
+        lines.push(labeled("Message", &entry.message));
+    }
+    lines.push(format!("Last written {}.", spelled_out(entry.timestamp)));
+    lines.push(reactions.map_or_else(|| "No reactions.".to_string(), |tally| reaction_counts(tally, reaction_names)));
+    lines.join("\n")
+}
+
+// E.g. "Tuesday 14 May 2024 at 09:30 UTC"
+pub fn spelled_out(unix_timestamp: i64) -> String {
+    match OffsetDateTime::from_unix_timestamp(unix_timestamp) {
+        Ok(at) => format!(
+            "{} {} {} {} at {:02}:{:02} UTC",
+            at.weekday(),
+            at.day(),
+            at.month(),
+            at.year(),
+            at.hour(),
+            at.minute()
+        ),
+        Err(_) => format!("at Unix time {unix_timestamp}"),
+    }
+}
+
+fn labeled(label: &str, text: &str) -> String {
+    if text.is_empty() {
+        format!("{label}: none.")
+    } else if emoji_only(text) {
+        format!("{label}, emoji only: {text}")
+    } else {
+        format!("{label}: {text}")
+    }
+}
+
+// No letters or digits, and something beyond ASCII punctuation and spaces
+fn emoji_only(text: &str) -> bool {
+    !text.chars().any(char::is_alphanumeric) && !text.is_ascii()
+}
+
+// E.g. "Reactions: 3 heart, 1 laughing."
+fn reaction_counts(tally: &ReactionTally, reaction_names: &[&str]) -> String {
+    let counts: Vec<String> = tally
+        .counts
+        .iter()
+        .enumerate()
+        .filter(|&(_, &count)| count > 0)
+        .map(|(code, count)| match reaction_names.get(code) {
+            Some(name) => format!("{count} {name}"),
+            None => format!("{count} reaction {code}"),
+        })
+        .collect();
+    if counts.is_empty() {
+        "No reactions.".to_string()
+    } else {
+        format!("Reactions: {}.", counts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_pubkey::Pubkey;
+
+    use super::*;
+
+    fn entry(title: &str, message: &str) -> JournalEntry {
+        JournalEntry {
+            authority: Pubkey::new_unique(),
+            id: 0,
+            version: 1,
+            title: title.into(),
+            message: message.into(),
+            timestamp: 1_715_679_000,
+            tips_received: 0,
+            deposit_lamports: 0,
+            deposit_unlock_at: 0,
+            rent_payer: Pubkey::new_unique(),
+            update_delay_seconds: 0,
+            nft_mint: None,
+            is_public: false,
+            reply_to: None,
+            reply_count: 0,
+            comment_count: 0,
+            journal: None,
+            sort_order: 0,
+            prompt_id: None,
+            price_snapshot: None,
+            location: None,
+            weather_code: None,
+            reader: None,
+            flagged: false,
+            report_count: 0,
+            locale: [0; 5],
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn renders_for_screen_readers() {
+        let tally = ReactionTally { journal_entry: Pubkey::new_unique(), counts: [3, 0, 1, 0, 0, 0, 0, 2], bump: 255 };
+        assert_eq!(
+            render_entry(&entry("Day one", "🎉🎉"), Some(&tally), &["heart", "thumbs up", "laughing"]),
+            "Title: Day one\n\
+             Message, emoji only: 🎉🎉\n\
+             Last written Tuesday 14 May 2024 at 09:30 UTC.\n\
+             Reactions: 3 heart, 1 laughing, 2 reaction 7."
+        );
+        assert_eq!(
+            render_entry(&entry("...", "Hello 👋"), None, &[]),
+            "Title: ...\n\
+             Message: Hello 👋\n\
+             Last written Tuesday 14 May 2024 at 09:30 UTC.\n\
+             No reactions."
+        );
+    }
+}
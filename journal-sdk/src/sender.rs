@@ -0,0 +1,308 @@
+// Sends transactions so they land under congestion. Each send is priced from recent prioritization fees for the
+// accounts it writes, given a compute unit limit sized by simulating it, rebroadcast until it confirms or its
+// blockhash expires, and re-signed with a fresh blockhash after an expiry.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_hash::Hash;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig};
+use solana_signature::Signature;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+
+use crate::error::SdkError;
+use crate::events::{parse_deprecations, DeprecationNotice};
+use crate::pda::JOURNAL_PROGRAM_ID;
+use crate::signer::{sign_transaction, JournalSigner};
+use crate::version::VersionCheck;
+use crate::{nonce, offline};
+
+mod dual;
+pub use dual::{AccountState, Divergence, DualSent, DualWrite};
+#[cfg(feature = "jito")]
+mod jito;
+#[cfg(feature = "jito")]
+pub use jito::{JitoConfig, MAX_BUNDLE_TRANSACTIONS};
+
+// getRecentPrioritizationFees accepts at most this many accounts
+const MAX_FEE_ACCOUNTS: usize = 128;
+// The most a transaction may request; simulations run with it so they report what the transaction really uses
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+#[derive(Debug, Clone)]
+pub struct SenderConfig {
+    pub fee_percentile: u8,          // Of the recent per-slot fees paid for the written accounts; 50 is the median
+    pub max_compute_unit_price: u64, // Micro-lamports per compute unit, whatever recent fees were
+    pub compute_unit_margin: u32,    // Percent added to the simulated compute units for the limit
+    pub max_attempts: usize,         // Blockhashes to try before giving up with BlockhashExpired
+    pub rebroadcast_interval: Duration,
+}
+
+impl Default for SenderConfig {
+    fn default() -> Self {
+        SenderConfig {
+            fee_percentile: 75,
+            max_compute_unit_price: 1_000_000,
+            compute_unit_margin: 10,
+            max_attempts: 3,
+            rebroadcast_interval: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendOutcome {
+    Confirmed(Signature),
+    // The identical transaction had already landed, e.g. from an earlier call that timed out; not a failure
+    AlreadyProcessed(Signature),
+}
+
+impl SendOutcome {
+    pub fn signature(&self) -> Signature {
+        match self {
+            SendOutcome::Confirmed(signature) | SendOutcome::AlreadyProcessed(signature) => *signature,
+        }
+    }
+}
+
+// What send reports once the transaction has landed
+#[derive(Debug, Clone)]
+pub struct Sent {
+    pub outcome: SendOutcome,
+    // Noted by deprecated journal instructions the transaction ran, as its simulation showed. They still work
+    // until the notice's removed_in_version; pass the notices on as warnings so callers move off them in time.
+    pub deprecations: Vec<DeprecationNotice>,
+}
+
+pub struct TransactionSender {
+    rpc: Arc<RpcClient>,
+    config: SenderConfig,
+    version: VersionCheck,
+}
+
+impl TransactionSender {
+    pub fn new(rpc: Arc<RpcClient>, config: SenderConfig) -> Self {
+        TransactionSender { rpc, config, version: VersionCheck::default() }
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    // Prepends set_compute_unit_limit and set_compute_unit_price instructions to `instructions`, then signs and
+    // sends them until they are confirmed at the client's commitment. `instructions` must not set a compute budget
+    // themselves. A transaction error (in simulation, preflight or on chain) fails right away. The simulation that
+    // sizes the limit also collects the deprecation notices it returns.
+    pub async fn send(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        signers: &[&dyn JournalSigner],
+    ) -> Result<Sent, SdkError> {
+        self.check_version(instructions.iter().map(|instruction| &instruction.program_id)).await?;
+        let mut priced = Vec::with_capacity(instructions.len() + 2);
+        priced.push(ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT));
+        priced.push(ComputeBudgetInstruction::set_compute_unit_price(self.compute_unit_price(instructions).await?));
+        priced.extend_from_slice(instructions);
+        // Sized from the transaction as sent, compute budget instructions included
+        let (consumed, logs) = self.simulate(&priced, payer).await?;
+        let deprecations = parse_deprecations(&logs);
+        priced[0] = ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit(
+            consumed,
+            self.config.compute_unit_margin,
+        ));
+
+        for _ in 0..self.config.max_attempts {
+            let (blockhash, last_valid_block_height) =
+                self.rpc.get_latest_blockhash_with_commitment(self.rpc.commitment()).await?;
+            let mut transaction = offline::unsigned_transaction(&priced, payer, blockhash);
+            sign_transaction(&mut transaction, signers).await?;
+            let expiry = Expiry::BlockHeight(last_valid_block_height);
+            if let Some(outcome) = self.send_until_expired(&transaction, expiry).await? {
+                return Ok(Sent { outcome, deprecations });
+            }
+        }
+        Err(SdkError::BlockhashExpired { attempts: self.config.max_attempts })
+    }
+
+    // Sends a transaction signed elsewhere (see crate::offline) as is, without a compute budget of its own, until
+    // it is confirmed. `last_valid_block_height` is the one returned with its blockhash; it can't be re-signed
+    // here, so an expired blockhash is final. It isn't simulated either, so deprecation notices (see Sent) are left
+    // to crate::events::parse_deprecations on the transaction's logs.
+    pub async fn send_signed(
+        &self,
+        transaction: &Transaction,
+        last_valid_block_height: u64,
+    ) -> Result<SendOutcome, SdkError> {
+        let missing = offline::missing_signers(transaction);
+        if !missing.is_empty() {
+            return Err(SdkError::MissingSignatures(missing));
+        }
+        self.check_version(transaction.message.program_ids()).await?;
+        self.send_until_expired(transaction, Expiry::BlockHeight(last_valid_block_height))
+            .await?
+            .ok_or(SdkError::BlockhashExpired { attempts: 1 })
+    }
+
+    // Sends a signed durable nonce transaction (see crate::nonce) until it is confirmed, for as long as
+    // `nonce_account` still holds the nonce it was signed with
+    pub async fn send_durable(
+        &self,
+        transaction: &Transaction,
+        nonce_account: &Pubkey,
+    ) -> Result<SendOutcome, SdkError> {
+        let missing = offline::missing_signers(transaction);
+        if !missing.is_empty() {
+            return Err(SdkError::MissingSignatures(missing));
+        }
+        self.check_version(transaction.message.program_ids()).await?;
+        let expiry = Expiry::Nonce { account: *nonce_account, nonce: transaction.message.recent_blockhash };
+        self.send_until_expired(transaction, expiry).await?.ok_or(SdkError::NonceAdvanced(*nonce_account))
+    }
+
+    // UnsupportedProgramVersion if any of `program_ids` is the journal program and its deployed version isn't the
+    // one journal-client encodes (see crate::version)
+    async fn check_version(&self, program_ids: impl IntoIterator<Item = &Pubkey>) -> Result<(), SdkError> {
+        if program_ids.into_iter().any(|program_id| *program_id == JOURNAL_PROGRAM_ID) {
+            self.version.ensure(&self.rpc).await?;
+        }
+        Ok(())
+    }
+
+    // fee_percentile of the recent prioritization fees for the writable accounts, capped at max_compute_unit_price
+    pub async fn compute_unit_price(&self, instructions: &[Instruction]) -> Result<u64, SdkError> {
+        let mut writable: Vec<Pubkey> = instructions
+            .iter()
+            .flat_map(|instruction| &instruction.accounts)
+            .filter(|meta| meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .collect();
+        writable.sort_unstable();
+        writable.dedup();
+        writable.truncate(MAX_FEE_ACCOUNTS);
+
+        let recent_fees = self.rpc.get_recent_prioritization_fees(&writable).await?;
+        let mut fees: Vec<u64> = recent_fees.iter().map(|fee| fee.prioritization_fee).collect();
+        Ok(percentile(&mut fees, self.config.fee_percentile).min(self.config.max_compute_unit_price))
+    }
+
+    // Compute units `instructions` consume when simulated unsigned against the latest blockhash. Give them a
+    // set_compute_unit_limit of MAX_COMPUTE_UNIT_LIMIT, or the simulation stops at the default limit.
+    pub async fn simulate_compute_units(&self, instructions: &[Instruction], payer: &Pubkey) -> Result<u64, SdkError> {
+        Ok(self.simulate(instructions, payer).await?.0)
+    }
+
+    // simulate_compute_units, with the simulation's logs
+    async fn simulate(&self, instructions: &[Instruction], payer: &Pubkey) -> Result<(u64, Vec<String>), SdkError> {
+        let transaction = Transaction::new_with_payer(instructions, Some(payer));
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.rpc.commitment()),
+            ..Default::default()
+        };
+        let result = self.rpc.simulate_transaction_with_config(&transaction, config).await?.value;
+        if let Some(err) = result.err {
+            return Err(SdkError::Simulation { err, logs: result.logs.unwrap_or_default() });
+        }
+        let consumed = result.units_consumed.unwrap_or(u64::from(MAX_COMPUTE_UNIT_LIMIT));
+        Ok((consumed, result.logs.unwrap_or_default()))
+    }
+
+    // Broadcasts `transaction` every rebroadcast_interval until it is confirmed; None once it has expired without
+    // landing, so it is safe to re-sign
+    async fn send_until_expired(
+        &self,
+        transaction: &Transaction,
+        expiry: Expiry,
+    ) -> Result<Option<SendOutcome>, SdkError> {
+        let signature = transaction.signatures[0];
+        let commitment = self.rpc.commitment();
+        let mut already_processed = false;
+        // Preflight only on the first broadcast; rebroadcasts of the same transaction would just repeat it
+        let mut skip_preflight = false;
+        loop {
+            let config = RpcSendTransactionConfig { skip_preflight, max_retries: Some(0), ..Default::default() };
+            if let Err(err) = self.rpc.send_transaction_with_config(transaction, config).await {
+                match err.get_transaction_error() {
+                    Some(TransactionError::AlreadyProcessed) => already_processed = true,
+                    // The node is behind the blockhash, or the nonce has moved on; try again with one it knows
+                    Some(TransactionError::BlockhashNotFound) => return Ok(None),
+                    Some(err) => return Err(SdkError::transaction_failed(transaction, err)),
+                    None => return Err(err.into()),
+                }
+            }
+            skip_preflight = true;
+            tokio::time::sleep(self.config.rebroadcast_interval).await;
+
+            // Expiry first: if the transaction had expired before the status was read and the status is still
+            // empty, it can no longer land
+            let expired = self.has_expired(&expiry).await?;
+            match self.rpc.get_signature_status_with_commitment(&signature, commitment).await? {
+                Some(Ok(())) if already_processed => return Ok(Some(SendOutcome::AlreadyProcessed(signature))),
+                Some(Ok(())) => return Ok(Some(SendOutcome::Confirmed(signature))),
+                Some(Err(err)) => return Err(SdkError::transaction_failed(transaction, err)),
+                None if expired => return Ok(None),
+                None => {}
+            }
+        }
+    }
+
+    async fn has_expired(&self, expiry: &Expiry) -> Result<bool, SdkError> {
+        Ok(match expiry {
+            Expiry::BlockHeight(last_valid_block_height) => {
+                self.rpc.get_block_height().await? > *last_valid_block_height
+            }
+            Expiry::Nonce { account, nonce } => {
+                nonce::fetch_nonce_data(&self.rpc, account).await?.blockhash() != *nonce
+            }
+        })
+    }
+}
+
+// What ends a transaction's validity: its blockhash ageing out, or its durable nonce being advanced
+enum Expiry {
+    BlockHeight(u64),
+    Nonce { account: Pubkey, nonce: Hash },
+}
+
+fn compute_unit_limit(consumed: u64, margin: u32) -> u32 {
+    let limit = consumed.saturating_add(consumed.saturating_mul(u64::from(margin)) / 100);
+    u32::try_from(limit).unwrap_or(u32::MAX).min(MAX_COMPUTE_UNIT_LIMIT)
+}
+
+fn percentile(values: &mut [u64], percentile: u8) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort_unstable();
+    let rank = (values.len() - 1) * usize::from(percentile.min(100)) / 100;
+    values[rank]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_unit_limit, percentile, MAX_COMPUTE_UNIT_LIMIT};
+
+    #[test]
+    fn percentile_of_fees() {
+        let mut fees = [500, 0, 100, 0, 2_000];
+        assert_eq!(percentile(&mut fees, 0), 0);
+        assert_eq!(percentile(&mut fees, 50), 100);
+        assert_eq!(percentile(&mut fees, 75), 500);
+        assert_eq!(percentile(&mut fees, 100), 2_000);
+        assert_eq!(percentile(&mut [], 75), 0);
+    }
+
+    #[test]
+    fn compute_unit_limit_with_margin() {
+        assert_eq!(compute_unit_limit(20_000, 10), 22_000);
+        assert_eq!(compute_unit_limit(20_000, 0), 20_000);
+        assert_eq!(compute_unit_limit(1_300_000, 10), MAX_COMPUTE_UNIT_LIMIT);
+    }
+}
@@ -0,0 +1,169 @@
+// Dual writes for a migration window, such as the move to a new seed scheme: each write is sent to the current
+// deployment, then, built for the new one, to a canary deployment on the same cluster. Once both have landed the
+// accounts each wrote are read back and compared. The current deployment stays the source of truth, so a canary
+// write that fails or leaves different state is reported as a Divergence rather than failing the write.
+//
+// Only what users see is compared, since bumps, versions and addresses differ between deployments by design:
+// entries by authority, id, title, message and visibility, profiles by authority and entry count. Any other
+// account is compared byte for byte.
+
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_rpc_client_api::request::MAX_MULTIPLE_ACCOUNTS;
+
+use super::{Sent, TransactionSender};
+use crate::accounts;
+use crate::error::SdkError;
+use crate::signer::JournalSigner;
+
+// One write, built once for each deployment. Seeds differ between them, so the caller derives the canary's
+// addresses; `compare` pairs each account the current write touches with its counterpart on the canary.
+#[derive(Debug, Clone)]
+pub struct DualWrite {
+    pub current: Vec<Instruction>,
+    pub canary: Vec<Instruction>,
+    pub compare: Vec<(Pubkey, Pubkey)>, // (current, canary)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountState {
+    Missing,
+    Entry { authority: Pubkey, id: u64, title: String, message: String, is_public: bool },
+    Profile { authority: Pubkey, entry_count: u64 },
+    Other(Vec<u8>),
+}
+
+#[derive(Debug)]
+pub enum Divergence {
+    CanaryFailed(SdkError), // Nothing was compared
+    State { current: Pubkey, canary: Pubkey, current_state: AccountState, canary_state: AccountState },
+}
+
+#[derive(Debug)]
+pub struct DualSent {
+    pub current: Sent,
+    pub divergences: Vec<Divergence>, // Empty when the canary agreed
+}
+
+impl AccountState {
+    // From an account's data, or None if it doesn't exist
+    pub fn of(data: Option<&[u8]>) -> AccountState {
+        let Some(data) = data else {
+            return AccountState::Missing;
+        };
+        if let Ok(entry) = accounts::journal_entry(data) {
+            return AccountState::Entry {
+                authority: entry.authority,
+                id: entry.id,
+                title: entry.title,
+                message: entry.message,
+                is_public: entry.is_public,
+            };
+        }
+        if let Ok(profile) = accounts::user_profile(data) {
+            return AccountState::Profile { authority: profile.authority, entry_count: profile.entry_count };
+        }
+        AccountState::Other(data.to_vec())
+    }
+}
+
+impl TransactionSender {
+    // Sends `write.current`, then `write.canary`, and compares the paired accounts at the client's commitment.
+    // Errors are the current write's, or a failure reading the accounts back; the canary's own are divergences.
+    pub async fn send_dual(
+        &self,
+        write: &DualWrite,
+        payer: &Pubkey,
+        signers: &[&dyn JournalSigner],
+    ) -> Result<DualSent, SdkError> {
+        let current = self.send(&write.current, payer, signers).await?;
+        if let Err(err) = self.send(&write.canary, payer, signers).await {
+            return Ok(DualSent { current, divergences: vec![Divergence::CanaryFailed(err)] });
+        }
+
+        let addresses: Vec<Pubkey> = write.compare.iter().flat_map(|&(current, canary)| [current, canary]).collect();
+        let mut fetched = Vec::with_capacity(addresses.len());
+        for batch in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let accounts = self.rpc.get_multiple_accounts_with_commitment(batch, self.rpc.commitment()).await?.value;
+            fetched.extend(accounts.into_iter().map(|account| account.map(|account| account.data)));
+        }
+        Ok(DualSent { current, divergences: diverging(&write.compare, &fetched) })
+    }
+}
+
+// The pairs of `compare` whose accounts differ; `fetched` holds the data of each pair's current then canary account
+fn diverging(compare: &[(Pubkey, Pubkey)], fetched: &[Option<Vec<u8>>]) -> Vec<Divergence> {
+    compare
+        .iter()
+        .zip(fetched.chunks(2))
+        .filter_map(|(&(current, canary), pair)| {
+            let current_state = AccountState::of(pair[0].as_deref());
+            let canary_state = AccountState::of(pair[1].as_deref());
+            (current_state != canary_state).then_some(Divergence::State {
+                current,
+                canary,
+                current_state,
+                canary_state,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::AccountSerialize;
+    use journal_client::accounts::JournalEntry;
+
+    use super::*;
+
+    fn entry_data(message: &str, bump: u8) -> Option<Vec<u8>> {
+        let authority = Pubkey::new_from_array([7; 32]);
+        let entry = JournalEntry {
+            authority,
+            id: 3,
+            version: 1,
+            title: "Day one".into(),
+            message: message.into(),
+            timestamp: 0,
+            tips_received: 0,
+            deposit_lamports: 0,
+            deposit_unlock_at: 0,
+            rent_payer: authority,
+            update_delay_seconds: 0,
+            nft_mint: None,
+            is_public: false,
+            reply_to: None,
+            reply_count: 0,
+            comment_count: 0,
+            journal: None,
+            sort_order: 0,
+            prompt_id: None,
+            price_snapshot: None,
+            location: None,
+            weather_code: None,
+            reader: None,
+            flagged: false,
+            report_count: 0,
+            locale: [0; 5],
+            bump,
+        };
+        let mut data = Vec::new();
+        entry.try_serialize(&mut data).unwrap();
+        Some(data)
+    }
+
+    #[test]
+    fn compares_what_users_see() {
+        let pair = (Pubkey::new_unique(), Pubkey::new_unique());
+        let same = [entry_data("Hello", 254), entry_data("Hello", 251)];
+        assert!(diverging(&[pair], &same).is_empty());
+
+        let edited = [entry_data("Hello", 254), entry_data("Hello!", 254)];
+        let missing = [entry_data("Hello", 254), None];
+        let raw = [Some(vec![1, 2]), Some(Vec::new())];
+        for fetched in [edited, missing, raw] {
+            let divergences = diverging(&[pair], &fetched);
+            assert!(matches!(divergences[..], [Divergence::State { current, .. }] if current == pair.0));
+        }
+    }
+}
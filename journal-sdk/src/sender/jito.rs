@@ -0,0 +1,99 @@
+// Bundle submission through a Jito block engine. A bundle's transactions execute in order within one slot and
+// land all together or not at all, so a multi-transaction flow (a profile plus a batch of entries, an import)
+// can't be left half applied with the counter and entries out of step. The block engine only accepts bundles
+// that tip one of its tip accounts; the tip replaces the compute unit price `send` attaches.
+
+use std::iter;
+
+use serde_json::json;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::request::RpcRequest;
+use solana_signature::Signature;
+use solana_system_interface::instruction::transfer;
+
+use super::TransactionSender;
+use crate::error::SdkError;
+use crate::offline;
+use crate::signer::{sign_transaction, JournalSigner};
+
+// The block engine rejects bundles with more transactions than this
+pub const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct JitoConfig {
+    pub block_engine_url: String, // The bundles endpoint, e.g. https://mainnet.block-engine.jito.wtf/api/v1/bundles
+    pub tip_lamports: u64,        // Paid by the payer; the block engine enforces a minimum (1000 lamports)
+}
+
+impl TransactionSender {
+    // Sends each of `transactions` as one transaction of a single bundle and waits until the bundle has landed,
+    // returning the signatures in order. The tip transfer is appended to the last transaction, so it is only paid
+    // if every transaction lands. Each transaction is signed by whichever of `signers` it needs.
+    pub async fn send_bundle(
+        &self,
+        jito: &JitoConfig,
+        transactions: &[&[Instruction]],
+        payer: &Pubkey,
+        signers: &[&dyn JournalSigner],
+    ) -> Result<Vec<Signature>, SdkError> {
+        let Some((last, rest)) = transactions.split_last() else {
+            return Ok(Vec::new());
+        };
+        if transactions.len() > MAX_BUNDLE_TRANSACTIONS {
+            return Err(SdkError::Bundle(format!(
+                "{} transactions, at most {MAX_BUNDLE_TRANSACTIONS} fit in a bundle",
+                transactions.len()
+            )));
+        }
+
+        let program_ids = transactions.iter().copied().flatten().map(|instruction| &instruction.program_id);
+        self.check_version(program_ids).await?;
+
+        let block_engine = RpcClient::new(jito.block_engine_url.clone());
+        let tip_accounts: Vec<String> =
+            block_engine.send(RpcRequest::Custom { method: "getTipAccounts" }, json!([])).await?;
+        if tip_accounts.is_empty() {
+            return Err(SdkError::Bundle("the block engine returned no tip accounts".to_string()));
+        }
+
+        let commitment = self.rpc.commitment();
+        for _ in 0..self.config.max_attempts {
+            let (blockhash, last_valid_block_height) = self.rpc.get_latest_blockhash_with_commitment(commitment).await?;
+            // Spread tips over the tip accounts; bundles tipping the same one contend for its write lock
+            let tip_account = &tip_accounts[usize::from(blockhash.as_ref()[0]) % tip_accounts.len()];
+            let tip_account: Pubkey = tip_account
+                .parse()
+                .map_err(|_| SdkError::Bundle(format!("invalid tip account {tip_account}")))?;
+            let mut tipped = last.to_vec();
+            tipped.push(transfer(payer, &tip_account, jito.tip_lamports));
+
+            let mut bundle = Vec::with_capacity(transactions.len());
+            for instructions in rest.iter().copied().chain(iter::once(tipped.as_slice())) {
+                let mut transaction = offline::unsigned_transaction(instructions, payer, blockhash);
+                sign_transaction(&mut transaction, signers).await?;
+                bundle.push(transaction);
+            }
+            let encoded: Vec<String> = bundle.iter().map(offline::encode).collect();
+            let _bundle_id: String = block_engine
+                .send(RpcRequest::Custom { method: "sendBundle" }, json!([encoded, { "encoding": "base64" }]))
+                .await?;
+
+            // The bundle lands atomically, so the tipping transaction landing means all of them did
+            let signatures: Vec<Signature> = bundle.iter().map(|transaction| transaction.signatures[0]).collect();
+            let tip_signature = signatures[signatures.len() - 1];
+            loop {
+                tokio::time::sleep(self.config.rebroadcast_interval).await;
+                let block_height = self.rpc.get_block_height().await?;
+                match self.rpc.get_signature_status_with_commitment(&tip_signature, commitment).await? {
+                    Some(Ok(())) => return Ok(signatures),
+                    Some(Err(err)) => return Err(SdkError::transaction_failed(&bundle[bundle.len() - 1], err)),
+                    None if block_height > last_valid_block_height => break,
+                    None => {}
+                }
+            }
+        }
+        Err(SdkError::BlockhashExpired { attempts: self.config.max_attempts })
+    }
+}
@@ -0,0 +1,130 @@
+// Signing behind a trait, so keys can stay in a KMS, an HSM or a remote signing service. Signing is async because
+// those sign over the network. Every solana_signer::Signer is a JournalSigner, which covers Keypair and the Ledger
+// signer from solana-remote-wallet; RemoteSigner adapts an async signing function.
+
+use std::future::Future;
+
+use futures::future::{self, BoxFuture, FutureExt};
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_signer::{Signer, SignerError};
+use solana_transaction::Transaction;
+
+use crate::error::SdkError;
+use crate::offline;
+
+pub trait JournalSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+
+    // `message` is the serialized transaction message (offline::message_bytes)
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, SignerError>>;
+}
+
+impl<T: Signer + Send + Sync + ?Sized> JournalSigner for T {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(self)
+    }
+
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, SignerError>> {
+        future::ready(self.try_sign_message(message)).boxed()
+    }
+}
+
+// A JournalSigner from a key's address and a function that has it sign a message somewhere else, e.g. a KMS
+// client call. Each call gets its own copy of the message.
+pub struct RemoteSigner<F> {
+    pubkey: Pubkey,
+    sign: F,
+}
+
+impl<F> RemoteSigner<F> {
+    pub fn new(pubkey: Pubkey, sign: F) -> Self {
+        RemoteSigner { pubkey, sign }
+    }
+}
+
+impl<F, Fut> JournalSigner for RemoteSigner<F>
+where
+    F: Fn(Vec<u8>) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Signature, SignerError>> + Send + 'static,
+{
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, SignerError>> {
+        (self.sign)(message.to_vec()).boxed()
+    }
+}
+
+// Has each of the transaction's required signers that is among `signers` sign it, concurrently, checking every
+// signature before it is placed. Signers the transaction doesn't need are ignored; signers it needs that are
+// missing from `signers` are an error unless they have already signed.
+pub async fn sign_transaction(transaction: &mut Transaction, signers: &[&dyn JournalSigner]) -> Result<(), SdkError> {
+    let message = offline::message_bytes(transaction);
+    let mut signing = Vec::new();
+    let mut missing = Vec::new();
+    for required in offline::missing_signers(transaction) {
+        match signers.iter().find(|signer| signer.pubkey() == required) {
+            Some(signer) => signing.push(signer.sign_message(&message).map(move |signature| (required, signature))),
+            None => missing.push(required),
+        }
+    }
+    if !missing.is_empty() {
+        return Err(SdkError::MissingSignatures(missing));
+    }
+
+    for (signer, signature) in future::join_all(signing).await {
+        offline::add_signature(transaction, &signer, signature?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use solana_hash::Hash;
+    use solana_keypair::Keypair;
+
+    use super::*;
+    use crate::instructions;
+
+    fn unsigned(authority: &Pubkey, payer: &Pubkey) -> Transaction {
+        let instruction = instructions::add_journal_entry(authority, payer, 0, "title".into(), "message".into());
+        offline::unsigned_transaction(&[instruction], payer, Hash::new_from_array([9; 32]))
+    }
+
+    #[test]
+    fn keypairs_and_remote_signers_together() {
+        let payer = Keypair::new();
+        let remote_key = Keypair::new();
+        let remote_pubkey = Signer::pubkey(&remote_key);
+        // Stands in for a KMS: the key lives behind the function, not with the caller
+        let remote = RemoteSigner::new(remote_pubkey, move |message: Vec<u8>| {
+            future::ready(remote_key.try_sign_message(&message))
+        });
+
+        let mut transaction = unsigned(&remote_pubkey, &Signer::pubkey(&payer));
+        block_on(sign_transaction(&mut transaction, &[&payer, &remote])).unwrap();
+        assert!(offline::missing_signers(&transaction).is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_and_missing_signatures() {
+        let payer = Keypair::new();
+        let authority = Keypair::new();
+        let impostor = RemoteSigner::new(Signer::pubkey(&authority), |_message: Vec<u8>| {
+            future::ready(Ok(Signature::from([1; 64])))
+        });
+
+        let mut transaction = unsigned(&Signer::pubkey(&authority), &Signer::pubkey(&payer));
+        assert!(matches!(
+            block_on(sign_transaction(&mut transaction, &[&payer, &impostor])),
+            Err(SdkError::InvalidSignature(_))
+        ));
+        assert!(matches!(
+            block_on(sign_transaction(&mut transaction, &[&payer])),
+            Err(SdkError::MissingSignatures(missing)) if missing == [Signer::pubkey(&authority)]
+        ));
+    }
+}
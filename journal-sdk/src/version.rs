@@ -0,0 +1,93 @@
+// The journal program records the version of its instruction encoding in ProgramConfig.program_version, and
+// journal-client is generated from the IDL of one version. JournalReader and TransactionSender check the two match
+// before their first call that touches the journal program, so a stale client fails with UnsupportedProgramVersion
+// instead of sending instructions an upgraded program would misread.
+
+#[cfg(feature = "rpc")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub use journal_client::constants::PROGRAM_VERSION as SUPPORTED_PROGRAM_VERSION;
+#[cfg(feature = "rpc")]
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+
+use crate::accounts;
+use crate::error::SdkError;
+use crate::pda::find_program_config_pda;
+
+// Ok when `program_config` (the ProgramConfig account data) was written by the version this client encodes
+pub fn check_program_config(program_config: &[u8]) -> Result<(), SdkError> {
+    let address = find_program_config_pda().0;
+    let config = accounts::program_config(program_config).map_err(|source| SdkError::Decode { address, source })?;
+    if config.program_version != SUPPORTED_PROGRAM_VERSION {
+        return Err(SdkError::UnsupportedProgramVersion {
+            deployed: config.program_version,
+            supported: SUPPORTED_PROGRAM_VERSION,
+        });
+    }
+    Ok(())
+}
+
+// Reads the ProgramConfig once; later calls return right away. A cluster without a ProgramConfig passes without
+// being remembered, since the program can't be used there until the admin creates one.
+#[cfg(feature = "rpc")]
+#[derive(Default)]
+pub(crate) struct VersionCheck {
+    passed: AtomicBool,
+}
+
+#[cfg(feature = "rpc")]
+impl VersionCheck {
+    pub(crate) async fn ensure(&self, rpc: &RpcClient) -> Result<(), SdkError> {
+        if self.passed.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let address = find_program_config_pda().0;
+        if let Some(account) = rpc.get_account_with_commitment(&address, rpc.commitment()).await?.value {
+            check_program_config(&account.data)?;
+            self.passed.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::AccountSerialize;
+    use journal_client::accounts::ProgramConfig;
+    use journal_client::types::LocaleLimits;
+    use solana_pubkey::Pubkey;
+
+    use super::*;
+
+    fn program_config(program_version: u16) -> Vec<u8> {
+        let config = ProgramConfig {
+            admin: Pubkey::new_unique(),
+            pending_admin: None,
+            fee_lamports: 0,
+            paused: false,
+            program_version,
+            max_entries_per_day: 0,
+            entry_deposit_lamports: 0,
+            deposit_cooldown_seconds: 0,
+            cnft_merkle_tree: None,
+            locale_limits: [LocaleLimits { title_chars: 50, message_chars: 280 }; 2],
+            moderator: None,
+            cpi_allowlist: Vec::new(),
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn only_the_generated_version_is_supported() {
+        assert!(check_program_config(&program_config(SUPPORTED_PROGRAM_VERSION)).is_ok());
+        match check_program_config(&program_config(SUPPORTED_PROGRAM_VERSION + 1)) {
+            Err(SdkError::UnsupportedProgramVersion { deployed, supported }) => {
+                assert_eq!((deployed, supported), (SUPPORTED_PROGRAM_VERSION + 1, SUPPORTED_PROGRAM_VERSION));
+            }
+            other => panic!("expected UnsupportedProgramVersion, got {other:?}"),
+        }
+    }
+}
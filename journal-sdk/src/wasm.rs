@@ -0,0 +1,236 @@
+// Bindings for browser apps (feature "wasm", built without the default "rpc"), so they share this crate's PDA
+// derivation, account decoding and encryption instead of porting them to TypeScript:
+//
+//   wasm-pack build --target web -- --no-default-features --features wasm
+//
+// Addresses cross as base58 strings and u64s as BigInts. Reads go over fetch as plain JSON-RPC, one
+// getMultipleAccounts call per hundred accounts; sending is left to the app's wallet adapter, which signs in the
+// browser anyway.
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde_json::{json, Value};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use wasm_bindgen::prelude::*;
+use x25519_dalek::StaticSecret;
+
+use crate::accounts;
+use crate::encryption::{self, ContentKey};
+use crate::error::SdkError;
+use crate::pda::{find_journal_entry_pda, find_key_envelope_pda, find_reaction_tally_pda, find_user_profile_pda};
+use crate::render;
+
+// getMultipleAccounts accepts at most this many addresses
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+#[wasm_bindgen(getter_with_clone)]
+pub struct Entry {
+    pub address: String,
+    pub authority: String,
+    pub id: u64,
+    pub title: String,
+    pub message: String, // As stored: still encrypted for encrypted entries (isEncrypted, decryptMessage)
+    pub timestamp: i64,
+    #[wasm_bindgen(js_name = isPublic)]
+    pub is_public: bool,
+}
+
+#[wasm_bindgen(getter_with_clone)]
+pub struct Profile {
+    pub address: String,
+    pub authority: String,
+    #[wasm_bindgen(js_name = entryCount)]
+    pub entry_count: u64, // Ids run from 0 up to it; deleted ones have no entry
+    #[wasm_bindgen(js_name = totalEntriesCreated)]
+    pub total_entries_created: u64,
+    #[wasm_bindgen(js_name = lastEntryTimestamp)]
+    pub last_entry_timestamp: i64,
+}
+
+// What grant_key_envelope takes
+#[wasm_bindgen(getter_with_clone)]
+pub struct WrappedKey {
+    #[wasm_bindgen(js_name = ephemeralPublicKey)]
+    pub ephemeral_public_key: Vec<u8>,
+    pub nonce: Vec<u8>,
+    #[wasm_bindgen(js_name = wrappedKey)]
+    pub wrapped_key: Vec<u8>,
+}
+
+#[wasm_bindgen(js_name = userProfileAddress)]
+pub fn user_profile_address(authority: &str) -> Result<String, JsError> {
+    Ok(find_user_profile_pda(&authority.parse()?).0.to_string())
+}
+
+#[wasm_bindgen(js_name = journalEntryAddress)]
+pub fn journal_entry_address(authority: &str, id: u64) -> Result<String, JsError> {
+    Ok(find_journal_entry_pda(&authority.parse()?, id).0.to_string())
+}
+
+#[wasm_bindgen(js_name = keyEnvelopeAddress)]
+pub fn key_envelope_address(journal_entry: &str, reader: &str) -> Result<String, JsError> {
+    Ok(find_key_envelope_pda(&journal_entry.parse()?, &reader.parse()?).0.to_string())
+}
+
+#[wasm_bindgen(js_name = reactionTallyAddress)]
+pub fn reaction_tally_address(journal_entry: &str) -> Result<String, JsError> {
+    Ok(find_reaction_tally_pda(&journal_entry.parse()?).0.to_string())
+}
+
+#[wasm_bindgen(js_name = decodeJournalEntry)]
+pub fn decode_journal_entry(address: &str, data: &[u8]) -> Result<Entry, JsError> {
+    let address: Pubkey = address.parse()?;
+    let entry = accounts::journal_entry(data).map_err(|source| SdkError::Decode { address, source })?;
+    Ok(Entry {
+        address: address.to_string(),
+        authority: entry.authority.to_string(),
+        id: entry.id,
+        title: entry.title,
+        message: entry.message,
+        timestamp: entry.timestamp,
+        is_public: entry.is_public,
+    })
+}
+
+#[wasm_bindgen(js_name = decodeUserProfile)]
+pub fn decode_user_profile(address: &str, data: &[u8]) -> Result<Profile, JsError> {
+    let address: Pubkey = address.parse()?;
+    let profile = accounts::user_profile(data).map_err(|source| SdkError::Decode { address, source })?;
+    Ok(Profile {
+        address: address.to_string(),
+        authority: profile.authority.to_string(),
+        entry_count: profile.entry_count,
+        total_entries_created: profile.total_entries_created,
+        last_entry_timestamp: profile.last_entry_timestamp,
+    })
+}
+
+// A screen-reader-friendly rendering of the entry (crate::render); `tally` is its reaction tally's data, if it
+// has one, and `reaction_names` names the app's reaction palette by emoji code
+#[wasm_bindgen(js_name = renderEntry)]
+pub fn render_entry(
+    address: &str,
+    data: &[u8],
+    tally: Option<Vec<u8>>,
+    reaction_names: Vec<String>,
+) -> Result<String, JsError> {
+    let address: Pubkey = address.parse()?;
+    let entry = accounts::journal_entry(data).map_err(|source| SdkError::Decode { address, source })?;
+    let tally = tally.map(|tally| accounts::reaction_tally(&tally)).transpose()?;
+    let reaction_names: Vec<&str> = reaction_names.iter().map(String::as_str).collect();
+    Ok(render::render_entry(&entry, tally.as_ref(), &reaction_names))
+}
+
+#[wasm_bindgen(js_name = generateContentKey)]
+pub fn generate_content_key() -> Vec<u8> {
+    ContentKey::generate().to_bytes().to_vec()
+}
+
+#[wasm_bindgen(js_name = isEncrypted)]
+pub fn is_encrypted(message: &str) -> bool {
+    encryption::is_encrypted(message)
+}
+
+#[wasm_bindgen(js_name = encryptMessage)]
+pub fn encrypt_message(key: &[u8], message: &str) -> Result<String, JsError> {
+    Ok(encryption::encrypt_message(&content_key(key)?, message))
+}
+
+#[wasm_bindgen(js_name = decryptMessage)]
+pub fn decrypt_message(key: &[u8], message: &str) -> Result<String, JsError> {
+    Ok(encryption::decrypt_message(&content_key(key)?, message)?)
+}
+
+// Wraps `key` to a wallet address
+#[wasm_bindgen(js_name = wrapKey)]
+pub fn wrap_key(key: &[u8], reader: &str) -> Result<WrappedKey, JsError> {
+    let reader = encryption::x25519_public_key(&reader.parse()?)
+        .ok_or_else(|| JsError::new("the reader is not a wallet address"))?;
+    let wrapped = encryption::wrap_key(&content_key(key)?, &reader);
+    Ok(WrappedKey {
+        ephemeral_public_key: wrapped.ephemeral_public_key.to_vec(),
+        nonce: wrapped.nonce.to_vec(),
+        wrapped_key: wrapped.wrapped_key.to_vec(),
+    })
+}
+
+// The X25519 secret of a 64-byte keypair (secret key then public key, as Keypair.secretKey holds it in web3.js)
+#[wasm_bindgen(js_name = x25519Secret)]
+pub fn x25519_secret(keypair: &[u8]) -> Result<Vec<u8>, JsError> {
+    let keypair = Keypair::try_from(keypair)?;
+    Ok(encryption::x25519_secret(&keypair).to_bytes().to_vec())
+}
+
+// Opens the KeyEnvelope account `envelope` (its data, as fetched) with the reader's X25519 secret
+#[wasm_bindgen(js_name = unwrapKey)]
+pub fn unwrap_key(envelope: &[u8], x25519_secret: &[u8]) -> Result<Vec<u8>, JsError> {
+    let secret = <[u8; 32]>::try_from(x25519_secret).map_err(|_| JsError::new("X25519 secrets are 32 bytes"))?;
+    let envelope = accounts::key_envelope(envelope)?;
+    Ok(encryption::unwrap_key(&envelope, &StaticSecret::from(secret))?.to_bytes().to_vec())
+}
+
+#[wasm_bindgen(js_name = fetchUserProfile)]
+pub async fn fetch_user_profile(rpc_url: String, authority: String) -> Result<Option<Profile>, JsError> {
+    let address = find_user_profile_pda(&authority.parse()?).0;
+    match get_multiple_accounts(&rpc_url, &[address]).await?.pop().flatten() {
+        Some(data) => Ok(Some(decode_user_profile(&address.to_string(), &data)?)),
+        None => Ok(None),
+    }
+}
+
+// The entries of `authority` with ids in [start, end) that exist, by id
+#[wasm_bindgen(js_name = fetchEntries)]
+pub async fn fetch_entries(rpc_url: String, authority: String, start: u64, end: u64) -> Result<Vec<Entry>, JsError> {
+    let authority: Pubkey = authority.parse()?;
+    let addresses: Vec<Pubkey> = (start..end).map(|id| find_journal_entry_pda(&authority, id).0).collect();
+    let accounts = get_multiple_accounts(&rpc_url, &addresses).await?;
+    addresses
+        .iter()
+        .zip(accounts)
+        .filter_map(|(address, data)| Some(decode_journal_entry(&address.to_string(), &data?)))
+        .collect()
+}
+
+fn content_key(key: &[u8]) -> Result<ContentKey, JsError> {
+    let key = <[u8; 32]>::try_from(key).map_err(|_| JsError::new("content keys are 32 bytes"))?;
+    Ok(ContentKey::from_bytes(key))
+}
+
+// Each account's data, in order; None where there is no account
+async fn get_multiple_accounts(rpc_url: &str, addresses: &[Pubkey]) -> Result<Vec<Option<Vec<u8>>>, JsError> {
+    let client = reqwest::Client::new();
+    let mut accounts = Vec::with_capacity(addresses.len());
+    for batch in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let keys: Vec<String> = batch.iter().map(Pubkey::to_string).collect();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getMultipleAccounts",
+            "params": [keys, { "encoding": "base64", "commitment": "confirmed" }],
+        });
+        let response = client
+            .post(rpc_url)
+            .header("content-type", "application/json")
+            .body(request.to_string())
+            .send()
+            .await?
+            .text()
+            .await?;
+        let response: Value = serde_json::from_str(&response)?;
+        if let Some(error) = response.get("error") {
+            return Err(JsError::new(&format!("getMultipleAccounts failed: {error}")));
+        }
+        let values = response["result"]["value"]
+            .as_array()
+            .ok_or_else(|| JsError::new("getMultipleAccounts returned no accounts"))?;
+        for value in values {
+            // Accounts are null when missing, otherwise { data: [<base64>, "base64"], ... }
+            let data = match value["data"][0].as_str() {
+                Some(data) => Some(BASE64_STANDARD.decode(data)?),
+                None => None,
+            };
+            accounts.push(data);
+        }
+    }
+    Ok(accounts)
+}
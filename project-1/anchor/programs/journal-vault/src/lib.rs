@@ -0,0 +1,184 @@
+#![allow(clippy::result_large_err)]
+
+// Stands in for a multisig (e.g. a Squads vault) in tests: the vault is a system-owned PDA of this program
+// that owns a journal and drives add/update/delete through CPI with `invoke_signed`.
+
+use anchor_lang::prelude::*;
+use journal::cpi::accounts::{AddJournalEntry, DeleteJournalEntry, InitializeUserProfile, UpdateJournalEntry};
+use journal::program::JournalProgram;
+
+declare_id!("Hdo2j9K2jAH7kkLeWahERg4SFfqtzLyqgqBFXSNYoE9d");
+
+const VAULT_SEED: &[u8] = b"vault";
+
+#[program]
+pub mod journal_vault {
+    use super::*;
+
+    pub fn initialize_profile(ctx: Context<VaultProfile>) -> Result<()> {
+        let bump = [ctx.bumps.vault];
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, &bump]];
+        journal::cpi::initialize_user_profile(CpiContext::new_with_signer(
+            ctx.accounts.journal_program.to_account_info(),
+            InitializeUserProfile {
+                program_config: ctx.accounts.program_config.to_account_info(),
+                user_profile: ctx.accounts.user_profile.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+                payer: ctx.accounts.vault.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            signer_seeds,
+        ))
+    }
+
+    pub fn add_entry(ctx: Context<VaultAddEntry>, title: String, message: String) -> Result<()> {
+        let bump = [ctx.bumps.vault];
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, &bump]];
+        journal::cpi::add_journal_entry(
+            CpiContext::new_with_signer(
+                ctx.accounts.journal_program.to_account_info(),
+                AddJournalEntry {
+                    user_profile: ctx.accounts.user_profile.to_account_info(),
+                    journal_entry: ctx.accounts.journal_entry.to_account_info(),
+                    program_config: ctx.accounts.program_config.to_account_info(),
+                    treasury: ctx.accounts.treasury.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                    signer: ctx.accounts.vault.to_account_info(),
+                    session_key: None,
+                    gate_token_account: None,
+                    title_claim: None,
+                    price_update: None,
+                    daily_prompt: None,
+                    journal: None,
+                    feed_head: None,
+                    reply_parent: None,
+                    memo_program: None,
+                    instructions_sysvar: ctx.accounts.instructions_sysvar.to_account_info(),
+                    payer: ctx.accounts.vault.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            title,
+            message,
+        )
+    }
+
+    pub fn update_entry(ctx: Context<VaultUpdateEntry>, entry_id: u64, title: String, message: String) -> Result<()> {
+        let bump = [ctx.bumps.vault];
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, &bump]];
+        journal::cpi::update_journal_entry(
+            CpiContext::new_with_signer(
+                ctx.accounts.journal_program.to_account_info(),
+                UpdateJournalEntry {
+                    program_config: ctx.accounts.program_config.to_account_info(),
+                    user_profile: ctx.accounts.user_profile.to_account_info(),
+                    journal_entry: ctx.accounts.journal_entry.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                    signer: ctx.accounts.vault.to_account_info(),
+                    session_key: None,
+                    memo_program: None,
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            entry_id,
+            title,
+            message,
+        )
+    }
+
+    pub fn delete_entry(ctx: Context<VaultDeleteEntry>, entry_id: u64) -> Result<()> {
+        let bump = [ctx.bumps.vault];
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, &bump]];
+        journal::cpi::delete_journal_entry(
+            CpiContext::new_with_signer(
+                ctx.accounts.journal_program.to_account_info(),
+                DeleteJournalEntry {
+                    program_config: ctx.accounts.program_config.to_account_info(),
+                    user_profile: ctx.accounts.user_profile.to_account_info(),
+                    journal_entry: ctx.accounts.journal_entry.to_account_info(),
+                    rent_recipient: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                    signer: ctx.accounts.vault.to_account_info(),
+                    session_key: None,
+                    title_claim: None,
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            entry_id,
+        )
+    }
+}
+
+// Journal accounts are validated by the journal program itself, so they are passed through unchecked here.
+
+#[derive(Accounts)]
+pub struct VaultProfile<'info> {
+    // System-owned PDA acting as the journal authority and payer; fund it before use
+    #[account(mut, seeds = [VAULT_SEED], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: validated by the journal program
+    pub program_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the journal program
+    #[account(mut)]
+    pub user_profile: UncheckedAccount<'info>,
+    pub journal_program: Program<'info, JournalProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VaultAddEntry<'info> {
+    #[account(mut, seeds = [VAULT_SEED], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: validated by the journal program
+    pub program_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the journal program
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: validated by the journal program
+    #[account(mut)]
+    pub user_profile: UncheckedAccount<'info>,
+    /// CHECK: validated by the journal program
+    #[account(mut)]
+    pub journal_entry: UncheckedAccount<'info>,
+    /// CHECK: address constraint pins this to the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub journal_program: Program<'info, JournalProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VaultUpdateEntry<'info> {
+    // Writable because it pays for upgrading old-layout journal accounts
+    #[account(mut, seeds = [VAULT_SEED], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: validated by the journal program
+    pub program_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the journal program
+    #[account(mut)]
+    pub user_profile: UncheckedAccount<'info>,
+    /// CHECK: validated by the journal program
+    #[account(mut)]
+    pub journal_entry: UncheckedAccount<'info>,
+    pub journal_program: Program<'info, JournalProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VaultDeleteEntry<'info> {
+    #[account(mut, seeds = [VAULT_SEED], bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: validated by the journal program
+    pub program_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the journal program
+    #[account(mut)]
+    pub user_profile: UncheckedAccount<'info>,
+    /// CHECK: validated by the journal program
+    #[account(mut)]
+    pub journal_entry: UncheckedAccount<'info>,
+    pub journal_program: Program<'info, JournalProgram>,
+    pub system_program: Program<'info, System>,
+}
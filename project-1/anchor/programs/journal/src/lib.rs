@@ -1,421 +1,5152 @@
-// anchor/programs/journal_program/src/lib.rs
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::clock::Clock;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_lang::solana_program::instruction::{get_stack_height, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::program::{invoke_signed, set_return_data};
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::memo::{self, BuildMemo, Memo};
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{
+    create_master_edition_v3, create_metadata_accounts_v3, CreateMasterEditionV3, CreateMetadataAccountsV3, Metadata,
+};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked};
+use switchboard_on_demand::{RandomnessAccountData, SWITCHBOARD_ON_DEMAND_PROGRAM_ID};
 
-declare_id!("JRNA1S7xcX6P9sS5a95hTSGmD3Yk8z123456789ABC"); // Placeholder, replace with actual
+declare_id!("3NBQpXFyortqjRVgVSR7Mi2zRp3Jkn182r3wn5vomdKQ");
+
+// The authority on profiles and entries is only ever compared by key and required to sign, so it may be a
+// PDA (e.g. a Squads vault) signing through CPI rather than a system-owned keypair. When a PDA also pays
+// rent it has to be system-owned, since it is the `from` account of the system program's create_account.
+//
+// A DAO-run community journal works the same way: use an spl-governance native treasury PDA
+// (["native-treasury", governance]) as the authority and pass it as `authority`, `signer` and `payer`.
+// The only way that PDA signs is when spl-governance executes an approved proposal transaction, so entries
+// (e.g. meeting minutes) can only be written, edited or deleted through proposals. The CPI path is the one
+// exercised by the journal-vault test harness.
 
 // Constants for PDA seeds
-const USER_PROFILE_SEED: &[u8] = b"user_profile";
-const JOURNAL_ENTRY_SEED: &[u8] = b"journal_entry";
+const USER_PROFILE_SEED_PREFIX: &[u8] = b"user_profile";
+const JOURNAL_ENTRY_SEED_PREFIX: &[u8] = b"journal_entry";
+const TIP_JAR_SEED_PREFIX: &[u8] = b"tip_jar";
+const TOKEN_TIP_TALLY_SEED_PREFIX: &[u8] = b"token_tip_tally";
+const PROGRAM_CONFIG_SEED: &[u8] = b"program_config";
+const TREASURY_SEED: &[u8] = b"treasury";
+const SPONSOR_POOL_SEED: &[u8] = b"sponsor_pool";
+const SESSION_KEY_SEED: &[u8] = b"session_key";
+const PENDING_UPDATE_SEED: &[u8] = b"pending_update";
+const ENTRY_NFT_MINT_SEED: &[u8] = b"entry_nft_mint";
+const NFT_AUTHORITY_SEED: &[u8] = b"nft_authority";
+const PROFILE_METADATA_SEED: &[u8] = b"profile_metadata";
+const HANDLE_SEED: &[u8] = b"handle";
+const SLUG_SEED: &[u8] = b"slug";
+const TITLE_CLAIM_SEED: &[u8] = b"title_claim";
+const COMMENT_SEED: &[u8] = b"comment";
+const REACTION_SEED: &[u8] = b"reaction";
+const REACTION_TALLY_SEED: &[u8] = b"reaction_tally";
+const VIEW_RECEIPT_SEED: &[u8] = b"view_receipt";
+const VIEW_TALLY_SEED: &[u8] = b"view_tally";
+const FEED_HEAD_SEED: &[u8] = b"feed_head";
+const JOURNAL_SEED: &[u8] = b"journal";
+const PROMPT_LIST_SEED: &[u8] = b"prompt_list";
+const DAILY_PROMPT_SEED: &[u8] = b"daily_prompt";
+const COMPRESSED_JOURNAL_SEED: &[u8] = b"compressed_journal";
+const KEY_ENVELOPE_SEED: &[u8] = b"key_envelope";
+const PSEUDONYM_SEED: &[u8] = b"pseudonym";
+const REPORT_SEED: &[u8] = b"report";
+const ZERO_COPY_ENTRY_SEED: &[u8] = b"zero_copy_entry";
+
+// Constants for string lengths (characters, not including 4-byte length prefix)
+const MAX_TITLE_CHARS: usize = 50; 
+const MAX_MESSAGE_CHARS: usize = 280; // Like a tweet
+// Storage budget for entry text. Char limits are per locale family (see LocaleLimits), but the account is
+// sized in bytes; twice the default char limits fits accented Latin text and CJK at half the char count.
+const MAX_TITLE_BYTES: usize = 2 * MAX_TITLE_CHARS;
+const MAX_MESSAGE_BYTES: usize = 2 * MAX_MESSAGE_CHARS;
+const LOCALE_FAMILY_DEFAULT: usize = 0;
+const LOCALE_FAMILY_CJK: usize = 1;
+const LOCALE_FAMILIES: usize = 2;
+const MAX_CPI_CALLERS: usize = 8;
+// Depth of the per-profile entry history tree (about a million creates, updates and deletes)
+const ENTRY_HISTORY_DEPTH: usize = 20;
+const MAX_COMMENT_CHARS: usize = 280;
+const MAX_JOURNAL_NAME_CHARS: usize = 32;
+const MAX_PINNED_ENTRIES: usize = 8;
+const MAX_PROMPTS: usize = 32;
+const MAX_PROMPT_CHARS: usize = 140;
+// A commit whose randomness was never revealed can be replaced after this many slots (~20 minutes)
+const PROMPT_COMMIT_EXPIRY_SLOTS: u64 = 3_000;
+const MAX_JOURNAL_DESCRIPTION_CHARS: usize = 160;
+const REACTION_KINDS: usize = 8; // emoji_code indexes a fixed palette chosen by the front-end
+const FEED_HEAD_CAPACITY: usize = 32;
+const MAX_DISPLAY_NAME_CHARS: usize = 32;
+const MAX_BIO_CHARS: usize = 160;
+const MAX_AVATAR_URI_LENGTH: usize = 200;
+const MIN_HANDLE_LENGTH: usize = 3;
+const MAX_HANDLE_LENGTH: usize = 32; // A PDA seed can be at most 32 bytes
+const MAX_SLUG_LENGTH: usize = 64; // Longer than a seed allows, so slug PDAs are seeded by its hash
+
+// Bumped whenever the instruction encoding changes, so clients can detect an upgraded deployment. Exported in
+// the IDL, where journal-client picks it up as the version its encoders were generated for.
+#[constant]
+pub const PROGRAM_VERSION: u16 = 1;
+// Account schema versions, stored after the fixed-size fields every layout starts with (authority and
+// entry_count for profiles, authority and id for entries). Accounts written before versioning have no version
+// byte and are recognized by their size (see `mod legacy`).
+const USER_PROFILE_VERSION: u8 = 1;
+const JOURNAL_ENTRY_VERSION: u8 = 1;
+// Stable byte offsets (discriminator included) of the owner and id in JournalEntry, in every layout version,
+// and in ZeroCopyEntry, for getProgramAccounts memcmp filters
+pub const ENTRY_AUTHORITY_OFFSET: usize = 8;
+pub const ENTRY_ID_OFFSET: usize = 40;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+// Shortest challenge period a recovery authority can be registered with
+const MIN_RECOVERY_DELAY_SECONDS: i64 = SECONDS_PER_DAY;
+
+// Permission bits granted to delegates (session keys, automated agents); each mutating instruction
+// checks the bit it needs, so e.g. an agent can be append-only with PERMISSION_CREATE alone.
+pub const PERMISSION_CREATE: u8 = 1 << 0;
+pub const PERMISSION_UPDATE: u8 = 1 << 1;
+pub const PERMISSION_DELETE: u8 = 1 << 2;
+pub const PERMISSION_SET_VISIBILITY: u8 = 1 << 3;
+pub const PERMISSION_ALL: u8 = PERMISSION_CREATE | PERMISSION_UPDATE | PERMISSION_DELETE | PERMISSION_SET_VISIBILITY;
+
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+
+// Metaplex limits for on-chain metadata strings
+const MAX_NFT_NAME_LENGTH: usize = 32;
+const MAX_NFT_SYMBOL_LENGTH: usize = 10;
+const MAX_NFT_URI_LENGTH: usize = 200;
+
+// Prefix of the message a user signs off-chain for add_entry_relayed
+const RELAYED_ENTRY_DOMAIN: &[u8] = b"journal-relayed-entry:v1";
+
+// Compressed NFTs
+const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+const SPL_ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+const SPL_NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+// Pyth price snapshots, read from the push oracle's sponsored feed accounts (shard 0)
+const PYTH_PUSH_ORACLE_PROGRAM_ID: Pubkey = pubkey!("pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT");
+const PYTH_SHARD_ID: u16 = 0;
+const SOL_USD_FEED_ID: [u8; 32] = [
+    239, 13, 139, 111, 218, 44, 235, 164, 29, 161, 93, 64, 149, 209, 218, 57, 42, 13, 47, 142, 208, 198, 199, 188, 15, 76,
+    250, 200, 194, 128, 181, 109,
+];
+const MAX_PRICE_AGE_SECONDS: i64 = 60;
+
+// Coordinates are stored in units of 1e-7 degrees
+const MAX_LATITUDE_E7: i32 = 900_000_000;
+const MAX_LONGITUDE_E7: i32 = 1_800_000_000;
 
 #[program]
 pub mod journal_program {
     use super::*;
 
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_lamports: u64,
+        max_entries_per_day: u32,
+        entry_deposit_lamports: u64,
+        deposit_cooldown_seconds: i64,
+    ) -> Result<()> {
+        let program_config = &mut ctx.accounts.program_config;
+        program_config.admin = ctx.accounts.admin.key();
+        program_config.pending_admin = None;
+        program_config.fee_lamports = fee_lamports;
+        program_config.paused = false;
+        program_config.max_entries_per_day = max_entries_per_day;
+        program_config.entry_deposit_lamports = entry_deposit_lamports;
+        program_config.deposit_cooldown_seconds = deposit_cooldown_seconds;
+        program_config.program_version = PROGRAM_VERSION;
+        program_config.cnft_merkle_tree = None;
+        program_config.moderator = None;
+        program_config.cpi_allowlist = Vec::new();
+        program_config.locale_limits[LOCALE_FAMILY_DEFAULT] = LocaleLimits {
+            title_chars: MAX_TITLE_CHARS as u16,
+            message_chars: MAX_MESSAGE_CHARS as u16,
+        };
+        // CJK packs more meaning per character, so it gets half the characters (like Twitter's weighting)
+        program_config.locale_limits[LOCALE_FAMILY_CJK] = LocaleLimits {
+            title_chars: (MAX_TITLE_CHARS / 2) as u16,
+            message_chars: (MAX_MESSAGE_CHARS / 2) as u16,
+        };
+        program_config.bump = ctx.bumps.program_config;
+        ctx.accounts.treasury.bump = ctx.bumps.treasury;
+        msg!("Program config initialized with admin {}", program_config.admin);
+        Ok(())
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_lamports: u64,
+        max_entries_per_day: u32,
+        entry_deposit_lamports: u64,
+        deposit_cooldown_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.program_config.fee_lamports = fee_lamports;
+        ctx.accounts.program_config.max_entries_per_day = max_entries_per_day;
+        ctx.accounts.program_config.entry_deposit_lamports = entry_deposit_lamports;
+        ctx.accounts.program_config.deposit_cooldown_seconds = deposit_cooldown_seconds;
+        // Any admin touch after an upgrade records the version of the code now deployed
+        ctx.accounts.program_config.program_version = PROGRAM_VERSION;
+        msg!("Program config updated: fee {} lamports, {} entries per day", fee_lamports, max_entries_per_day);
+        Ok(())
+    }
+
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.program_config.paused = paused;
+        msg!("Program paused: {}", paused);
+        Ok(())
+    }
+
+    // Admin handover is two-step so a mistyped key can't take over (or brick) the config.
+    // Proposing again replaces any previous pending admin.
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.program_config.pending_admin = Some(new_admin);
+        msg!("Admin transfer to {} proposed", new_admin);
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let program_config = &mut ctx.accounts.program_config;
+        msg!("Admin transferred from {} to {}", program_config.admin, ctx.accounts.new_admin.key());
+        program_config.admin = ctx.accounts.new_admin.key();
+        program_config.pending_admin = None;
+        Ok(())
+    }
+
+    // Points mint_entry_cnft at a Bubblegum tree, or disables it with None. The tree's delegate must already be
+    // the program's nft_authority PDA (create the tree, then call Bubblegum's set_tree_delegate).
+    pub fn set_cnft_tree(ctx: Context<UpdateConfig>, merkle_tree: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.program_config.cnft_merkle_tree = merkle_tree;
+        msg!("Compressed NFT tree set to {:?}", merkle_tree);
+        Ok(())
+    }
+
+    // Programs allowed to call add_journal_entry through CPI; replaces the whole list
+    pub fn set_cpi_allowlist(ctx: Context<UpdateConfig>, programs: Vec<Pubkey>) -> Result<()> {
+        if programs.len() > MAX_CPI_CALLERS {
+            return err!(JournalError::TooManyCpiCallers);
+        }
+        msg!("CPI allowlist set to {} programs", programs.len());
+        ctx.accounts.program_config.cpi_allowlist = programs;
+        Ok(())
+    }
+
+    // The moderator can flag public entries but not edit or delete them; None leaves flagging to nobody
+    pub fn set_moderator(ctx: Context<UpdateConfig>, moderator: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.program_config.moderator = moderator;
+        msg!("Moderator set to {:?}", moderator);
+        Ok(())
+    }
+
+    // Character limits for a locale family; byte limits (MAX_TITLE_BYTES, MAX_MESSAGE_BYTES) always apply too
+    pub fn set_locale_limits(ctx: Context<UpdateConfig>, family: u8, title_chars: u16, message_chars: u16) -> Result<()> {
+        let limits = ctx
+            .accounts
+            .program_config
+            .locale_limits
+            .get_mut(family as usize)
+            .ok_or(JournalError::InvalidLocale)?;
+        *limits = LocaleLimits { title_chars, message_chars };
+        msg!("Locale family {} limited to {} title and {} message characters", family, title_chars, message_chars);
+        Ok(())
+    }
+
+    pub fn initialize_feed_head(ctx: Context<InitializeFeedHead>) -> Result<()> {
+        ctx.accounts.feed_head.bump = ctx.bumps.feed_head;
+        msg!("Public feed initialized");
+        Ok(())
+    }
+
+    // Appends to the list daily prompts are drawn from; prompt ids are indexes into this list
+    pub fn add_prompt(ctx: Context<AddPrompt>, text: String) -> Result<()> {
+        if text.is_empty() || text.chars().count() > MAX_PROMPT_CHARS {
+            return err!(JournalError::InvalidPrompt);
+        }
+        let prompt_list = &mut ctx.accounts.prompt_list;
+        if prompt_list.prompts.len() >= MAX_PROMPTS {
+            return err!(JournalError::TooManyPrompts);
+        }
+        prompt_list.prompts.push(text);
+        prompt_list.bump = ctx.bumps.prompt_list;
+        msg!("Prompt {} added", prompt_list.prompts.len() - 1);
+        Ok(())
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        let treasury = ctx.accounts.treasury.to_account_info();
+
+        // The treasury is program-owned, so lamports can be moved directly as long as it stays rent exempt.
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury.data_len());
+        let available = treasury.lamports().saturating_sub(rent_exempt_minimum);
+        if amount == 0 || amount > available {
+            return err!(JournalError::InsufficientTreasuryBalance);
+        }
+
+        treasury.sub_lamports(amount)?;
+        ctx.accounts.recipient.add_lamports(amount)?;
+
+        emit!(TreasuryWithdrawn {
+            admin: ctx.accounts.admin.key(),
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+        });
+        Ok(())
+    }
+
     pub fn initialize_user_profile(ctx: Context<InitializeUserProfile>) -> Result<()> {
-        ctx.accounts.user_profile.authority = ctx.accounts.authority.key();
-        ctx.accounts.user_profile.entry_count = 0;
-        ctx.accounts.user_profile.bump = ctx.bumps.user_profile;
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.version = USER_PROFILE_VERSION;
+        user_profile.authority = ctx.accounts.authority.key();
+        user_profile.entry_count = 0;
+        user_profile.total_chars_written = 0;
+        user_profile.total_entries_created = 0;
+        user_profile.last_entry_timestamp = 0;
+        user_profile.current_day = 0;
+        user_profile.entries_today = 0;
+        user_profile.write_nonce = 0;
+        user_profile.write_gate_mint = None;
+        user_profile.write_gate_min_amount = 0;
+        user_profile.unique_titles = false;
+        user_profile.journal_count = 0;
+        user_profile.pinned_entries = Vec::new();
+        user_profile.recovery_authority = None;
+        user_profile.recovery_delay_seconds = 0;
+        user_profile.pending_recovery = None;
+        user_profile.recovery_unlock_at = 0;
+        user_profile.locale = [0; 5];
+        user_profile.history_root = [0; 32];
+        user_profile.history_leaf_count = 0;
+        user_profile.history_frontier = [[0; 32]; ENTRY_HISTORY_DEPTH];
+        user_profile.bump = ctx.bumps.user_profile;
         msg!("User profile initialized for {}", ctx.accounts.authority.key());
         Ok(())
     }
 
     pub fn add_journal_entry(ctx: Context<AddJournalEntry>, title: String, message: String) -> Result<()> {
-        let user_profile = &mut ctx.accounts.user_profile;
+        authorize_signer(&ctx.accounts.authority, &ctx.accounts.signer, &ctx.accounts.session_key, PERMISSION_CREATE)?;
+        check_cpi_caller(&ctx.accounts.program_config, &ctx.accounts.instructions_sysvar)?;
+        check_write_gate(&ctx.accounts.user_profile, &ctx.accounts.signer.key(), &ctx.accounts.gate_token_account)?;
+        validate_entry_content(&title, &message, &ctx.accounts.program_config, &ctx.accounts.user_profile.locale)?;
+        claim_title(
+            &ctx.accounts.user_profile,
+            &mut ctx.accounts.title_claim,
+            ctx.accounts.journal_entry.key(),
+            ctx.bumps.title_claim,
+        )?;
+        write_memo(&ctx.accounts.memo_program, "create", &ctx.accounts.journal_entry.key(), &title, &message)?;
+        let entry_key = ctx.accounts.journal_entry.key();
+        record_entry_history(&mut ctx.accounts.user_profile, EntryAction::Create, entry_key, &title, &message)?;
+
+        let deposit_lamports = charge_entry_costs(
+            &ctx.accounts.program_config,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.journal_entry.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
         let journal_entry = &mut ctx.accounts.journal_entry;
         let authority = &ctx.accounts.authority;
         let clock = Clock::get()?;
+        let chars_written = (title.chars().count() + message.chars().count()) as u64;
+
+        journal_entry.version = JOURNAL_ENTRY_VERSION;
+        journal_entry.authority = authority.key();
+        journal_entry.title = title;
+        journal_entry.message = message;
+        journal_entry.timestamp = clock.unix_timestamp;
+        journal_entry.tips_received = 0;
+        journal_entry.deposit_lamports = deposit_lamports;
+        journal_entry.deposit_unlock_at = clock.unix_timestamp
+            .checked_add(ctx.accounts.program_config.deposit_cooldown_seconds)
+            .ok_or(JournalError::Overflow)?;
+        journal_entry.rent_payer = ctx.accounts.payer.key();
+        journal_entry.update_delay_seconds = 0;
+        journal_entry.nft_mint = None;
+        journal_entry.is_public = false;
+        journal_entry.reply_to = None;
+        journal_entry.reply_count = 0;
+        journal_entry.comment_count = 0;
+        journal_entry.journal = None;
+        journal_entry.sort_order = 0;
+        journal_entry.prompt_id = None;
+        journal_entry.price_snapshot = None;
+        journal_entry.location = None;
+        journal_entry.weather_code = None;
+        journal_entry.locale = ctx.accounts.user_profile.locale;
+        journal_entry.reader = None;
+        journal_entry.flagged = false;
+        journal_entry.report_count = 0;
+        journal_entry.bump = ctx.bumps.journal_entry;
+        journal_entry.id = record_entry_creation(
+            &mut ctx.accounts.user_profile,
+            &ctx.accounts.program_config,
+            chars_written,
+            clock.unix_timestamp,
+        )?;
 
-        // Basic validation for string lengths (consider more robust checks)
-        if title.len() > MAX_TITLE_LENGTH as usize {
-            return err!(JournalError::TitleTooLong);
+        if let Some(price_update) = &ctx.accounts.price_update {
+            journal_entry.price_snapshot = Some(pyth::sol_usd_snapshot(price_update, clock.unix_timestamp)?);
         }
-        if message.len() > MAX_MESSAGE_LENGTH as usize {
-            return err!(JournalError::MessageTooLong);
+
+        // The prompt is taken from the DailyPrompt account rather than trusted from the client
+        if let Some(daily_prompt) = &ctx.accounts.daily_prompt {
+            if daily_prompt.day != clock.unix_timestamp.div_euclid(SECONDS_PER_DAY) {
+                return err!(JournalError::PromptExpired);
+            }
+            journal_entry.prompt_id = daily_prompt.prompt_id;
         }
 
-        journal_entry.authority = authority.key();
+        if let Some(journal) = &mut ctx.accounts.journal {
+            journal.entry_count = journal.entry_count.checked_add(1).ok_or(JournalError::Overflow)?;
+            journal_entry.journal = Some(journal.key());
+            if journal.default_visibility {
+                let feed_head = ctx.accounts.feed_head.as_mut().ok_or(JournalError::FeedHeadRequired)?;
+                feed_head.push(journal_entry.key())?;
+                journal_entry.is_public = true;
+            }
+        }
+
+        if let Some(reply_parent) = &mut ctx.accounts.reply_parent {
+            if !reply_parent.is_public {
+                return err!(JournalError::ReplyParentNotPublic);
+            }
+            reply_parent.reply_count = reply_parent.reply_count.checked_add(1).ok_or(JournalError::Overflow)?;
+            journal_entry.reply_to = Some(reply_parent.key());
+        }
+
+        emit!(EntryCreated {
+            authority: authority.key(),
+            entry: journal_entry.key(),
+            id: journal_entry.id,
+            payer: ctx.accounts.payer.key(),
+            rent_lamports: Rent::get()?.minimum_balance(8 + JournalEntry::INIT_SPACE),
+            protocol_fee_lamports: ctx.accounts.program_config.fee_lamports,
+            deposit_lamports,
+            priority_fee_estimate_lamports: priority_fee_estimate(ctx.remaining_accounts)?,
+        });
+        msg!("Journal entry {} added for user {}", journal_entry.id, authority.key());
+        Ok(())
+    }
+
+    // Gasless path: the user signs the entry off-chain and a relayer submits it, paying fees, rent and deposit.
+    // The transaction must carry an ed25519 program instruction immediately before this one that verifies the
+    // user's signature over `relayed_entry_message`. `nonce` must equal the profile's current write_nonce, so
+    // each signed payload can be used at most once.
+    pub fn add_entry_relayed(ctx: Context<AddEntryRelayed>, nonce: u64, title: String, message: String) -> Result<()> {
+        validate_entry_content(&title, &message, &ctx.accounts.program_config, &ctx.accounts.user_profile.locale)?;
+        let entry_key = ctx.accounts.journal_entry.key();
+        record_entry_history(&mut ctx.accounts.user_profile, EntryAction::Create, entry_key, &title, &message)?;
+
+        let user_profile = &mut ctx.accounts.user_profile;
+        if nonce != user_profile.write_nonce {
+            return err!(JournalError::InvalidWriteNonce);
+        }
+        user_profile.write_nonce = user_profile.write_nonce.checked_add(1).ok_or(JournalError::Overflow)?;
+
+        let authority_key = ctx.accounts.authority.key();
+        check_write_gate(&ctx.accounts.user_profile, &authority_key, &ctx.accounts.gate_token_account)?;
+        let expected_message = relayed_entry_message(&authority_key, nonce, &title, &message);
+        verify_preceding_ed25519_signature(&ctx.accounts.instructions_sysvar, &authority_key, &expected_message)?;
+        claim_title(
+            &ctx.accounts.user_profile,
+            &mut ctx.accounts.title_claim,
+            ctx.accounts.journal_entry.key(),
+            ctx.bumps.title_claim,
+        )?;
+
+        let deposit_lamports = charge_entry_costs(
+            &ctx.accounts.program_config,
+            &ctx.accounts.relayer.to_account_info(),
+            &ctx.accounts.journal_entry.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        let clock = Clock::get()?;
+        let chars_written = (title.chars().count() + message.chars().count()) as u64;
+
+        journal_entry.version = JOURNAL_ENTRY_VERSION;
+        journal_entry.authority = authority_key;
         journal_entry.title = title;
         journal_entry.message = message;
         journal_entry.timestamp = clock.unix_timestamp;
-        journal_entry.id = user_profile.entry_count;
+        journal_entry.tips_received = 0;
+        journal_entry.deposit_lamports = deposit_lamports;
+        journal_entry.deposit_unlock_at = clock.unix_timestamp
+            .checked_add(ctx.accounts.program_config.deposit_cooldown_seconds)
+            .ok_or(JournalError::Overflow)?;
+        journal_entry.rent_payer = ctx.accounts.relayer.key();
+        journal_entry.update_delay_seconds = 0;
+        journal_entry.nft_mint = None;
+        journal_entry.is_public = false;
+        journal_entry.reply_to = None;
+        journal_entry.reply_count = 0;
+        journal_entry.comment_count = 0;
+        journal_entry.journal = None;
+        journal_entry.sort_order = 0;
+        journal_entry.prompt_id = None;
+        journal_entry.price_snapshot = None;
+        journal_entry.location = None;
+        journal_entry.weather_code = None;
+        journal_entry.locale = ctx.accounts.user_profile.locale;
+        journal_entry.reader = None;
+        journal_entry.flagged = false;
+        journal_entry.report_count = 0;
         journal_entry.bump = ctx.bumps.journal_entry;
+        journal_entry.id = record_entry_creation(
+            &mut ctx.accounts.user_profile,
+            &ctx.accounts.program_config,
+            chars_written,
+            clock.unix_timestamp,
+        )?;
 
-        user_profile.entry_count = user_profile.entry_count.checked_add(1).ok_or(JournalError::Overflow)?;
-        
-        msg!("Journal entry {} added for user {}", journal_entry.id, authority.key());
+        emit!(EntryCreated {
+            authority: authority_key,
+            entry: journal_entry.key(),
+            id: journal_entry.id,
+            payer: ctx.accounts.relayer.key(),
+            rent_lamports: Rent::get()?.minimum_balance(8 + JournalEntry::INIT_SPACE),
+            protocol_fee_lamports: ctx.accounts.program_config.fee_lamports,
+            deposit_lamports,
+            priority_fee_estimate_lamports: priority_fee_estimate(ctx.remaining_accounts)?,
+        });
+        msg!("Relayed journal entry {} added for user {} by {}", journal_entry.id, authority_key, ctx.accounts.relayer.key());
         Ok(())
     }
 
-    pub fn update_journal_entry(ctx: Context<UpdateJournalEntry>, _entry_id: u64, title: String, message: String) -> Result<()> {
+    // Requires whoever writes to this journal to hold at least `min_amount` of `mint` (use an amount of 1 with
+    // an NFT mint for single-token gating). Passing `None` removes the gate.
+    pub fn set_write_gate(ctx: Context<UpdateProfile>, mint: Option<Pubkey>, min_amount: u64) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.write_gate_mint = mint;
+        user_profile.write_gate_min_amount = min_amount;
+        msg!("Write gate for user {} set to {:?} (min {})", user_profile.authority, mint, min_amount);
+        Ok(())
+    }
+
+    // Daily prompts use Switchboard's commit-reveal randomness and can be cranked by anyone. First commit to a
+    // randomness account whose seed slot is the previous slot, then, once the oracle has revealed it, call
+    // reveal_daily_prompt in the same transaction as Switchboard's reveal instruction.
+    pub fn commit_daily_prompt(ctx: Context<CommitDailyPrompt>) -> Result<()> {
+        let clock = Clock::get()?;
+        let daily_prompt = &mut ctx.accounts.daily_prompt;
+        if daily_prompt.day == clock.unix_timestamp.div_euclid(SECONDS_PER_DAY) && daily_prompt.prompt_id.is_some() {
+            return err!(JournalError::PromptAlreadyDrawn);
+        }
+        if daily_prompt.pending_randomness.is_some()
+            && clock.slot < daily_prompt.commit_slot.saturating_add(PROMPT_COMMIT_EXPIRY_SLOTS)
+        {
+            return err!(JournalError::PromptDrawPending);
+        }
+
+        let randomness = RandomnessAccountData::parse(ctx.accounts.randomness_account.try_borrow_data()?)
+            .map_err(|_| JournalError::InvalidRandomness)?;
+        // A seed from the previous slot means nobody can have seen the revealed value yet
+        if randomness.seed_slot != clock.slot.saturating_sub(1) {
+            return err!(JournalError::InvalidRandomness);
+        }
+
+        daily_prompt.pending_randomness = Some(ctx.accounts.randomness_account.key());
+        daily_prompt.commit_slot = clock.slot;
+        daily_prompt.bump = ctx.bumps.daily_prompt;
+        msg!("Daily prompt draw committed to {}", ctx.accounts.randomness_account.key());
+        Ok(())
+    }
+
+    pub fn reveal_daily_prompt(ctx: Context<RevealDailyPrompt>) -> Result<()> {
+        let clock = Clock::get()?;
+        let randomness = RandomnessAccountData::parse(ctx.accounts.randomness_account.try_borrow_data()?)
+            .map_err(|_| JournalError::InvalidRandomness)?;
+        let value = randomness.get_value(&clock).map_err(|_| JournalError::RandomnessNotRevealed)?;
+
+        let prompt_count = ctx.accounts.prompt_list.prompts.len() as u64;
+        let prompt_id = (u64::from_le_bytes(value[..8].try_into().unwrap()) % prompt_count) as u16;
+
+        let daily_prompt = &mut ctx.accounts.daily_prompt;
+        daily_prompt.day = clock.unix_timestamp.div_euclid(SECONDS_PER_DAY);
+        daily_prompt.prompt_id = Some(prompt_id);
+        daily_prompt.pending_randomness = None;
+        msg!("Prompt {} drawn for day {}", prompt_id, daily_prompt.day);
+        Ok(())
+    }
+
+    // Notebooks group a user's entries ("work", "travel", ...). They are addressed by index rather than name so
+    // they can be renamed; entries keep their [authority, id] address and record their notebook in `journal`.
+    pub fn create_journal(ctx: Context<CreateJournal>, name: String) -> Result<()> {
+        validate_journal_name(&name)?;
+
+        let user_profile = &mut ctx.accounts.user_profile;
+        let journal = &mut ctx.accounts.journal;
+        journal.authority = user_profile.authority;
+        journal.index = user_profile.journal_count;
+        journal.name = name;
+        journal.description = String::new();
+        journal.default_visibility = false;
+        journal.entry_count = 0;
+        journal.bump = ctx.bumps.journal;
+        user_profile.journal_count = user_profile.journal_count.checked_add(1).ok_or(JournalError::Overflow)?;
+
+        msg!("Journal {} ({}) created for user {}", journal.index, journal.name, journal.authority);
+        Ok(())
+    }
+
+    pub fn rename_journal(ctx: Context<UpdateJournal>, _journal_index: u32, name: String) -> Result<()> {
+        validate_journal_name(&name)?;
+        ctx.accounts.journal.name = name;
+        msg!("Journal {} renamed to {}", ctx.accounts.journal.index, ctx.accounts.journal.name);
+        Ok(())
+    }
+
+    pub fn set_journal_metadata(
+        ctx: Context<UpdateJournal>,
+        _journal_index: u32,
+        description: String,
+        default_visibility: bool,
+    ) -> Result<()> {
+        if description.chars().count() > MAX_JOURNAL_DESCRIPTION_CHARS {
+            return err!(JournalError::JournalDescriptionTooLong);
+        }
+        let journal = &mut ctx.accounts.journal;
+        journal.description = description;
+        journal.default_visibility = default_visibility;
+        msg!("Journal {} metadata updated (public by default: {})", journal.index, default_visibility);
+        Ok(())
+    }
+
+    // Closing a notebook doesn't touch its entries; they keep pointing at the closed address and clients
+    // should show them in the default stream.
+    pub fn close_journal(ctx: Context<CloseJournal>, _journal_index: u32) -> Result<()> {
+        msg!("Journal {} closed for user {}", ctx.accounts.journal.index, ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    // Features an entry at the top of the profile; pins are kept in the order they were added
+    pub fn pin_entry(ctx: Context<PinEntry>, _entry_id: u64) -> Result<()> {
+        let entry = ctx.accounts.journal_entry.key();
+        let pinned_entries = &mut ctx.accounts.user_profile.pinned_entries;
+        if pinned_entries.contains(&entry) {
+            return err!(JournalError::AlreadyPinned);
+        }
+        if pinned_entries.len() >= MAX_PINNED_ENTRIES {
+            return err!(JournalError::TooManyPinnedEntries);
+        }
+        pinned_entries.push(entry);
+        msg!("Journal entry {} pinned", ctx.accounts.journal_entry.id);
+        Ok(())
+    }
+
+    // Takes the entry's address rather than its id so pins of deleted entries can still be removed
+    pub fn unpin_entry(ctx: Context<UpdateProfile>, entry: Pubkey) -> Result<()> {
+        let pinned_entries = &mut ctx.accounts.user_profile.pinned_entries;
+        let position = pinned_entries.iter().position(|pinned| *pinned == entry).ok_or(JournalError::NotPinned)?;
+        pinned_entries.remove(position);
+        msg!("Journal entry {} unpinned", entry);
+        Ok(())
+    }
+
+    // Registers (or with None, removes) a key that can take over the profile if the authority's key is lost.
+    // Recovery is initiate_recovery, then a challenge period of `delay_seconds` during which the authority can
+    // cancel_recovery, then complete_recovery. Setting this also cancels any recovery in progress.
+    pub fn set_recovery_authority(
+        ctx: Context<UpdateProfile>,
+        recovery_authority: Option<Pubkey>,
+        delay_seconds: i64,
+    ) -> Result<()> {
+        if recovery_authority.is_some() && delay_seconds < MIN_RECOVERY_DELAY_SECONDS {
+            return err!(JournalError::RecoveryDelayTooShort);
+        }
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.recovery_authority = recovery_authority;
+        user_profile.recovery_delay_seconds = delay_seconds;
+        user_profile.pending_recovery = None;
+        msg!("Recovery authority for user {} set to {:?}", user_profile.authority, recovery_authority);
+        Ok(())
+    }
+
+    pub fn initiate_recovery(ctx: Context<InitiateRecovery>, new_authority: Pubkey) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.pending_recovery = Some(new_authority);
+        user_profile.recovery_unlock_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(user_profile.recovery_delay_seconds)
+            .ok_or(JournalError::Overflow)?;
+        msg!(
+            "Recovery of user {} to {} initiated; completes after {}",
+            user_profile.authority,
+            new_authority,
+            user_profile.recovery_unlock_at
+        );
+        Ok(())
+    }
+
+    pub fn cancel_recovery(ctx: Context<UpdateProfile>) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        if user_profile.pending_recovery.take().is_none() {
+            return err!(JournalError::NoRecoveryPending);
+        }
+        msg!("Recovery of user {} cancelled", user_profile.authority);
+        Ok(())
+    }
+
+    // Hands the profile to the new authority. The profile and its entries stay at addresses derived from the old
+    // key, so neither key can write to them afterwards. The new authority carries on by creating its own profile
+    // with initialize_user_profile, then moving the entries over with migrate_entry, passing the old key as
+    // old_authority without its signature; the old rent comes back to the new authority. The old profile's
+    // stats and settings stay behind.
+    pub fn complete_recovery(ctx: Context<InitiateRecovery>) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        let new_authority = user_profile.pending_recovery.ok_or(JournalError::NoRecoveryPending)?;
+        if Clock::get()?.unix_timestamp < user_profile.recovery_unlock_at {
+            return err!(JournalError::RecoveryStillLocked);
+        }
+        msg!("User profile recovered from {} to {}", user_profile.authority, new_authority);
+        user_profile.authority = new_authority;
+        user_profile.pending_recovery = None;
+        user_profile.recovery_authority = None;
+        Ok(())
+    }
+
+    // BCP 47 tag such as "en-US" or "ja", zero-padded to 5 bytes. New entries take the profile's locale, which
+    // selects the character limits they are validated against.
+    pub fn set_locale(ctx: Context<UpdateProfile>, locale: [u8; 5]) -> Result<()> {
+        validate_locale(&locale)?;
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.locale = locale;
+        msg!("Locale for user {} set to {}", user_profile.authority, String::from_utf8_lossy(&locale).trim_end_matches('\0'));
+        Ok(())
+    }
+
+    // Opts the profile in or out of unique titles. While enabled, every new entry must bring a TitleClaim PDA
+    // for its title and titles can no longer be edited. Claims made while enabled stay in force after disabling
+    // until their entries are deleted or release_title_claim is called.
+    pub fn set_unique_titles(ctx: Context<UpdateProfile>, enabled: bool) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.unique_titles = enabled;
+        msg!("Unique titles for user {}: {}", user_profile.authority, enabled);
+        Ok(())
+    }
+
+    // Frees a title whose claim no longer matches a live entry with that title
+    pub fn release_title_claim(ctx: Context<ReleaseTitleClaim>, title: String) -> Result<()> {
+        let journal_entry = &ctx.accounts.journal_entry;
+        if !journal_entry.data_is_empty() {
+            let entry = JournalEntry::try_deserialize(&mut &journal_entry.try_borrow_data()?[..])?;
+            if entry.title == title {
+                return err!(JournalError::TitleStillInUse);
+            }
+        }
+        msg!("Title claim released for user {}", ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    // Creates or replaces the profile's display metadata. Stored in a companion PDA so existing
+    // UserProfile accounts don't need to be reallocated.
+    pub fn set_profile_metadata(
+        ctx: Context<SetProfileMetadata>,
+        display_name: String,
+        bio: String,
+        avatar_uri: String,
+    ) -> Result<()> {
+        if display_name.chars().count() > MAX_DISPLAY_NAME_CHARS
+            || bio.chars().count() > MAX_BIO_CHARS
+            || avatar_uri.len() > MAX_AVATAR_URI_LENGTH
+        {
+            return err!(JournalError::ProfileMetadataTooLong);
+        }
+
+        let profile_metadata = &mut ctx.accounts.profile_metadata;
+        profile_metadata.authority = ctx.accounts.authority.key();
+        profile_metadata.display_name = display_name;
+        profile_metadata.bio = bio;
+        profile_metadata.avatar_uri = avatar_uri;
+        profile_metadata.bump = ctx.bumps.profile_metadata;
+        msg!("Profile metadata updated for user {}", profile_metadata.authority);
+        Ok(())
+    }
+
+    // Claims a globally unique handle. Handles are stored in normalized form (lowercase ASCII letters,
+    // digits and underscores), so clients must lowercase before deriving the PDA; mixed case is rejected
+    // rather than silently folded, which keeps "Alice" and "alice" from ever being two different accounts.
+    pub fn claim_handle(ctx: Context<ClaimHandle>, handle: String) -> Result<()> {
+        validate_handle(&handle)?;
+
+        let handle_account = &mut ctx.accounts.handle_account;
+        if handle_account.authority != Pubkey::default() {
+            if handle_account.authority == ctx.accounts.authority.key() {
+                return Ok(());
+            }
+            return err!(JournalError::HandleTaken);
+        }
+
+        handle_account.authority = ctx.accounts.authority.key();
+        handle_account.user_profile = ctx.accounts.user_profile.key();
+        handle_account.handle = handle;
+        handle_account.claimed_at = Clock::get()?.unix_timestamp;
+        handle_account.bump = ctx.bumps.handle_account;
+        msg!("Handle @{} claimed by {}", handle_account.handle, handle_account.authority);
+        Ok(())
+    }
+
+    // Frees the handle for anyone to claim and refunds its rent
+    pub fn release_handle(ctx: Context<ReleaseHandle>, handle: String) -> Result<()> {
+        msg!("Handle @{} released by {}", handle, ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    // Lets an ephemeral key (e.g. held by a mobile app) act for the authority until `expires_at`,
+    // limited to the PERMISSION_* bits in `permissions`.
+    pub fn create_session_key(ctx: Context<CreateSessionKey>, session_pubkey: Pubkey, expires_at: i64, permissions: u8) -> Result<()> {
+        if expires_at <= Clock::get()?.unix_timestamp {
+            return err!(JournalError::SessionExpired);
+        }
+        if permissions & !PERMISSION_ALL != 0 {
+            return err!(JournalError::InvalidPermissions);
+        }
+
+        let session_key = &mut ctx.accounts.session_key;
+        session_key.authority = ctx.accounts.authority.key();
+        session_key.session_key = session_pubkey;
+        session_key.expires_at = expires_at;
+        session_key.permissions = permissions;
+        session_key.bump = ctx.bumps.session_key;
+        msg!("Session key {} created for user {} until {}", session_pubkey, session_key.authority, expires_at);
+        Ok(())
+    }
+
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>, _session_pubkey: Pubkey) -> Result<()> {
+        msg!("Session key {} revoked for user {}", ctx.accounts.session_key.session_key, ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    pub fn fund_sponsor_pool(ctx: Context<FundSponsorPool>, amount: u64) -> Result<()> {
+        // Like the tip jar, the pool is a system-owned PDA; the first deposit must cover its rent exemption.
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.sponsor_pool.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        msg!("Sponsor pool funded with {} lamports by {}", amount, ctx.accounts.funder.key());
+        Ok(())
+    }
+
+    // Same as add_journal_entry, but the sponsor pool pays the entry's rent so the authority needs no SOL
+    // for storage. Sponsored entries carry no deposit and no protocol fee, and their rent returns to the pool
+    // on delete, so the pool can't be drained by create/delete cycles.
+    pub fn add_journal_entry_sponsored(ctx: Context<AddJournalEntrySponsored>, title: String, message: String) -> Result<()> {
+        validate_entry_content(&title, &message, &ctx.accounts.program_config, &ctx.accounts.user_profile.locale)?;
+        let entry_key = ctx.accounts.journal_entry.key();
+        record_entry_history(&mut ctx.accounts.user_profile, EntryAction::Create, entry_key, &title, &message)?;
+
+        let authority_key = ctx.accounts.authority.key();
+        check_write_gate(&ctx.accounts.user_profile, &authority_key, &ctx.accounts.gate_token_account)?;
+        // The pool can't fund the extra claim account, so unique-title profiles use the other create paths
+        if ctx.accounts.user_profile.unique_titles {
+            return err!(JournalError::TitleClaimRequired);
+        }
+        let entry_id_bytes = ctx.accounts.user_profile.entry_count.to_le_bytes();
+        let space = 8 + JournalEntry::INIT_SPACE;
+
+        // The entry can't use `init` because the payer is a PDA, so it is created by hand with both PDAs signing.
+        let sponsor_pool_seeds: &[&[u8]] = &[SPONSOR_POOL_SEED, &[ctx.bumps.sponsor_pool]];
+        let journal_entry_seeds: &[&[u8]] = &[
+            JOURNAL_ENTRY_SEED_PREFIX,
+            authority_key.as_ref(),
+            &entry_id_bytes,
+            &[ctx.bumps.journal_entry],
+        ];
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: ctx.accounts.sponsor_pool.to_account_info(),
+                    to: ctx.accounts.journal_entry.to_account_info(),
+                },
+                &[sponsor_pool_seeds, journal_entry_seeds],
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &crate::ID,
+        )?;
+
+        let clock = Clock::get()?;
+        let chars_written = (title.chars().count() + message.chars().count()) as u64;
+        let id = record_entry_creation(
+            &mut ctx.accounts.user_profile,
+            &ctx.accounts.program_config,
+            chars_written,
+            clock.unix_timestamp,
+        )?;
+
+        let journal_entry = JournalEntry {
+            authority: authority_key,
+            id,
+            version: JOURNAL_ENTRY_VERSION,
+            title,
+            message,
+            timestamp: clock.unix_timestamp,
+            tips_received: 0,
+            deposit_lamports: 0,
+            deposit_unlock_at: clock.unix_timestamp,
+            rent_payer: ctx.accounts.sponsor_pool.key(),
+            update_delay_seconds: 0,
+            nft_mint: None,
+            is_public: false,
+            reply_to: None,
+            reply_count: 0,
+            comment_count: 0,
+            journal: None,
+            sort_order: 0,
+            prompt_id: None,
+            price_snapshot: None,
+            location: None,
+            weather_code: None,
+            locale: ctx.accounts.user_profile.locale,
+            reader: None,
+            flagged: false,
+            report_count: 0,
+            bump: ctx.bumps.journal_entry,
+        };
+        journal_entry.try_serialize(&mut &mut ctx.accounts.journal_entry.try_borrow_mut_data()?[..])?;
+
+        emit!(EntryCreated {
+            authority: authority_key,
+            entry: ctx.accounts.journal_entry.key(),
+            id,
+            payer: ctx.accounts.sponsor_pool.key(),
+            rent_lamports: ctx.accounts.journal_entry.lamports(),
+            protocol_fee_lamports: 0,
+            deposit_lamports: 0,
+            priority_fee_estimate_lamports: priority_fee_estimate(ctx.remaining_accounts)?,
+        });
+
+        msg!("Sponsored journal entry {} added for user {}", id, authority_key);
+        Ok(())
+    }
+
+    // Copies an entry's title, message and sort order into a new entry, filed in `journal` (or the default stream
+    // when omitted). The copy is a fresh entry: it pays its own rent, fee and deposit and starts private.
+    pub fn duplicate_entry(ctx: Context<DuplicateEntry>, _source_entry_id: u64) -> Result<()> {
+        if ctx.accounts.user_profile.unique_titles {
+            return err!(JournalError::DuplicateTitle);
+        }
+
+        let deposit_lamports = charge_entry_costs(
+            &ctx.accounts.program_config,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.journal_entry.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let source_entry = &ctx.accounts.source_entry;
         let journal_entry = &mut ctx.accounts.journal_entry;
         let clock = Clock::get()?;
+        let chars_written = (source_entry.title.chars().count() + source_entry.message.chars().count()) as u64;
 
-        if title.len() > MAX_TITLE_LENGTH as usize {
-            return err!(JournalError::TitleTooLong);
+        journal_entry.version = JOURNAL_ENTRY_VERSION;
+        journal_entry.authority = source_entry.authority;
+        journal_entry.title = source_entry.title.clone();
+        journal_entry.message = source_entry.message.clone();
+        journal_entry.timestamp = clock.unix_timestamp;
+        journal_entry.tips_received = 0;
+        journal_entry.deposit_lamports = deposit_lamports;
+        journal_entry.deposit_unlock_at = clock.unix_timestamp
+            .checked_add(ctx.accounts.program_config.deposit_cooldown_seconds)
+            .ok_or(JournalError::Overflow)?;
+        journal_entry.rent_payer = ctx.accounts.authority.key();
+        journal_entry.update_delay_seconds = 0;
+        journal_entry.nft_mint = None;
+        journal_entry.is_public = false;
+        journal_entry.reply_to = None;
+        journal_entry.reply_count = 0;
+        journal_entry.comment_count = 0;
+        journal_entry.journal = None;
+        journal_entry.sort_order = source_entry.sort_order;
+        journal_entry.prompt_id = source_entry.prompt_id;
+        journal_entry.price_snapshot = source_entry.price_snapshot;
+        journal_entry.location = source_entry.location;
+        journal_entry.weather_code = source_entry.weather_code;
+        journal_entry.locale = source_entry.locale;
+        journal_entry.reader = None;
+        journal_entry.flagged = false;
+        journal_entry.report_count = 0;
+        journal_entry.bump = ctx.bumps.journal_entry;
+        journal_entry.id = record_entry_creation(
+            &mut ctx.accounts.user_profile,
+            &ctx.accounts.program_config,
+            chars_written,
+            clock.unix_timestamp,
+        )?;
+        record_entry_history(
+            &mut ctx.accounts.user_profile,
+            EntryAction::Create,
+            journal_entry.key(),
+            &journal_entry.title,
+            &journal_entry.message,
+        )?;
+
+        if let Some(journal) = &mut ctx.accounts.journal {
+            journal.entry_count = journal.entry_count.checked_add(1).ok_or(JournalError::Overflow)?;
+            journal_entry.journal = Some(journal.key());
         }
-        if message.len() > MAX_MESSAGE_LENGTH as usize {
-            return err!(JournalError::MessageTooLong);
+
+        emit!(EntryCreated {
+            authority: journal_entry.authority,
+            entry: journal_entry.key(),
+            id: journal_entry.id,
+            payer: ctx.accounts.authority.key(),
+            rent_lamports: Rent::get()?.minimum_balance(8 + JournalEntry::INIT_SPACE),
+            protocol_fee_lamports: ctx.accounts.program_config.fee_lamports,
+            deposit_lamports,
+            priority_fee_estimate_lamports: priority_fee_estimate(ctx.remaining_accounts)?,
+        });
+        msg!("Journal entry {} duplicated as {}", source_entry.id, journal_entry.id);
+        Ok(())
+    }
+
+    // Moves entries to a new wallet. `remaining_accounts` holds (old entry, new entry) pairs; each new entry is
+    // created at the new authority's next id with the new authority paying rent, and the old entry is closed to
+    // the old authority. Both wallets must sign, unless the old profile was handed to the new wallet by
+    // complete_recovery, in which case the old rent goes to the new authority. Accounts keyed by the old entry address (tip jars, slugs,
+    // comments, reactions, NFTs) are not moved, and sponsored entries can't be migrated since their rent
+    // belongs to the sponsor pool.
+    pub fn migrate_entry<'info>(ctx: Context<'_, '_, 'info, 'info, MigrateEntry<'info>>) -> Result<()> {
+        let pairs = ctx.remaining_accounts.chunks_exact(2);
+        if ctx.remaining_accounts.is_empty() || !pairs.remainder().is_empty() {
+            return err!(JournalError::InvalidMigrationAccounts);
         }
-        
-        journal_entry.title = title;
-        journal_entry.message = message;
-        journal_entry.timestamp = clock.unix_timestamp; // Update timestamp on modification
+        let old_authority = ctx.accounts.old_authority.key();
+        let new_authority = ctx.accounts.new_authority.key();
+        let space = 8 + JournalEntry::INIT_SPACE;
+
+        let recovered = ctx.accounts.old_profile.authority == new_authority;
+        if !recovered && !ctx.accounts.old_authority.is_signer {
+            return err!(JournalError::Unauthorized);
+        }
+        let refund_recipient = if recovered {
+            ctx.accounts.new_authority.to_account_info()
+        } else {
+            ctx.accounts.old_authority.to_account_info()
+        };
+
+        for pair in pairs {
+            let (old_entry_info, new_entry_info) = (&pair[0], &pair[1]);
+            let old_entry = Account::<JournalEntry>::try_from(old_entry_info)?;
+            if old_entry.authority != old_authority {
+                return err!(JournalError::Unauthorized);
+            }
+            if old_entry.rent_payer != old_authority {
+                return err!(JournalError::SponsoredEntryMigration);
+            }
+
+            let new_profile = &mut ctx.accounts.new_profile;
+            let id = new_profile.entry_count;
+            let id_bytes = id.to_le_bytes();
+            let (expected_address, bump) = Pubkey::find_program_address(
+                &[JOURNAL_ENTRY_SEED_PREFIX, new_authority.as_ref(), &id_bytes],
+                &crate::ID,
+            );
+            if new_entry_info.key() != expected_address {
+                return err!(JournalError::InvalidMigrationAccounts);
+            }
+            new_profile.entry_count = new_profile.entry_count.checked_add(1).ok_or(JournalError::Overflow)?;
+
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::CreateAccount {
+                        from: ctx.accounts.new_authority.to_account_info(),
+                        to: new_entry_info.clone(),
+                    },
+                    &[&[JOURNAL_ENTRY_SEED_PREFIX, new_authority.as_ref(), &id_bytes, &[bump]]],
+                ),
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                &crate::ID,
+            )?;
+
+            // Everything except ownership-related fields carries over; the deposit stays behind with the old rent
+            let migrated_entry = JournalEntry {
+                authority: new_authority,
+                id,
+                deposit_lamports: 0,
+                rent_payer: new_authority,
+                journal: None, // Notebooks belong to the old authority
+                bump,
+                ..old_entry.clone().into_inner()
+            };
+            migrated_entry.try_serialize(&mut &mut new_entry_info.try_borrow_mut_data()?[..])?;
+            record_entry_history(new_profile, EntryAction::Create, new_entry_info.key(), &old_entry.title, &old_entry.message)?;
+            record_entry_history(
+                &mut ctx.accounts.old_profile,
+                EntryAction::Delete,
+                old_entry.key(),
+                &old_entry.title,
+                &old_entry.message,
+            )?;
+
+            old_entry.close(refund_recipient.clone())?;
+            msg!("Journal entry {} of {} migrated to entry {} of {}", old_entry.id, old_authority, id, new_authority);
+        }
+        Ok(())
+    }
 
-        msg!("Journal entry {} updated for user {}", journal_entry.id, ctx.accounts.authority.key());
+    // Rewrites a profile stored in an older layout in the current one, growing the account as needed.
+    // Anyone can run it; the payer covers the extra rent.
+    pub fn migrate_profile(ctx: Context<MigrateProfile>) -> Result<()> {
+        let info = ctx.accounts.user_profile.to_account_info();
+        let user_profile = legacy::read_legacy(&info, legacy::read_user_profile)?;
+        migrate_account_layout(
+            &info,
+            &user_profile,
+            8 + UserProfile::INIT_SPACE,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+        msg!("User profile {} migrated to version {}", info.key(), USER_PROFILE_VERSION);
         Ok(())
     }
 
-    pub fn delete_journal_entry(ctx: Context<DeleteJournalEntry>, _entry_id: u64) -> Result<()> {
-        // Account is closed by Anchor due to `close = authority` in `DeleteJournalEntry`
-        // If we needed to adjust `user_profile.entry_count` or manage gaps, more logic would be here.
-        // For simplicity, we are not compacting IDs or decrementing entry_count.
-        // This means fetching all entries would require iterating up to `user_profile.entry_count`
-        // and handling potential `AccountDoesNotExist` errors for deleted entries.
-        msg!("Journal entry {} deleted for user {}", ctx.accounts.journal_entry.id, ctx.accounts.authority.key());
+    // Same as migrate_profile, for entries. (migrate_entry moves entries between wallets.)
+    pub fn migrate_entry_layout(ctx: Context<MigrateEntryLayout>, _entry_id: u64) -> Result<()> {
+        let info = ctx.accounts.journal_entry.to_account_info();
+        let journal_entry = legacy::read_legacy(&info, legacy::read_journal_entry)?;
+        migrate_account_layout(
+            &info,
+            &journal_entry,
+            8 + JournalEntry::INIT_SPACE,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+        msg!("Journal entry {} migrated to version {}", info.key(), JOURNAL_ENTRY_VERSION);
         Ok(())
     }
-}
 
-// Account Structs
-const MAX_TITLE_LENGTH: u32 = 100; // 4 bytes for length + 100 bytes for string
-const MAX_MESSAGE_LENGTH: u32 = 500; // 4 bytes for length + 500 bytes for string
+    pub fn update_journal_entry(ctx: Context<UpdateJournalEntry>, _entry_id: u64, title: String, message: String) -> Result<()> {
+        authorize_signer(&ctx.accounts.authority, &ctx.accounts.signer, &ctx.accounts.session_key, PERMISSION_UPDATE)?;
+        validate_entry_content(&title, &message, &ctx.accounts.program_config, &ctx.accounts.journal_entry.locale)?;
+        if ctx.accounts.journal_entry.update_delay_seconds > 0 {
+            return err!(JournalError::UpdateTimelocked);
+        }
+        write_memo(&ctx.accounts.memo_program, "update", &ctx.accounts.journal_entry.key(), &title, &message)?;
 
-#[account]
-pub struct UserProfile {
-    pub authority: Pubkey,
-    pub entry_count: u64,
-    pub bump: u8,
-}
+        let entry_key = ctx.accounts.journal_entry.key();
+        apply_entry_update(&mut ctx.accounts.user_profile, &mut ctx.accounts.journal_entry, entry_key, title, message)?;
 
-impl UserProfile {
-    // Pubkey + u64 + u8
-    pub const LEN: usize = 8 + 32 + 8 + 1;
-}
+        msg!("Journal entry {} updated for user {}", ctx.accounts.journal_entry.id, ctx.accounts.authority.key());
+        Ok(())
+    }
 
-#[account]
-pub struct JournalEntry {
-    pub authority: Pubkey,    // User who owns the entry
-    pub id: u64,              // ID of the entry, specific to the user
-    pub title: String,
-    pub message: String,
-    pub timestamp: i64,
-    pub bump: u8,
-}
+    // Public entries can be replied to by anyone. Publishing an entry also pushes it onto the FeedHead.
+    pub fn set_entry_visibility(ctx: Context<SetEntryVisibility>, _entry_id: u64, is_public: bool) -> Result<()> {
+        authorize_signer(&ctx.accounts.authority, &ctx.accounts.signer, &ctx.accounts.session_key, PERMISSION_SET_VISIBILITY)?;
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        if is_public && !journal_entry.is_public {
+            ctx.accounts.feed_head.push(journal_entry.key())?;
+        }
+        journal_entry.is_public = is_public;
+        msg!("Journal entry {} is now {}", journal_entry.id, if is_public { "public" } else { "private" });
+        Ok(())
+    }
+
+    // Anyone can report a public entry once; the moderator reviews reports and may flag the entry
+    pub fn report_entry(ctx: Context<ReportEntry>, reason_code: u8) -> Result<()> {
+        let report = &mut ctx.accounts.report;
+        report.journal_entry = ctx.accounts.journal_entry.key();
+        report.reporter = ctx.accounts.reporter.key();
+        report.reason_code = reason_code;
+        report.created_at = Clock::get()?.unix_timestamp;
+        report.bump = ctx.bumps.report;
 
-impl JournalEntry {
-    // Discriminator (8) + Pubkey (32) + u64 (8) + String (4+N) + String (4+M) + i64 (8) + u8 (1)
-    // Add InitSpace trait for easier calculation if needed, or manually calculate
-    pub fn space(title_len: u32, message_len: u32) -> usize {
-        8 + // discriminator
-        32 + // authority
-        8 +  // id
-        4 + title_len as usize + // title
-        4 + message_len as usize + // message
-        8 +  // timestamp
-        1    // bump
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        journal_entry.report_count = journal_entry.report_count.checked_add(1).ok_or(JournalError::Overflow)?;
+        msg!("Journal entry {} reported by {} (reason {})", journal_entry.id, report.reporter, reason_code);
+        Ok(())
     }
+
+    // Flagged entries stay on chain unchanged; clients are expected to hide or blur them in public feeds
+    pub fn set_entry_flagged(ctx: Context<SetEntryFlagged>, flagged: bool) -> Result<()> {
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        journal_entry.flagged = flagged;
+        msg!("Journal entry {} flagged: {}", journal_entry.id, flagged);
+        Ok(())
+    }
+
+    // Anyone can comment on a public entry. Comments are indexed by the entry's comment_count at creation.
+    pub fn add_comment(ctx: Context<AddComment>, body: String) -> Result<()> {
+        if body.chars().count() > MAX_COMMENT_CHARS {
+            return err!(JournalError::CommentTooLong);
+        }
+
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        let comment = &mut ctx.accounts.comment;
+        comment.journal_entry = journal_entry.key();
+        comment.commenter = ctx.accounts.commenter.key();
+        comment.index = journal_entry.comment_count;
+        comment.body = body;
+        comment.timestamp = Clock::get()?.unix_timestamp;
+        comment.bump = ctx.bumps.comment;
+        journal_entry.comment_count = journal_entry.comment_count.checked_add(1).ok_or(JournalError::Overflow)?;
+
+        msg!("Comment {} added to journal entry {} by {}", comment.index, journal_entry.id, comment.commenter);
+        Ok(())
+    }
+
+    // Either the commenter or the entry's authority may remove a comment; rent goes back to the commenter.
+    // Comments outlive their entry, so the commenter can still clean up after the entry is deleted.
+    pub fn delete_comment(ctx: Context<DeleteComment>, _comment_index: u64) -> Result<()> {
+        let signer = ctx.accounts.signer.key();
+        if signer != ctx.accounts.commenter.key() {
+            let journal_entry = &ctx.accounts.journal_entry;
+            if journal_entry.data_is_empty() {
+                return err!(JournalError::Unauthorized);
+            }
+            let entry = JournalEntry::try_deserialize(&mut &journal_entry.try_borrow_data()?[..])?;
+            if entry.authority != signer {
+                return err!(JournalError::Unauthorized);
+            }
+        }
+        msg!("Comment {} deleted by {}", ctx.accounts.comment.index, signer);
+        Ok(())
+    }
+
+    // One reaction per reactor and public entry; remove_reaction first to pick a different emoji
+    pub fn react_to_entry(ctx: Context<ReactToEntry>, emoji_code: u8) -> Result<()> {
+        if emoji_code as usize >= REACTION_KINDS {
+            return err!(JournalError::InvalidEmojiCode);
+        }
+        let reaction = &mut ctx.accounts.reaction;
+        if reaction.reactor != Pubkey::default() {
+            return err!(JournalError::AlreadyReacted);
+        }
+        reaction.journal_entry = ctx.accounts.journal_entry.key();
+        reaction.reactor = ctx.accounts.reactor.key();
+        reaction.emoji_code = emoji_code;
+        reaction.bump = ctx.bumps.reaction;
+
+        let reaction_tally = &mut ctx.accounts.reaction_tally;
+        reaction_tally.journal_entry = ctx.accounts.journal_entry.key();
+        reaction_tally.bump = ctx.bumps.reaction_tally;
+        let count = &mut reaction_tally.counts[emoji_code as usize];
+        *count = count.checked_add(1).ok_or(JournalError::Overflow)?;
+
+        msg!("{} reacted {} to journal entry {}", reaction.reactor, emoji_code, ctx.accounts.journal_entry.id);
+        Ok(())
+    }
+
+    pub fn remove_reaction(ctx: Context<RemoveReaction>) -> Result<()> {
+        let emoji_code = ctx.accounts.reaction.emoji_code as usize;
+        let count = &mut ctx.accounts.reaction_tally.counts[emoji_code];
+        *count = count.saturating_sub(1);
+        msg!("{} removed their reaction", ctx.accounts.reactor.key());
+        Ok(())
+    }
+
+    // Read receipt for a public entry. Each viewer counts once, and repeat views succeed without counting. The
+    // viewer pays rent for their receipt, so inflating the count takes a funded wallet per view.
+    pub fn record_view(ctx: Context<RecordView>) -> Result<()> {
+        let view_receipt = &mut ctx.accounts.view_receipt;
+        if view_receipt.viewer != Pubkey::default() {
+            return Ok(());
+        }
+        view_receipt.viewer = ctx.accounts.viewer.key();
+        view_receipt.bump = ctx.bumps.view_receipt;
+
+        let view_tally = &mut ctx.accounts.view_tally;
+        view_tally.journal_entry = ctx.accounts.journal_entry.key();
+        view_tally.bump = ctx.bumps.view_tally;
+        view_tally.count = view_tally.count.checked_add(1).ok_or(JournalError::Overflow)?;
+
+        msg!("Journal entry {} viewed by {} ({} views)", ctx.accounts.journal_entry.id, view_receipt.viewer, view_tally.count);
+        Ok(())
+    }
+
+    pub fn reorder_entry(ctx: Context<UpdateEntryMetadata>, _entry_id: u64, sort_order: u32) -> Result<()> {
+        authorize_signer(&ctx.accounts.authority, &ctx.accounts.signer, &ctx.accounts.session_key, PERMISSION_UPDATE)?;
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        journal_entry.sort_order = sort_order;
+        msg!("Journal entry {} moved to position {}", journal_entry.id, sort_order);
+        Ok(())
+    }
+
+    // Attaches where/when context to an entry; pass None to clear a field. `weather_code` is a WMO weather
+    // interpretation code (0-99) as reported by most weather APIs.
+    pub fn set_entry_context(
+        ctx: Context<UpdateEntryMetadata>,
+        _entry_id: u64,
+        location: Option<GeoPoint>,
+        weather_code: Option<u8>,
+    ) -> Result<()> {
+        authorize_signer(&ctx.accounts.authority, &ctx.accounts.signer, &ctx.accounts.session_key, PERMISSION_UPDATE)?;
+        if let Some(location) = &location {
+            if location.latitude_e7.abs() > MAX_LATITUDE_E7 || location.longitude_e7.abs() > MAX_LONGITUDE_E7 {
+                return err!(JournalError::InvalidLocation);
+            }
+        }
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        journal_entry.location = location;
+        journal_entry.weather_code = weather_code;
+        msg!("Context updated for journal entry {}", journal_entry.id);
+        Ok(())
+    }
+
+    // Turns the entry into a note for `reader` that disappears once they call mark_read; None disarms it
+    pub fn set_entry_reader(ctx: Context<UpdateEntryMetadata>, _entry_id: u64, reader: Option<Pubkey>) -> Result<()> {
+        authorize_signer(&ctx.accounts.authority, &ctx.accounts.signer, &ctx.accounts.session_key, PERMISSION_UPDATE)?;
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        journal_entry.reader = reader;
+        msg!("Reader of journal entry {} set to {:?}", journal_entry.id, reader);
+        Ok(())
+    }
+
+    // Burn-after-read: the entry's reader closes it. Rent and deposit go back to whoever paid the rent,
+    // which is the author unless the entry was sponsored.
+    pub fn mark_read(ctx: Context<MarkRead>, _entry_id: u64) -> Result<()> {
+        let journal_entry = &ctx.accounts.journal_entry;
+        record_entry_history(
+            &mut ctx.accounts.user_profile,
+            EntryAction::Delete,
+            journal_entry.key(),
+            &journal_entry.title,
+            &journal_entry.message,
+        )?;
+        emit!(EntryDeleted {
+            authority: journal_entry.authority,
+            entry: journal_entry.key(),
+            id: journal_entry.id,
+            refund_recipient: ctx.accounts.rent_recipient.key(),
+            refunded_lamports: journal_entry.to_account_info().lamports(),
+        });
+        msg!("Journal entry {} read by {} and burned", journal_entry.id, ctx.accounts.reader.key());
+        Ok(())
+    }
+
+    // Registers the signing key as a pseudonym of some wallet without naming it: `commitment` is
+    // hash(owner || salt) with a random 32-byte salt the owner keeps. The pseudonym key then gets its own
+    // profile and writes entries like any other authority; fund it through sponsored or relayed writes,
+    // since paying from the main wallet would link the two.
+    pub fn create_pseudonym(ctx: Context<CreatePseudonym>, commitment: [u8; 32]) -> Result<()> {
+        let pseudonym = &mut ctx.accounts.pseudonym;
+        pseudonym.authority = ctx.accounts.authority.key();
+        pseudonym.commitment = commitment;
+        pseudonym.revealed_owner = None;
+        pseudonym.bump = ctx.bumps.pseudonym;
+        msg!("Pseudonym {} created", pseudonym.authority);
+        Ok(())
+    }
+
+    // Publicly proves that the signing wallet owns the pseudonym by opening the commitment
+    pub fn reveal_pseudonym(ctx: Context<RevealPseudonym>, salt: [u8; 32]) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let pseudonym = &mut ctx.accounts.pseudonym;
+        if hashv(&[owner.as_ref(), &salt]).to_bytes() != pseudonym.commitment {
+            return err!(JournalError::InvalidPseudonymProof);
+        }
+        pseudonym.revealed_owner = Some(owner);
+        msg!("Pseudonym {} revealed as {}", pseudonym.authority, owner);
+        Ok(())
+    }
+
+    // Shares the content key of a client-encrypted entry with `reader`. The key is sealed to the reader's
+    // X25519 key with an ephemeral key pair (XChaCha20-Poly1305, so 32 key bytes plus a 16-byte tag). Granting
+    // again overwrites the envelope, e.g. after rotating the content key.
+    pub fn grant_key_envelope(
+        ctx: Context<GrantKeyEnvelope>,
+        _entry_id: u64,
+        reader: Pubkey,
+        ephemeral_public_key: [u8; 32],
+        nonce: [u8; 24],
+        wrapped_key: [u8; 48],
+    ) -> Result<()> {
+        let key_envelope = &mut ctx.accounts.key_envelope;
+        key_envelope.journal_entry = ctx.accounts.journal_entry.key();
+        key_envelope.reader = reader;
+        key_envelope.ephemeral_public_key = ephemeral_public_key;
+        key_envelope.nonce = nonce;
+        key_envelope.wrapped_key = wrapped_key;
+        key_envelope.bump = ctx.bumps.key_envelope;
+        msg!("Key for journal entry {} granted to {}", ctx.accounts.journal_entry.id, reader);
+        Ok(())
+    }
+
+    // Removes the envelope. A reader who already unwrapped the key keeps it, so rotate the content key
+    // (re-encrypt the entry and re-grant the remaining readers) to cut them off from future versions.
+    pub fn revoke_key_envelope(ctx: Context<RevokeKeyEnvelope>, _entry_id: u64, reader: Pubkey) -> Result<()> {
+        // The envelope is closed by Anchor (`close = authority`)
+        msg!("Key for journal entry {} revoked from {}", ctx.accounts.journal_entry.id, reader);
+        Ok(())
+    }
+
+    // Opts an entry into timelocked updates (or lengthens the lock). The delay can never be shortened,
+    // otherwise someone with temporary key access could simply switch it off.
+    pub fn set_update_delay(ctx: Context<SetUpdateDelay>, _entry_id: u64, update_delay_seconds: i64) -> Result<()> {
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        if update_delay_seconds <= journal_entry.update_delay_seconds {
+            return err!(JournalError::UpdateDelayDecrease);
+        }
+        journal_entry.update_delay_seconds = update_delay_seconds;
+        msg!("Journal entry {} now requires a {}s delay for updates", journal_entry.id, update_delay_seconds);
+        Ok(())
+    }
+
+    pub fn propose_update(ctx: Context<ProposeUpdate>, _entry_id: u64, title: String, message: String) -> Result<()> {
+        validate_entry_content(&title, &message, &ctx.accounts.program_config, &ctx.accounts.journal_entry.locale)?;
+        let journal_entry = &ctx.accounts.journal_entry;
+        if journal_entry.update_delay_seconds == 0 {
+            return err!(JournalError::UpdateNotTimelocked);
+        }
+
+        let pending_update = &mut ctx.accounts.pending_update;
+        pending_update.entry = journal_entry.key();
+        pending_update.title = title;
+        pending_update.message = message;
+        pending_update.executable_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(journal_entry.update_delay_seconds)
+            .ok_or(JournalError::Overflow)?;
+        pending_update.bump = ctx.bumps.pending_update;
+
+        msg!("Update to journal entry {} proposed, executable at {}", journal_entry.id, pending_update.executable_at);
+        Ok(())
+    }
+
+    pub fn execute_update(ctx: Context<ExecuteUpdate>, _entry_id: u64) -> Result<()> {
+        let pending_update = &ctx.accounts.pending_update;
+        if Clock::get()?.unix_timestamp < pending_update.executable_at {
+            return err!(JournalError::UpdateStillLocked);
+        }
+
+        let title = pending_update.title.clone();
+        let message = pending_update.message.clone();
+        let entry_key = ctx.accounts.journal_entry.key();
+        apply_entry_update(&mut ctx.accounts.user_profile, &mut ctx.accounts.journal_entry, entry_key, title, message)?;
+
+        // The pending update account is closed by Anchor (`close = authority`)
+        msg!("Timelocked update applied to journal entry {}", ctx.accounts.journal_entry.id);
+        Ok(())
+    }
+
+    pub fn cancel_update(ctx: Context<CancelUpdate>, _entry_id: u64) -> Result<()> {
+        // The pending update account is closed by Anchor (`close = authority`)
+        msg!("Pending update to journal entry {} cancelled", ctx.accounts.journal_entry.id);
+        Ok(())
+    }
+
+    // Gives the entry a human-readable address, unique per authority. Slugs are lowercase letters, digits and
+    // hyphens; the PDA is seeded by sha256(slug). Deleting the entry does not remove its slug, so resolvers
+    // must check the entry still exists (or call remove_entry_slug first).
+    pub fn set_entry_slug(ctx: Context<SetEntrySlug>, entry_id: u64, slug: String) -> Result<()> {
+        validate_slug(&slug)?;
+
+        let slug_account = &mut ctx.accounts.slug_account;
+        if slug_account.authority != Pubkey::default() {
+            if slug_account.journal_entry == ctx.accounts.journal_entry.key() {
+                return Ok(());
+            }
+            return err!(JournalError::SlugTaken);
+        }
+
+        slug_account.authority = ctx.accounts.authority.key();
+        slug_account.journal_entry = ctx.accounts.journal_entry.key();
+        slug_account.entry_id = entry_id;
+        slug_account.slug = slug;
+        slug_account.bump = ctx.bumps.slug_account;
+        msg!("Journal entry {} is now addressable as {}", entry_id, slug_account.slug);
+        Ok(())
+    }
+
+    pub fn remove_entry_slug(ctx: Context<RemoveEntrySlug>, slug: String) -> Result<()> {
+        msg!("Slug {} removed from journal entry {}", slug, ctx.accounts.slug_account.entry_id);
+        Ok(())
+    }
+
+    pub fn delete_journal_entry(ctx: Context<DeleteJournalEntry>, _entry_id: u64) -> Result<()> {
+        authorize_signer(&ctx.accounts.authority, &ctx.accounts.signer, &ctx.accounts.session_key, PERMISSION_DELETE)?;
+        let journal_entry = &ctx.accounts.journal_entry;
+        record_entry_history(
+            &mut ctx.accounts.user_profile,
+            EntryAction::Delete,
+            journal_entry.key(),
+            &journal_entry.title,
+            &journal_entry.message,
+        )?;
+        msg!("Journal entry {} with ID {} deleted for user {}", 
+             ctx.accounts.journal_entry.key(), 
+             ctx.accounts.journal_entry.id, 
+             ctx.accounts.authority.key());
+        // Everything held by the entry (rent plus any deposit) is refunded when Anchor closes it
+        emit!(EntryDeleted {
+            authority: ctx.accounts.authority.key(),
+            entry: ctx.accounts.journal_entry.key(),
+            id: ctx.accounts.journal_entry.id,
+            refund_recipient: ctx.accounts.rent_recipient.key(),
+            refunded_lamports: ctx.accounts.journal_entry.to_account_info().lamports(),
+        });
+        // Account is closed by Anchor due to `close = rent_recipient` in `DeleteJournalEntry`
+        // Note: This leaves a "gap" in entry_ids if user_profile.entry_count is not managed.
+        // For frontend retrieval, one would iterate from 0 to user_profile.entry_count -1
+        // and attempt to fetch each. If an account is not found, it's considered deleted or never existed.
+        Ok(())
+    }
+
+    // Zero-copy variant of add_journal_entry: the text lives in fixed-size byte arrays, so handlers read and
+    // write the account data in place instead of Borsh-decoding the whole entry. It shares the profile's id
+    // counter, rate limit and history with regular entries, but leaves out fees, deposits, sessions and the
+    // other optional features to keep the path short.
+    pub fn add_zero_copy_entry(ctx: Context<AddZeroCopyEntry>, title: String, message: String) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        validate_entry_content(&title, &message, &ctx.accounts.program_config, &user_profile.locale)?;
+        let entry_key = ctx.accounts.zero_copy_entry.key();
+        record_entry_history(user_profile, EntryAction::Create, entry_key, &title, &message)?;
+        let now = Clock::get()?.unix_timestamp;
+        let chars_written = (title.chars().count() + message.chars().count()) as u64;
+        let id = record_entry_creation(user_profile, &ctx.accounts.program_config, chars_written, now)?;
+
+        let mut zero_copy_entry = ctx.accounts.zero_copy_entry.load_init()?;
+        zero_copy_entry.authority = ctx.accounts.authority.key();
+        zero_copy_entry.id = id;
+        zero_copy_entry.timestamp = now;
+        zero_copy_entry.set_title(&title);
+        zero_copy_entry.set_message(&message);
+        zero_copy_entry.locale = user_profile.locale;
+        zero_copy_entry.bump = ctx.bumps.zero_copy_entry;
+        msg!("Zero-copy entry {} added for user {}", id, ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    pub fn update_zero_copy_entry(
+        ctx: Context<ModifyZeroCopyEntry>,
+        _entry_id: u64,
+        title: String,
+        message: String,
+    ) -> Result<()> {
+        let entry_key = ctx.accounts.zero_copy_entry.key();
+        let mut zero_copy_entry = ctx.accounts.zero_copy_entry.load_mut()?;
+        // Before the setters, which would otherwise cut oversized text down to something that passes
+        validate_entry_content(&title, &message, &ctx.accounts.program_config, &zero_copy_entry.locale)?;
+        zero_copy_entry.set_title(&title);
+        zero_copy_entry.set_message(&message);
+        finish_zero_copy_update(&mut ctx.accounts.user_profile, &ctx.accounts.program_config, &mut zero_copy_entry, entry_key)?;
+        msg!("Zero-copy entry {} updated for user {}", zero_copy_entry.id, ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    // Overwrites part of the message in place, starting at byte `offset`. The patch may run past the current
+    // end of the message (extending it) but may not start after it. The result must still be valid UTF-8.
+    pub fn patch_zero_copy_message(
+        ctx: Context<ModifyZeroCopyEntry>,
+        _entry_id: u64,
+        offset: u16,
+        bytes: Vec<u8>,
+    ) -> Result<()> {
+        let entry_key = ctx.accounts.zero_copy_entry.key();
+        let mut zero_copy_entry = ctx.accounts.zero_copy_entry.load_mut()?;
+        let start = offset as usize;
+        let end = start.checked_add(bytes.len()).ok_or(JournalError::Overflow)?;
+        if start > zero_copy_entry.message_len as usize || end > MAX_MESSAGE_BYTES {
+            return err!(JournalError::MessagePatchOutOfRange);
+        }
+        zero_copy_entry.message[start..end].copy_from_slice(&bytes);
+        zero_copy_entry.message_len = zero_copy_entry.message_len.max(end as u16);
+        finish_zero_copy_update(&mut ctx.accounts.user_profile, &ctx.accounts.program_config, &mut zero_copy_entry, entry_key)?;
+        msg!("Zero-copy entry {} patched at byte {} for user {}", zero_copy_entry.id, offset, ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    pub fn delete_zero_copy_entry(ctx: Context<DeleteZeroCopyEntry>, _entry_id: u64) -> Result<()> {
+        let entry_key = ctx.accounts.zero_copy_entry.key();
+        let zero_copy_entry = ctx.accounts.zero_copy_entry.load()?;
+        record_entry_history(
+            &mut ctx.accounts.user_profile,
+            EntryAction::Delete,
+            entry_key,
+            zero_copy_entry.title()?,
+            zero_copy_entry.message()?,
+        )?;
+        msg!("Zero-copy entry {} deleted for user {}", zero_copy_entry.id, ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    pub fn reclaim_deposit(ctx: Context<ReclaimDeposit>, _entry_id: u64) -> Result<()> {
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        let amount = journal_entry.deposit_lamports;
+        if amount == 0 {
+            return err!(JournalError::NoDepositToReclaim);
+        }
+        if Clock::get()?.unix_timestamp < journal_entry.deposit_unlock_at {
+            return err!(JournalError::DepositLocked);
+        }
+
+        journal_entry.deposit_lamports = 0;
+        journal_entry.sub_lamports(amount)?;
+        ctx.accounts.rent_recipient.add_lamports(amount)?;
+
+        msg!("Reclaimed {} lamport deposit from entry {} to {}", amount, journal_entry.id, ctx.accounts.rent_recipient.key());
+        Ok(())
+    }
+
+    // Mints a 1-of-1 NFT for the entry. `uri` should point at the entry's exported JSON; the name defaults to
+    // the entry title. The mint is a PDA of the entry, so each entry can be minted at most once.
+    pub fn mint_entry_nft(ctx: Context<MintEntryNft>, _entry_id: u64, symbol: String, uri: String) -> Result<()> {
+        if symbol.len() > MAX_NFT_SYMBOL_LENGTH || uri.len() > MAX_NFT_URI_LENGTH {
+            return err!(JournalError::NftMetadataTooLong);
+        }
+        let name = nft_name(&ctx.accounts.journal_entry.title);
+
+        let nft_authority_seeds: &[&[&[u8]]] = &[&[NFT_AUTHORITY_SEED, &[ctx.bumps.nft_authority]]];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.token_account.to_account_info(),
+                    authority: ctx.accounts.nft_authority.to_account_info(),
+                },
+                nft_authority_seeds,
+            ),
+            1,
+        )?;
+
+        create_metadata_accounts_v3(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                CreateMetadataAccountsV3 {
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    mint_authority: ctx.accounts.nft_authority.to_account_info(),
+                    payer: ctx.accounts.authority.to_account_info(),
+                    update_authority: ctx.accounts.authority.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+                nft_authority_seeds,
+            ),
+            DataV2 {
+                name,
+                symbol,
+                uri,
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            true,
+            true,
+            None,
+        )?;
+
+        // A master edition with max supply 0 makes the token a true 1-of-1 and revokes our mint authority
+        create_master_edition_v3(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                CreateMasterEditionV3 {
+                    edition: ctx.accounts.master_edition.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    update_authority: ctx.accounts.authority.to_account_info(),
+                    mint_authority: ctx.accounts.nft_authority.to_account_info(),
+                    payer: ctx.accounts.authority.to_account_info(),
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+                nft_authority_seeds,
+            ),
+            Some(0),
+        )?;
+
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        journal_entry.nft_mint = Some(ctx.accounts.mint.key());
+        msg!("Journal entry {} minted as NFT {}", journal_entry.id, ctx.accounts.mint.key());
+        Ok(())
+    }
+
+    // Mints the entry as a compressed NFT into the tree configured with set_cnft_tree, at a fraction of the
+    // cost of mint_entry_nft. The leaf goes to the authority and the asset id is recorded in nft_mint.
+    pub fn mint_entry_cnft(ctx: Context<MintEntryCnft>, _entry_id: u64, symbol: String, uri: String) -> Result<()> {
+        if symbol.len() > MAX_NFT_SYMBOL_LENGTH || uri.len() > MAX_NFT_URI_LENGTH {
+            return err!(JournalError::NftMetadataTooLong);
+        }
+        let metadata = bubblegum::MetadataArgs {
+            name: nft_name(&ctx.accounts.journal_entry.title),
+            symbol,
+            uri,
+            seller_fee_basis_points: 0,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: Some(bubblegum::TokenStandard::NonFungible),
+            collection: None,
+            uses: None,
+            token_program_version: bubblegum::TokenProgramVersion::Original,
+            creators: vec![],
+        };
+
+        // The leaf about to be minted takes the tree's current mint count as its nonce
+        let nonce = bubblegum::num_minted(&ctx.accounts.tree_config)?;
+        let (asset_id, _) = Pubkey::find_program_address(
+            &[b"asset", ctx.accounts.merkle_tree.key().as_ref(), &nonce.to_le_bytes()],
+            &BUBBLEGUM_PROGRAM_ID,
+        );
+
+        let ix = bubblegum::mint_v1(
+            ctx.accounts.tree_config.key(),
+            ctx.accounts.authority.key(),
+            ctx.accounts.merkle_tree.key(),
+            ctx.accounts.nft_authority.key(),
+            &metadata,
+        )?;
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.tree_config.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.merkle_tree.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.nft_authority.to_account_info(),
+                ctx.accounts.log_wrapper.to_account_info(),
+                ctx.accounts.compression_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.bubblegum_program.to_account_info(),
+            ],
+            &[&[NFT_AUTHORITY_SEED, &[ctx.bumps.nft_authority]]],
+        )?;
+
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        journal_entry.nft_mint = Some(asset_id);
+        msg!("Journal entry {} minted as compressed NFT {}", journal_entry.id, asset_id);
+        Ok(())
+    }
+
+    // Compressed journals keep only a leaf hash per entry in a concurrent Merkle tree instead of one PDA each.
+    // The client allocates `merkle_tree` (owned by spl-account-compression, sized for the depth, buffer and
+    // canopy) in the same transaction; the CompressedJournal PDA becomes its tree authority.
+    pub fn create_compressed_journal(ctx: Context<CreateCompressedJournal>, max_depth: u32, max_buffer_size: u32) -> Result<()> {
+        let compressed_journal = &mut ctx.accounts.compressed_journal;
+        compressed_journal.authority = ctx.accounts.authority.key();
+        compressed_journal.merkle_tree = ctx.accounts.merkle_tree.key();
+        compressed_journal.max_depth = max_depth;
+        compressed_journal.max_buffer_size = max_buffer_size;
+        compressed_journal.entry_count = 0;
+        compressed_journal.bump = ctx.bumps.compressed_journal;
+
+        let ix = account_compression::init_empty_merkle_tree(
+            ctx.accounts.merkle_tree.key(),
+            compressed_journal.key(),
+            max_depth,
+            max_buffer_size,
+        )?;
+        invoke_compression(&ix, compressed_journal, &ctx.accounts.merkle_tree, &ctx.accounts.log_wrapper, &[])?;
+        msg!("Compressed journal created for user {} with tree {}", compressed_journal.authority, compressed_journal.merkle_tree);
+        Ok(())
+    }
+
+    // Entry content lives only in the CompressedEntryWritten event; indexers rebuild the leaves from it
+    pub fn append_compressed_entry(ctx: Context<AppendCompressedEntry>, title: String, message: String) -> Result<()> {
+        validate_entry_content(&title, &message, &ctx.accounts.program_config, &[0; 5])?;
+        let compressed_journal = &mut ctx.accounts.compressed_journal;
+        let index = compressed_journal.entry_count;
+        let leaf = CompressedEntryLeaf {
+            authority: compressed_journal.authority,
+            index,
+            timestamp: Clock::get()?.unix_timestamp,
+            title,
+            message,
+        };
+        let leaf_hash = leaf.hash()?;
+
+        let ix = account_compression::append(ctx.accounts.merkle_tree.key(), compressed_journal.key(), leaf_hash)?;
+        invoke_compression(&ix, compressed_journal, &ctx.accounts.merkle_tree, &ctx.accounts.log_wrapper, &[])?;
+        compressed_journal.entry_count = index.checked_add(1).ok_or(JournalError::Overflow)?;
+
+        emit!(CompressedEntryWritten { merkle_tree: compressed_journal.merkle_tree, leaf_hash, leaf });
+        Ok(())
+    }
+
+    // `previous_leaf` and `root` come from the indexer; the proof path goes in remaining_accounts, minus the
+    // levels covered by the tree's canopy.
+    pub fn update_compressed_entry<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ModifyCompressedEntry<'info>>,
+        index: u64,
+        root: [u8; 32],
+        previous_leaf: [u8; 32],
+        title: String,
+        message: String,
+    ) -> Result<()> {
+        validate_entry_content(&title, &message, &ctx.accounts.program_config, &[0; 5])?;
+        let leaf = CompressedEntryLeaf {
+            authority: ctx.accounts.compressed_journal.authority,
+            index,
+            timestamp: Clock::get()?.unix_timestamp,
+            title,
+            message,
+        };
+        let leaf_hash = leaf.hash()?;
+        replace_compressed_leaf(&ctx, index, root, previous_leaf, leaf_hash)?;
+        emit!(CompressedEntryWritten { merkle_tree: ctx.accounts.merkle_tree.key(), leaf_hash, leaf });
+        Ok(())
+    }
+
+    // Replaces the leaf with the empty node; the index is not reused
+    pub fn delete_compressed_entry<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ModifyCompressedEntry<'info>>,
+        index: u64,
+        root: [u8; 32],
+        previous_leaf: [u8; 32],
+    ) -> Result<()> {
+        replace_compressed_leaf(&ctx, index, root, previous_leaf, [0; 32])?;
+        msg!("Compressed entry {} deleted from tree {}", index, ctx.accounts.merkle_tree.key());
+        Ok(())
+    }
+
+    pub fn tip_entry(ctx: Context<TipEntry>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return err!(JournalError::InvalidTipAmount);
+        }
+
+        // The tip jar is a system-owned PDA, so a plain transfer is enough to fund it.
+        // Note: the first tip must cover rent exemption for the (empty) tip jar account.
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.tipper.to_account_info(),
+                    to: ctx.accounts.tip_jar.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let journal_entry = &mut ctx.accounts.journal_entry;
+        journal_entry.tips_received = journal_entry.tips_received.checked_add(amount).ok_or(JournalError::Overflow)?;
+
+        msg!("Entry {} of {} tipped {} lamports by {}",
+             journal_entry.id,
+             journal_entry.authority,
+             amount,
+             ctx.accounts.tipper.key());
+        Ok(())
+    }
+
+    // Works from the entry's address alone, so tips stay reachable after the entry is deleted, and after a
+    // recovery moves the profile to a new wallet
+    pub fn withdraw_tips(ctx: Context<WithdrawTips>, entry_id: u64) -> Result<()> {
+        let amount = ctx.accounts.tip_jar.lamports();
+        if amount == 0 {
+            return err!(JournalError::NoTipsToWithdraw);
+        }
+
+        // Drain the whole jar; an account left with zero lamports is simply garbage collected.
+        let journal_entry_key = ctx.accounts.journal_entry.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[TIP_JAR_SEED_PREFIX, journal_entry_key.as_ref(), &[ctx.bumps.tip_jar]]];
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.tip_jar.to_account_info(),
+                    to: ctx.accounts.authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        msg!("Withdrew {} lamports of tips from entry {} for user {}",
+             amount,
+             entry_id,
+             ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    pub fn tip_entry_spl(ctx: Context<TipEntrySpl>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return err!(JournalError::InvalidTipAmount);
+        }
+
+        // Works for both the legacy token program and token-2022 via the token interface.
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.tipper_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.tip_jar_token_account.to_account_info(),
+                    authority: ctx.accounts.tipper.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        let tally = &mut ctx.accounts.token_tip_tally;
+        tally.entry = ctx.accounts.journal_entry.key();
+        tally.mint = ctx.accounts.mint.key();
+        tally.total_tipped = tally.total_tipped.checked_add(amount).ok_or(JournalError::Overflow)?;
+        tally.bump = ctx.bumps.token_tip_tally;
+
+        msg!("Entry {} of {} tipped {} of mint {} by {}",
+             ctx.accounts.journal_entry.id,
+             ctx.accounts.journal_entry.authority,
+             amount,
+             ctx.accounts.mint.key(),
+             ctx.accounts.tipper.key());
+        Ok(())
+    }
+
+    pub fn withdraw_tips_spl(ctx: Context<WithdrawTipsSpl>, entry_id: u64) -> Result<()> {
+        let amount = ctx.accounts.tip_jar_token_account.amount;
+        if amount == 0 {
+            return err!(JournalError::NoTipsToWithdraw);
+        }
+
+        let journal_entry_key = ctx.accounts.journal_entry.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[TIP_JAR_SEED_PREFIX, journal_entry_key.as_ref(), &[ctx.bumps.tip_jar]]];
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.tip_jar_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.authority_token_account.to_account_info(),
+                    authority: ctx.accounts.tip_jar.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        msg!("Withdrew {} of mint {} in tips from entry {} for user {}",
+             amount,
+             ctx.accounts.mint.key(),
+             entry_id,
+             ctx.accounts.authority.key());
+        Ok(())
+    }
+}
+
+// Shared helpers
+fn validate_entry_content(title: &str, message: &str, program_config: &ProgramConfig, locale: &[u8; 5]) -> Result<()> {
+    let limits = program_config.locale_limits[locale_family(locale)];
+    if title.len() > MAX_TITLE_BYTES || title.chars().count() > limits.title_chars as usize {
+        return err!(JournalError::TitleTooLong);
+    }
+    if message.len() > MAX_MESSAGE_BYTES || message.chars().count() > limits.message_chars as usize {
+        return err!(JournalError::MessageTooLong);
+    }
+    Ok(())
+}
+
+// Logs "journal:<action> <entry> <digest>" through the Memo program when it was passed, so explorers and
+// compliance tools that index memos see the write. The digest is the first 8 bytes of the content hash.
+fn write_memo<'info>(
+    memo_program: &Option<Program<'info, Memo>>,
+    action: &str,
+    entry: &Pubkey,
+    title: &str,
+    message: &str,
+) -> Result<()> {
+    let Some(memo_program) = memo_program else {
+        return Ok(());
+    };
+    let content_hash = hash(&[title.as_bytes(), message.as_bytes()].concat());
+    let digest: String = content_hash.as_ref()[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    let memo_text = format!("journal:{} {} {}", action, entry, digest);
+    memo::build_memo(CpiContext::new(memo_program.to_account_info(), BuildMemo {}), memo_text.as_bytes())
+}
+
+fn validate_locale(locale: &[u8; 5]) -> Result<()> {
+    // ASCII letters, digits and hyphens, then zero padding
+    let len = locale.iter().position(|b| *b == 0).unwrap_or(locale.len());
+    let valid = locale[..len].iter().all(|b| b.is_ascii_alphanumeric() || *b == b'-')
+        && locale[len..].iter().all(|b| *b == 0);
+    if !valid {
+        return err!(JournalError::InvalidLocale);
+    }
+    Ok(())
+}
+
+fn locale_family(locale: &[u8; 5]) -> usize {
+    let language = locale.split(|b| *b == b'-' || *b == 0).next().unwrap_or_default();
+    match language.to_ascii_lowercase().as_slice() {
+        b"zh" | b"ja" | b"ko" => LOCALE_FAMILY_CJK,
+        _ => LOCALE_FAMILY_DEFAULT,
+    }
+}
+
+// Bytes the user signs off-chain for add_entry_relayed; bound to this program, the authority and a write nonce
+pub fn relayed_entry_message(authority: &Pubkey, nonce: u64, title: &str, message: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(RELAYED_ENTRY_DOMAIN.len() + 64 + 8 + 8 + title.len() + message.len());
+    bytes.extend_from_slice(RELAYED_ENTRY_DOMAIN);
+    bytes.extend_from_slice(crate::ID.as_ref());
+    bytes.extend_from_slice(authority.as_ref());
+    bytes.extend_from_slice(&nonce.to_le_bytes());
+    bytes.extend_from_slice(&(title.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(title.as_bytes());
+    bytes.extend_from_slice(&(message.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(message.as_bytes());
+    bytes
+}
+
+// Checks that the instruction before the current one is an ed25519 program instruction verifying exactly one
+// signature by `signer` over `expected_message`, with all data inline in that instruction.
+fn verify_preceding_ed25519_signature(instructions_sysvar: &AccountInfo, signer: &Pubkey, expected_message: &[u8]) -> Result<()> {
+    // Layout of the ed25519 program instruction data (see solana_sdk::ed25519_instruction)
+    const HEADER_LEN: usize = 2; // num_signatures: u8, padding: u8
+    const OFFSETS_LEN: usize = 14; // seven u16 fields
+    const SIGNATURE_LEN: usize = 64;
+    const PUBKEY_LEN: usize = 32;
+
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    if current_index == 0 {
+        return err!(JournalError::MissingSignatureVerification);
+    }
+    let ed25519_ix = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    if ed25519_ix.program_id != ed25519_program::ID || !ed25519_ix.accounts.is_empty() {
+        return err!(JournalError::MissingSignatureVerification);
+    }
+
+    let data = &ed25519_ix.data;
+    if data.len() < HEADER_LEN + OFFSETS_LEN || data[0] != 1 {
+        return err!(JournalError::InvalidSignatureVerification);
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([data[HEADER_LEN + at], data[HEADER_LEN + at + 1]]);
+    let signature_offset = read_u16(0) as usize;
+    let signature_ix_index = read_u16(2);
+    let pubkey_offset = read_u16(4) as usize;
+    let pubkey_ix_index = read_u16(6);
+    let message_offset = read_u16(8) as usize;
+    let message_size = read_u16(10) as usize;
+    let message_ix_index = read_u16(12);
+
+    // u16::MAX means "this instruction"; anything else could point the precompile at unrelated data
+    if signature_ix_index != u16::MAX || pubkey_ix_index != u16::MAX || message_ix_index != u16::MAX {
+        return err!(JournalError::InvalidSignatureVerification);
+    }
+    let signed_pubkey = data.get(pubkey_offset..pubkey_offset + PUBKEY_LEN);
+    let signed_message = data.get(message_offset..message_offset + message_size);
+    if data.get(signature_offset..signature_offset + SIGNATURE_LEN).is_none()
+        || signed_pubkey != Some(signer.as_ref())
+        || signed_message != Some(expected_message)
+    {
+        return err!(JournalError::InvalidSignatureVerification);
+    }
+    Ok(())
+}
+
+// Allows the instruction if the authority signed it directly, or if `signer` is an unexpired session key
+// of the authority carrying `permission`.
+fn authorize_signer(
+    authority: &AccountInfo,
+    signer: &Signer,
+    session_key: &Option<Account<SessionKey>>,
+    permission: u8,
+) -> Result<()> {
+    if signer.key() == authority.key() {
+        return Ok(());
+    }
+    let session_key = session_key.as_ref().ok_or(JournalError::Unauthorized)?;
+    if session_key.authority != authority.key() || session_key.session_key != signer.key() {
+        return err!(JournalError::Unauthorized);
+    }
+    if Clock::get()?.unix_timestamp >= session_key.expires_at {
+        return err!(JournalError::SessionExpired);
+    }
+    if session_key.permissions & permission != permission {
+        return err!(JournalError::PermissionDenied);
+    }
+    Ok(())
+}
+
+// Best-effort estimate of the priority fee paid by the transaction, for the economics events.
+// Clients opt in by passing the instructions sysvar as a remaining account; otherwise this is 0.
+fn priority_fee_estimate(remaining_accounts: &[AccountInfo]) -> Result<u64> {
+    const DEFAULT_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+    const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+    const SET_COMPUTE_UNIT_LIMIT_TAG: u8 = 2;
+    const SET_COMPUTE_UNIT_PRICE_TAG: u8 = 3;
+
+    let Some(instructions_sysvar) = remaining_accounts
+        .iter()
+        .find(|account| account.key() == anchor_lang::solana_program::sysvar::instructions::ID)
+    else {
+        return Ok(0);
+    };
+
+    let mut compute_unit_limit = DEFAULT_COMPUTE_UNIT_LIMIT;
+    let mut micro_lamports_per_cu: u64 = 0;
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions_sysvar) {
+        if ix.program_id == COMPUTE_BUDGET_PROGRAM_ID {
+            match ix.data.split_first() {
+                Some((&SET_COMPUTE_UNIT_LIMIT_TAG, rest)) if rest.len() >= 4 => {
+                    compute_unit_limit = u32::from_le_bytes(rest[..4].try_into().unwrap()) as u64;
+                }
+                Some((&SET_COMPUTE_UNIT_PRICE_TAG, rest)) if rest.len() >= 8 => {
+                    micro_lamports_per_cu = u64::from_le_bytes(rest[..8].try_into().unwrap());
+                }
+                _ => {}
+            }
+        }
+        index += 1;
+    }
+
+    let fee = (compute_unit_limit as u128 * micro_lamports_per_cu as u128).div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+    Ok(fee.min(u64::MAX as u128) as u64)
+}
+
+// Rejects CPI invocations unless the transaction's top-level program is allowlisted, so a program the user
+// signed for can't write entries on their behalf. Only the outermost caller is visible in the sysvar, so a
+// nested CPI is judged by the program at the top of the stack.
+fn check_cpi_caller(program_config: &ProgramConfig, instructions_sysvar: &AccountInfo) -> Result<()> {
+    if get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(());
+    }
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    let caller = load_instruction_at_checked(current_index as usize, instructions_sysvar)?.program_id;
+    if !program_config.cpi_allowlist.contains(&caller) {
+        return err!(JournalError::CpiCallerNotAllowed);
+    }
+    Ok(())
+}
+
+// Enforces the profile's token gate against the token account the writer passed in
+fn check_write_gate(
+    user_profile: &UserProfile,
+    writer: &Pubkey,
+    gate_token_account: &Option<InterfaceAccount<TokenAccount>>,
+) -> Result<()> {
+    let Some(gate_mint) = user_profile.write_gate_mint else {
+        return Ok(());
+    };
+    let token_account = gate_token_account.as_ref().ok_or(JournalError::WriteGateNotMet)?;
+    if token_account.mint != gate_mint
+        || token_account.owner != *writer
+        || token_account.amount < user_profile.write_gate_min_amount
+    {
+        return err!(JournalError::WriteGateNotMet);
+    }
+    Ok(())
+}
+
+// Records the title of a new entry when the profile has unique titles enabled. `title_claim` is created with
+// init_if_needed, so a claim that already has an authority belongs to another entry with the same title.
+fn claim_title(
+    user_profile: &UserProfile,
+    title_claim: &mut Option<Account<TitleClaim>>,
+    journal_entry: Pubkey,
+    bump: Option<u8>,
+) -> Result<()> {
+    if !user_profile.unique_titles {
+        return Ok(());
+    }
+    let (Some(title_claim), Some(bump)) = (title_claim, bump) else {
+        return err!(JournalError::TitleClaimRequired);
+    };
+    if title_claim.authority != Pubkey::default() {
+        return err!(JournalError::DuplicateTitle);
+    }
+    title_claim.authority = user_profile.authority;
+    title_claim.journal_entry = journal_entry;
+    title_claim.bump = bump;
+    Ok(())
+}
+
+// Writes new content to an entry and updates the author's writing stats
+fn apply_entry_update(
+    user_profile: &mut UserProfile,
+    journal_entry: &mut JournalEntry,
+    entry_key: Pubkey,
+    title: String,
+    message: String,
+) -> Result<()> {
+    // The title is the entry's key while unique titles are enabled
+    if user_profile.unique_titles && title != journal_entry.title {
+        return err!(JournalError::TitleLocked);
+    }
+    record_entry_history(user_profile, EntryAction::Update, entry_key, &title, &message)?;
+    let clock = Clock::get()?;
+    let chars_written = (title.chars().count() + message.chars().count()) as u64;
+    emit!(EntryUpdated { authority: user_profile.authority, entry: entry_key, id: journal_entry.id, chars_written });
+
+    journal_entry.title = title;
+    journal_entry.message = message;
+    journal_entry.timestamp = clock.unix_timestamp; // Update timestamp on modification
+
+    // Edits count towards characters written; the entry counter only tracks creations
+    user_profile.total_chars_written = user_profile.total_chars_written.checked_add(chars_written).ok_or(JournalError::Overflow)?;
+    user_profile.last_entry_timestamp = clock.unix_timestamp;
+    Ok(())
+}
+
+// Zero-copy counterpart of apply_entry_update, run after the new text has been written into the entry
+fn finish_zero_copy_update(
+    user_profile: &mut UserProfile,
+    program_config: &ProgramConfig,
+    zero_copy_entry: &mut ZeroCopyEntry,
+    entry_key: Pubkey,
+) -> Result<()> {
+    let title = zero_copy_entry.title()?;
+    let message = zero_copy_entry.message()?;
+    validate_entry_content(title, message, program_config, &zero_copy_entry.locale)?;
+    record_entry_history(user_profile, EntryAction::Update, entry_key, title, message)?;
+    let chars_written = (title.chars().count() + message.chars().count()) as u64;
+    let now = Clock::get()?.unix_timestamp;
+    emit!(EntryUpdated { authority: user_profile.authority, entry: entry_key, id: zero_copy_entry.id, chars_written });
+
+    zero_copy_entry.timestamp = now;
+    user_profile.total_chars_written = user_profile.total_chars_written.checked_add(chars_written).ok_or(JournalError::Overflow)?;
+    user_profile.last_entry_timestamp = now;
+    Ok(())
+}
+
+// Appends a leaf for a create, update or delete to the profile's incremental Merkle tree. Only the frontier
+// (the rightmost filled node at each level) is stored; the EntryHistoryAppended events carry every leaf, so
+// clients can rebuild the tree and prove that an entry had given content at some point in the history.
+fn record_entry_history(
+    user_profile: &mut UserProfile,
+    action: EntryAction,
+    entry: Pubkey,
+    title: &str,
+    message: &str,
+) -> Result<()> {
+    let leaf_index = user_profile.history_leaf_count;
+    if leaf_index >= 1 << ENTRY_HISTORY_DEPTH {
+        return err!(JournalError::EntryHistoryFull);
+    }
+    let leaf = EntryHistoryLeaf {
+        action,
+        entry,
+        content_hash: hash(&(title, message).try_to_vec()?).to_bytes(),
+        timestamp: Clock::get()?.unix_timestamp,
+    };
+    let leaf_hash = hash(&leaf.try_to_vec()?).to_bytes();
+
+    // Empty subtrees hash to `zero` at their level, starting from an all-zero leaf
+    let mut node = leaf_hash;
+    let mut zero = [0u8; 32];
+    let mut index = leaf_index;
+    for frontier_node in user_profile.history_frontier.iter_mut() {
+        if index & 1 == 0 {
+            *frontier_node = node;
+            node = hashv(&[&node, &zero]).to_bytes();
+        } else {
+            node = hashv(&[frontier_node.as_ref(), &node]).to_bytes();
+        }
+        zero = hashv(&[&zero, &zero]).to_bytes();
+        index /= 2;
+    }
+    user_profile.history_root = node;
+    user_profile.history_leaf_count = leaf_index.checked_add(1).ok_or(JournalError::Overflow)?;
+
+    emit!(EntryHistoryAppended { authority: user_profile.authority, leaf_index, leaf, root: node });
+    Ok(())
+}
+
+// Charges the protocol fee and locks the anti-spam deposit, both paid by `payer`, returning the deposit.
+// The deposit sits in the entry account on top of rent and goes back to the payer on delete or via
+// reclaim_deposit.
+fn charge_entry_costs<'info>(
+    program_config: &ProgramConfig,
+    payer: &AccountInfo<'info>,
+    journal_entry: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u64> {
+    let deposit_lamports = program_config.entry_deposit_lamports;
+    if deposit_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: journal_entry.clone(),
+                },
+            ),
+            deposit_lamports,
+        )?;
+    }
+
+    let fee_lamports = program_config.fee_lamports;
+    if fee_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: treasury.clone(),
+                },
+            ),
+            fee_lamports,
+        )?;
+    }
+    Ok(deposit_lamports)
+}
+
+// Applies the daily rate limit and profile stats for a new entry, returning the id to assign to it
+fn record_entry_creation(
+    user_profile: &mut UserProfile,
+    program_config: &ProgramConfig,
+    chars_written: u64,
+    now: i64,
+) -> Result<u64> {
+    // Daily rate limit, bucketed by UTC day of the cluster clock
+    let today = now.div_euclid(SECONDS_PER_DAY);
+    if user_profile.current_day != today {
+        user_profile.current_day = today;
+        user_profile.entries_today = 0;
+    }
+    let max_entries_per_day = program_config.max_entries_per_day;
+    if max_entries_per_day > 0 && user_profile.entries_today >= max_entries_per_day {
+        return err!(JournalError::DailyLimitReached);
+    }
+    user_profile.entries_today = user_profile.entries_today.checked_add(1).ok_or(JournalError::Overflow)?;
+
+    // Use current count as ID for this new entry, then increment it for the next one
+    let id = user_profile.entry_count;
+    user_profile.entry_count = user_profile.entry_count.checked_add(1).ok_or(JournalError::Overflow)?;
+
+    // Aggregate stats so clients don't have to download every entry
+    user_profile.total_entries_created = user_profile.total_entries_created.checked_add(1).ok_or(JournalError::Overflow)?;
+    user_profile.total_chars_written = user_profile.total_chars_written.checked_add(chars_written).ok_or(JournalError::Overflow)?;
+    user_profile.last_entry_timestamp = now;
+    Ok(id)
+}
+
+// Metaplex caps names at 32 bytes; cut on a char boundary
+fn nft_name(title: &str) -> String {
+    let mut name = title.to_string();
+    while name.len() > MAX_NFT_NAME_LENGTH {
+        name.pop();
+    }
+    name
+}
+
+// Minimal Bubblegum client. The published mpl-bubblegum crate is built against solana-program 1.x, so the
+// mint_v1 instruction and the types it encodes are mirrored here byte-for-byte.
+mod bubblegum {
+    use super::*;
+
+    const MINT_V1_DISCRIMINATOR: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
+    // TreeConfig: discriminator, tree_creator, tree_delegate, total_mint_capacity, then num_minted
+    const TREE_CONFIG_NUM_MINTED_OFFSET: usize = 8 + 32 + 32 + 8;
+
+    // Enums only list the variants we send; borsh encodes the variant index, so order must match Bubblegum
+    #[derive(AnchorSerialize)]
+    pub enum TokenStandard {
+        NonFungible,
+    }
+
+    #[derive(AnchorSerialize)]
+    pub enum TokenProgramVersion {
+        Original,
+    }
+
+    #[derive(AnchorSerialize)]
+    pub struct Collection {
+        pub verified: bool,
+        pub key: Pubkey,
+    }
+
+    #[derive(AnchorSerialize)]
+    pub struct Uses {
+        pub use_method: u8, // Burn, Multiple or Single
+        pub remaining: u64,
+        pub total: u64,
+    }
+
+    #[derive(AnchorSerialize)]
+    pub struct Creator {
+        pub address: Pubkey,
+        pub verified: bool,
+        pub share: u8,
+    }
+
+    #[derive(AnchorSerialize)]
+    pub struct MetadataArgs {
+        pub name: String,
+        pub symbol: String,
+        pub uri: String,
+        pub seller_fee_basis_points: u16,
+        pub primary_sale_happened: bool,
+        pub is_mutable: bool,
+        pub edition_nonce: Option<u8>,
+        pub token_standard: Option<TokenStandard>,
+        pub collection: Option<Collection>,
+        pub uses: Option<Uses>,
+        pub token_program_version: TokenProgramVersion,
+        pub creators: Vec<Creator>,
+    }
+
+    pub fn num_minted(tree_config: &AccountInfo) -> Result<u64> {
+        let data = tree_config.try_borrow_data()?;
+        let bytes = data
+            .get(TREE_CONFIG_NUM_MINTED_OFFSET..TREE_CONFIG_NUM_MINTED_OFFSET + 8)
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    // The leaf owner pays and is also set as the leaf delegate
+    pub fn mint_v1(
+        tree_config: Pubkey,
+        leaf_owner: Pubkey,
+        merkle_tree: Pubkey,
+        tree_delegate: Pubkey,
+        metadata: &MetadataArgs,
+    ) -> Result<Instruction> {
+        let mut data = MINT_V1_DISCRIMINATOR.to_vec();
+        metadata.serialize(&mut data)?;
+        Ok(Instruction {
+            program_id: BUBBLEGUM_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(tree_config, false),
+                AccountMeta::new_readonly(leaf_owner, false),
+                AccountMeta::new_readonly(leaf_owner, false),
+                AccountMeta::new(merkle_tree, false),
+                AccountMeta::new_readonly(leaf_owner, true),
+                AccountMeta::new_readonly(tree_delegate, true),
+                AccountMeta::new_readonly(SPL_NOOP_PROGRAM_ID, false),
+                AccountMeta::new_readonly(SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data,
+        })
+    }
+}
+
+// Hand-written CPI for spl-account-compression; the published crate is pinned to an older Anchor
+mod account_compression {
+    use super::*;
+
+    const INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+    const APPEND_DISCRIMINATOR: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+    const REPLACE_LEAF_DISCRIMINATOR: [u8; 8] = [204, 165, 76, 100, 73, 147, 0, 128];
+
+    fn build(discriminator: [u8; 8], args: impl AnchorSerialize, merkle_tree: Pubkey, tree_authority: Pubkey, proof: &[Pubkey]) -> Result<Instruction> {
+        let mut data = discriminator.to_vec();
+        args.serialize(&mut data)?;
+        let mut accounts = vec![
+            AccountMeta::new(merkle_tree, false),
+            AccountMeta::new_readonly(tree_authority, true),
+            AccountMeta::new_readonly(SPL_NOOP_PROGRAM_ID, false),
+        ];
+        accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(*node, false)));
+        Ok(Instruction { program_id: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, accounts, data })
+    }
+
+    pub fn init_empty_merkle_tree(merkle_tree: Pubkey, tree_authority: Pubkey, max_depth: u32, max_buffer_size: u32) -> Result<Instruction> {
+        build(INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR, (max_depth, max_buffer_size), merkle_tree, tree_authority, &[])
+    }
+
+    pub fn append(merkle_tree: Pubkey, tree_authority: Pubkey, leaf: [u8; 32]) -> Result<Instruction> {
+        build(APPEND_DISCRIMINATOR, leaf, merkle_tree, tree_authority, &[])
+    }
+
+    pub fn replace_leaf(
+        merkle_tree: Pubkey,
+        tree_authority: Pubkey,
+        root: [u8; 32],
+        previous_leaf: [u8; 32],
+        new_leaf: [u8; 32],
+        index: u32,
+        proof: &[Pubkey],
+    ) -> Result<Instruction> {
+        build(REPLACE_LEAF_DISCRIMINATOR, (root, previous_leaf, new_leaf, index), merkle_tree, tree_authority, proof)
+    }
+}
+
+// Signs as the CompressedJournal PDA, which is the tree authority
+fn invoke_compression<'info>(
+    ix: &Instruction,
+    compressed_journal: &Account<'info, CompressedJournal>,
+    merkle_tree: &UncheckedAccount<'info>,
+    log_wrapper: &UncheckedAccount<'info>,
+    proof: &[AccountInfo<'info>],
+) -> Result<()> {
+    let mut account_infos = vec![
+        merkle_tree.to_account_info(),
+        compressed_journal.to_account_info(),
+        log_wrapper.to_account_info(),
+    ];
+    account_infos.extend_from_slice(proof);
+    invoke_signed(
+        ix,
+        &account_infos,
+        &[&[COMPRESSED_JOURNAL_SEED, compressed_journal.authority.as_ref(), &[compressed_journal.bump]]],
+    )?;
+    Ok(())
+}
+
+fn replace_compressed_leaf<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, ModifyCompressedEntry<'info>>,
+    index: u64,
+    root: [u8; 32],
+    previous_leaf: [u8; 32],
+    new_leaf: [u8; 32],
+) -> Result<()> {
+    let compressed_journal = &ctx.accounts.compressed_journal;
+    if index >= compressed_journal.entry_count {
+        return err!(JournalError::InvalidCompressedEntryIndex);
+    }
+    let proof: Vec<Pubkey> = ctx.remaining_accounts.iter().map(|node| node.key()).collect();
+    let ix = account_compression::replace_leaf(
+        ctx.accounts.merkle_tree.key(),
+        compressed_journal.key(),
+        root,
+        previous_leaf,
+        new_leaf,
+        u32::try_from(index).map_err(|_| JournalError::InvalidCompressedEntryIndex)?,
+        &proof,
+    )?;
+    invoke_compression(&ix, compressed_journal, &ctx.accounts.merkle_tree, &ctx.accounts.log_wrapper, ctx.remaining_accounts)
+}
+
+// Layouts from before schema versioning, and earlier versions. Each upgrade fills fields the old layout lacked
+// with the values a freshly created account would get. When the current layout changes, freeze it here as the
+// next version (and recognize it in the matching read_ function).
+mod legacy {
+    use super::*;
+
+    #[derive(AnchorDeserialize, InitSpace)]
+    pub struct UserProfileV0 {
+        pub authority: Pubkey,
+        pub entry_count: u64,
+        pub total_chars_written: u64,
+        pub total_entries_created: u64,
+        pub last_entry_timestamp: i64,
+        pub current_day: i64,
+        pub entries_today: u32,
+        pub write_nonce: u64,
+        pub write_gate_mint: Option<Pubkey>,
+        pub write_gate_min_amount: u64,
+        pub unique_titles: bool,
+        pub journal_count: u32,
+        #[max_len(MAX_PINNED_ENTRIES)]
+        pub pinned_entries: Vec<Pubkey>,
+        pub recovery_authority: Option<Pubkey>,
+        pub recovery_delay_seconds: i64,
+        pub pending_recovery: Option<Pubkey>,
+        pub recovery_unlock_at: i64,
+        pub locale: [u8; 5],
+        pub history_root: [u8; 32],
+        pub history_leaf_count: u64,
+        pub history_frontier: [[u8; 32]; ENTRY_HISTORY_DEPTH],
+        pub bump: u8,
+    }
+
+    #[derive(AnchorDeserialize, InitSpace)]
+    pub struct JournalEntryV0 {
+        pub authority: Pubkey,
+        pub id: u64,
+        #[max_len(MAX_TITLE_BYTES)]
+        pub title: String,
+        #[max_len(MAX_MESSAGE_BYTES)]
+        pub message: String,
+        pub timestamp: i64,
+        pub tips_received: u64,
+        pub deposit_lamports: u64,
+        pub deposit_unlock_at: i64,
+        pub rent_payer: Pubkey,
+        pub update_delay_seconds: i64,
+        pub nft_mint: Option<Pubkey>,
+        pub is_public: bool,
+        pub reply_to: Option<Pubkey>,
+        pub reply_count: u64,
+        pub comment_count: u64,
+        pub journal: Option<Pubkey>,
+        pub sort_order: u32,
+        pub prompt_id: Option<u16>,
+        pub price_snapshot: Option<PriceSnapshot>,
+        pub location: Option<GeoPoint>,
+        pub weather_code: Option<u8>,
+        pub reader: Option<Pubkey>,
+        pub flagged: bool,
+        pub report_count: u32,
+        pub locale: [u8; 5],
+        pub bump: u8,
+    }
+
+    // The account upgraded from an older layout, or None when `body` (the data after the discriminator) is
+    // already in the current one
+    pub fn read_user_profile(body: &[u8]) -> Option<Result<UserProfile>> {
+        (body.len() == UserProfileV0::INIT_SPACE).then(|| upgrade_user_profile(body))
+    }
+
+    pub fn read_journal_entry(body: &[u8]) -> Option<Result<JournalEntry>> {
+        (body.len() == JournalEntryV0::INIT_SPACE).then(|| upgrade_journal_entry(body))
+    }
+
+    // For the explicit migrations: the account read through `read`, or AccountAlreadyCurrent if it needs no upgrade
+    pub fn read_legacy<T: Discriminator>(info: &AccountInfo, read: fn(&[u8]) -> Option<Result<T>>) -> Result<T> {
+        let data = info.try_borrow_data()?;
+        if !data.starts_with(T::DISCRIMINATOR) {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        read(&data[T::DISCRIMINATOR.len()..]).unwrap_or_else(|| err!(JournalError::AccountAlreadyCurrent))
+    }
+
+    pub fn upgrade_user_profile(body: &[u8]) -> Result<UserProfile> {
+        let old = UserProfileV0::deserialize(&mut &body[..])?;
+        Ok(UserProfile {
+            authority: old.authority,
+            entry_count: old.entry_count,
+            version: USER_PROFILE_VERSION,
+            total_chars_written: old.total_chars_written,
+            total_entries_created: old.total_entries_created,
+            last_entry_timestamp: old.last_entry_timestamp,
+            current_day: old.current_day,
+            entries_today: old.entries_today,
+            write_nonce: old.write_nonce,
+            write_gate_mint: old.write_gate_mint,
+            write_gate_min_amount: old.write_gate_min_amount,
+            unique_titles: old.unique_titles,
+            journal_count: old.journal_count,
+            pinned_entries: old.pinned_entries,
+            recovery_authority: old.recovery_authority,
+            recovery_delay_seconds: old.recovery_delay_seconds,
+            pending_recovery: old.pending_recovery,
+            recovery_unlock_at: old.recovery_unlock_at,
+            locale: old.locale,
+            history_root: old.history_root,
+            history_leaf_count: old.history_leaf_count,
+            history_frontier: old.history_frontier,
+            bump: old.bump,
+        })
+    }
+
+    pub fn upgrade_journal_entry(body: &[u8]) -> Result<JournalEntry> {
+        let old = JournalEntryV0::deserialize(&mut &body[..])?;
+        Ok(JournalEntry {
+            authority: old.authority,
+            id: old.id,
+            version: JOURNAL_ENTRY_VERSION,
+            title: old.title,
+            message: old.message,
+            timestamp: old.timestamp,
+            tips_received: old.tips_received,
+            deposit_lamports: old.deposit_lamports,
+            deposit_unlock_at: old.deposit_unlock_at,
+            rent_payer: old.rent_payer,
+            update_delay_seconds: old.update_delay_seconds,
+            nft_mint: old.nft_mint,
+            is_public: old.is_public,
+            reply_to: old.reply_to,
+            reply_count: old.reply_count,
+            comment_count: old.comment_count,
+            journal: old.journal,
+            sort_order: old.sort_order,
+            prompt_id: old.prompt_id,
+            price_snapshot: old.price_snapshot,
+            location: old.location,
+            weather_code: old.weather_code,
+            reader: old.reader,
+            flagged: old.flagged,
+            report_count: old.report_count,
+            locale: old.locale,
+            bump: old.bump,
+        })
+    }
+}
+
+// Tops up rent from `payer`, resizes the account to `space` and writes `upgraded` over it
+fn migrate_account_layout<'info, T: AccountSerialize>(
+    info: &AccountInfo<'info>,
+    upgraded: &T,
+    space: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer { from: payer.to_account_info(), to: info.clone() },
+            ),
+            shortfall,
+        )?;
+    }
+    info.realloc(space, false)?;
+    upgraded.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])
+}
+
+fn validate_handle(handle: &str) -> Result<()> {
+    let valid_length = (MIN_HANDLE_LENGTH..=MAX_HANDLE_LENGTH).contains(&handle.len());
+    let normalized = handle.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+    if !valid_length || !normalized {
+        return err!(JournalError::InvalidHandle);
+    }
+    Ok(())
+}
+
+fn validate_journal_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.chars().count() > MAX_JOURNAL_NAME_CHARS {
+        return err!(JournalError::InvalidJournalName);
+    }
+    Ok(())
+}
+
+// The last seed of a title claim or slug PDA: the text's hash, so text of any length fits in a seed. Seeds
+// call it rather than hashing inline because Anchor's IDL build can't resolve an inline hash of an argument
+// and fails; with a call it leaves these PDAs for clients to derive.
+fn text_seed(text: &str) -> [u8; 32] {
+    hash(text.as_bytes()).to_bytes()
+}
+
+fn validate_slug(slug: &str) -> Result<()> {
+    let valid_length = !slug.is_empty() && slug.len() <= MAX_SLUG_LENGTH;
+    let normalized = slug.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+    if !valid_length || !normalized {
+        return err!(JournalError::InvalidSlug);
+    }
+    Ok(())
+}
+
+// Minimal Pyth pull-oracle reader. pyth-sdk-solana only supports solana-program 1.x, so the receiver's
+// PriceUpdateV2 account is decoded here directly.
+mod pyth {
+    use super::*;
+
+    const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+    #[derive(AnchorDeserialize, PartialEq)]
+    pub enum VerificationLevel {
+        Partial { num_signatures: u8 },
+        Full,
+    }
+
+    #[derive(AnchorDeserialize)]
+    pub struct PriceFeedMessage {
+        pub feed_id: [u8; 32],
+        pub price: i64,
+        pub conf: u64,
+        pub exponent: i32,
+        pub publish_time: i64,
+    }
+
+    // Leading part of the account after the discriminator and write_authority; trailing fields are ignored
+    #[derive(AnchorDeserialize)]
+    pub struct PriceUpdateV2 {
+        pub verification_level: VerificationLevel,
+        pub price_message: PriceFeedMessage,
+    }
+
+    // Only fully verified, recent SOL/USD updates are accepted
+    pub fn sol_usd_snapshot(price_update: &AccountInfo, now: i64) -> Result<PriceSnapshot> {
+        let data = price_update.try_borrow_data()?;
+        if data.get(..8) != Some(&PRICE_UPDATE_V2_DISCRIMINATOR[..]) {
+            return err!(JournalError::InvalidPriceUpdate);
+        }
+        let update = PriceUpdateV2::deserialize(&mut data.get(8 + 32..).unwrap_or_default())?;
+        let message = update.price_message;
+        if update.verification_level != VerificationLevel::Full || message.feed_id != SOL_USD_FEED_ID {
+            return err!(JournalError::InvalidPriceUpdate);
+        }
+        if now.saturating_sub(message.publish_time) > MAX_PRICE_AGE_SECONDS {
+            return err!(JournalError::StalePrice);
+        }
+        Ok(PriceSnapshot {
+            price: message.price,
+            conf: message.conf,
+            exponent: message.exponent,
+            publish_time: message.publish_time,
+        })
+    }
+}
+
+// Deprecation support
+// Deprecated instructions keep working but call `notify_deprecated`, which attaches a borsh-encoded
+// `DeprecationNotice` as return data and emits it as an event, so SDKs can surface a warning.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DeprecationNotice {
+    pub instruction: String,
+    pub replacement: String,
+    pub removed_in_version: u16, // First PROGRAM_VERSION that will no longer accept the instruction
+}
+
+pub fn notify_deprecated(instruction: &str, replacement: &str, removed_in_version: u16) -> Result<()> {
+    let notice = DeprecationNotice {
+        instruction: instruction.to_string(),
+        replacement: replacement.to_string(),
+        removed_in_version,
+    };
+    set_return_data(&notice.try_to_vec()?);
+    msg!("DEPRECATED: {} will be removed in version {}, use {} instead", instruction, removed_in_version, replacement);
+    emit!(InstructionDeprecated { notice });
+    Ok(())
+}
+
+// Account Structs
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramConfig {
+    pub admin: Pubkey,         // Allowed to change the config
+    pub pending_admin: Option<Pubkey>, // Set by propose_admin, cleared once accepted
+    pub fee_lamports: u64,     // Charged per add_journal_entry into the treasury; 0 disables the fee
+    pub paused: bool,          // When set, every user-facing mutating instruction fails with ProgramPaused
+    pub program_version: u16,  // Instruction encoding version clients should negotiate against
+    pub max_entries_per_day: u32, // Per-user cap on add_journal_entry per UTC day; 0 means unlimited
+    pub entry_deposit_lamports: u64, // Refundable deposit locked in each new entry; 0 disables deposits
+    pub deposit_cooldown_seconds: i64, // Time after creation before the deposit can be reclaimed without deleting
+    pub cnft_merkle_tree: Option<Pubkey>, // Bubblegum tree used by mint_entry_cnft; None disables compressed minting
+    pub locale_limits: [LocaleLimits; LOCALE_FAMILIES], // Indexed by LOCALE_FAMILY_*
+    pub moderator: Option<Pubkey>, // May flag public entries
+    #[max_len(MAX_CPI_CALLERS)]
+    pub cpi_allowlist: Vec<Pubkey>, // Programs allowed to call add_journal_entry through CPI
+    pub bump: u8,
+}
+
+// Program-owned account that accumulates protocol fees until the admin withdraws them
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub bump: u8,
+}
+
+// UserProfile and JournalEntry implement the account traits by hand instead of using #[account], so that
+// old-layout accounts deserialize too (see `versioned_account!`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct UserProfile {
+    pub authority: Pubkey,
+    pub entry_count: u64, // Stores the number of entries created by this user, also used as next entry_id
+    pub version: u8, // USER_PROFILE_VERSION when written; follows the fixed-size prefix
+    pub total_chars_written: u64, // Characters (title + message) written across all creates and updates
+    pub total_entries_created: u64, // Never decremented, unlike the number of live entries
+    pub last_entry_timestamp: i64, // Unix timestamp of the most recent create or update
+    pub current_day: i64, // Day bucket (unix_timestamp / 86400) that entries_today refers to
+    pub entries_today: u32, // Entries created during current_day, for the daily rate limit
+    pub write_nonce: u64, // Next nonce a relayed write must sign; incremented on every relayed write
+    pub write_gate_mint: Option<Pubkey>, // When set, writers must hold write_gate_min_amount of this mint
+    pub write_gate_min_amount: u64,
+    pub unique_titles: bool, // When set, new entries must claim their title and titles can't be edited
+    pub journal_count: u32, // Notebooks ever created, also the index of the next one
+    #[max_len(MAX_PINNED_ENTRIES)]
+    pub pinned_entries: Vec<Pubkey>, // Featured entries, shown first on public profile pages
+    pub recovery_authority: Option<Pubkey>, // May start a recovery if the authority's key is lost
+    pub recovery_delay_seconds: i64, // Challenge period between initiate_recovery and complete_recovery
+    pub pending_recovery: Option<Pubkey>, // New authority of a recovery in progress
+    pub recovery_unlock_at: i64, // When the pending recovery can be completed
+    pub locale: [u8; 5], // Zero-padded BCP 47 tag copied onto new entries; all zeros uses the default limits
+    pub history_root: [u8; 32], // Merkle root over every create, update and delete (see record_entry_history)
+    pub history_leaf_count: u64,
+    pub history_frontier: [[u8; 32]; ENTRY_HISTORY_DEPTH],
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryAction {
+    Create,
+    Update,
+    Delete,
+}
+
+// A history leaf is the hash of its borsh encoding; content_hash is the hash of the borsh-encoded (title, message)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EntryHistoryLeaf {
+    pub action: EntryAction,
+    pub entry: Pubkey,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+// Implements what #[account] would, except that data the given legacy reader recognizes as an older layout is
+// upgraded by it. Mutating instructions then only need to grow the account (a `realloc` constraint) for the
+// current layout to be written back, which migrates the account lazily on its next write.
+macro_rules! versioned_account {
+    ($account:ident, $discriminator:expr, $legacy:path) => {
+        impl anchor_lang::Discriminator for $account {
+            const DISCRIMINATOR: &'static [u8] = &$discriminator;
+        }
+
+        impl anchor_lang::Owner for $account {
+            fn owner() -> Pubkey {
+                crate::ID
+            }
+        }
+
+        impl anchor_lang::AccountSerialize for $account {
+            fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<()> {
+                writer.write_all(Self::DISCRIMINATOR).map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+                AnchorSerialize::serialize(self, writer).map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+                Ok(())
+            }
+        }
+
+        impl anchor_lang::AccountDeserialize for $account {
+            fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+                if buf.len() < Self::DISCRIMINATOR.len() {
+                    return err!(ErrorCode::AccountDiscriminatorNotFound);
+                }
+                if &buf[..Self::DISCRIMINATOR.len()] != Self::DISCRIMINATOR {
+                    return Err(error!(ErrorCode::AccountDiscriminatorMismatch).with_account_name(stringify!($account)));
+                }
+                Self::try_deserialize_unchecked(buf)
+            }
+
+            fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+                let body = buf.get(Self::DISCRIMINATOR.len()..).ok_or(ErrorCode::AccountDidNotDeserialize)?;
+                if let Some(upgraded) = $legacy(body) {
+                    return upgraded;
+                }
+                AnchorDeserialize::deserialize(&mut &body[..]).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+            }
+        }
+    };
+}
+
+// sha256("account:<name>")[..8], as #[account] derives it
+versioned_account!(UserProfile, [32, 37, 119, 205, 179, 180, 13, 194], legacy::read_user_profile);
+versioned_account!(JournalEntry, [255, 74, 177, 178, 227, 112, 46, 152], legacy::read_journal_entry);
+
+// Price is `price * 10^exponent` USD, with confidence interval `conf` on the same scale
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct PriceSnapshot {
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct LocaleLimits {
+    pub title_chars: u16,
+    pub message_chars: u16,
+}
+
+// Fixed-point coordinates in 1e-7 degrees (~1 cm precision)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct GeoPoint {
+    pub latitude_e7: i32,
+    pub longitude_e7: i32,
+}
+
+// Prompts the daily prompt is drawn from, managed by the admin
+#[account]
+#[derive(InitSpace)]
+pub struct PromptList {
+    #[max_len(MAX_PROMPTS, MAX_PROMPT_CHARS)]
+    pub prompts: Vec<String>,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DailyPrompt {
+    pub day: i64,                // Day bucket (unix_timestamp / 86400) prompt_id was drawn for
+    pub prompt_id: Option<u16>,  // Index into PromptList; None until the first draw
+    pub pending_randomness: Option<Pubkey>, // Switchboard randomness account of a committed, unrevealed draw
+    pub commit_slot: u64,
+    pub bump: u8,
+}
+
+// Tree authority for a user's compressed entries; entry_count is the next leaf index
+#[account]
+#[derive(InitSpace)]
+pub struct CompressedJournal {
+    pub authority: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub entry_count: u64,
+    pub bump: u8,
+}
+
+// A compressed entry's leaf is the hash of its borsh encoding
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CompressedEntryLeaf {
+    pub authority: Pubkey,
+    pub index: u64,
+    pub timestamp: i64,
+    pub title: String,
+    pub message: String,
+}
+
+impl CompressedEntryLeaf {
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        Ok(hash(&self.try_to_vec()?).to_bytes())
+    }
+}
+
+// A named notebook of entries
+#[account]
+#[derive(InitSpace)]
+pub struct Journal {
+    pub authority: Pubkey,
+    pub index: u32,
+    #[max_len(MAX_JOURNAL_NAME_CHARS)]
+    pub name: String,
+    #[max_len(MAX_JOURNAL_DESCRIPTION_CHARS)]
+    pub description: String,
+    pub default_visibility: bool, // true publishes new entries in this notebook as they are created
+    pub entry_count: u64, // Entries ever created in this notebook
+    pub bump: u8,
+}
+
+// Display data for a profile, kept separate from UserProfile so it can grow without reallocating
+#[account]
+#[derive(InitSpace)]
+pub struct ProfileMetadata {
+    pub authority: Pubkey,
+    #[max_len(MAX_DISPLAY_NAME_CHARS)]
+    pub display_name: String,
+    #[max_len(MAX_BIO_CHARS)]
+    pub bio: String,
+    #[max_len(MAX_AVATAR_URI_LENGTH)]
+    pub avatar_uri: String, // e.g. an Arweave or IPFS link to the avatar image
+    pub bump: u8,
+}
+
+// Global registry record: the PDA address is derived from the normalized handle, so each handle exists once
+#[account]
+#[derive(InitSpace)]
+pub struct Handle {
+    pub authority: Pubkey,
+    pub user_profile: Pubkey, // Profile the handle resolves to
+    #[max_len(MAX_HANDLE_LENGTH)]
+    pub handle: String,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+
+// authority and id must stay the first two fields: indexers filter on them at ENTRY_AUTHORITY_OFFSET and
+// ENTRY_ID_OFFSET, the same offsets as in the legacy layout and in ZeroCopyEntry.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct JournalEntry {
+    pub authority: Pubkey,    // User who owns the entry
+    pub id: u64,              // ID of the entry, specific to the user (0, 1, 2, ...)
+    pub version: u8,          // JOURNAL_ENTRY_VERSION when written; follows the fixed-size prefix
+    #[max_len(MAX_TITLE_BYTES)]
+    pub title: String,
+    #[max_len(MAX_MESSAGE_BYTES)]
+    pub message: String,
+    pub timestamp: i64,
+    pub tips_received: u64,   // Total lamports ever tipped to this entry (not reduced by withdrawals)
+    pub deposit_lamports: u64, // Anti-spam deposit held in this account beyond rent
+    pub deposit_unlock_at: i64, // Unix timestamp after which reclaim_deposit is allowed
+    pub rent_payer: Pubkey,   // Refunded on delete: the authority, or the sponsor pool
+    pub update_delay_seconds: i64, // 0 allows direct updates; otherwise updates go through propose/execute_update
+    pub nft_mint: Option<Pubkey>, // Mint from mint_entry_nft, or asset id from mint_entry_cnft; an entry mints once
+    pub is_public: bool,      // Entries start private; set_entry_visibility publishes them
+    pub reply_to: Option<Pubkey>, // Parent entry when this entry was posted as a reply
+    pub reply_count: u64,     // Replies ever posted to this entry (not reduced when replies are deleted)
+    pub comment_count: u64,   // Comments ever posted, also the index of the next comment
+    pub journal: Option<Pubkey>, // Notebook the entry was created in; None is the default stream
+    pub sort_order: u32,      // User-chosen position; clients sort by (sort_order, id), so 0 keeps creation order
+    pub prompt_id: Option<u16>, // Index in PromptList of the daily prompt the entry answers
+    pub price_snapshot: Option<PriceSnapshot>, // SOL/USD from Pyth when the entry was created
+    pub location: Option<GeoPoint>,
+    pub weather_code: Option<u8>, // WMO weather interpretation code
+    pub reader: Option<Pubkey>, // When set, this wallet can read the entry once via mark_read, which closes it
+    pub flagged: bool,        // Set by the moderator; content is untouched
+    pub report_count: u32,
+    pub locale: [u8; 5],      // Author's locale at creation; selects the limits edits are validated against
+    pub bump: u8,
+}
+
+// Fixed-layout entry mapped directly onto the account data. Each text field is a byte array with a length
+// in front of it; bytes past the length are ignored. Clients can decode it straight from the offsets, and
+// authority and id sit at the same offsets as in JournalEntry.
+#[account(zero_copy)]
+pub struct ZeroCopyEntry {
+    pub authority: Pubkey,
+    pub id: u64,              // Shares the profile's entry_count with regular entries
+    pub timestamp: i64,
+    pub title_len: u16,
+    pub message_len: u16,
+    pub title: [u8; MAX_TITLE_BYTES],
+    pub message: [u8; MAX_MESSAGE_BYTES],
+    pub locale: [u8; 5],
+    pub bump: u8,
+    pub padding: [u8; 2],     // Keeps the size a multiple of 8 with no implicit padding
+}
+
+impl ZeroCopyEntry {
+    pub fn title(&self) -> Result<&str> {
+        std::str::from_utf8(&self.title[..self.title_len as usize]).map_err(|_| error!(JournalError::InvalidUtf8))
+    }
+
+    pub fn message(&self) -> Result<&str> {
+        std::str::from_utf8(&self.message[..self.message_len as usize]).map_err(|_| error!(JournalError::InvalidUtf8))
+    }
+
+    // Callers validate the text first; anything past the byte budget is cut off here
+    pub fn set_title(&mut self, title: &str) {
+        let len = title.len().min(MAX_TITLE_BYTES);
+        self.title[..len].copy_from_slice(&title.as_bytes()[..len]);
+        self.title_len = len as u16;
+    }
+
+    pub fn set_message(&mut self, message: &str) {
+        let len = message.len().min(MAX_MESSAGE_BYTES);
+        self.message[..len].copy_from_slice(&message.as_bytes()[..len]);
+        self.message_len = len as u16;
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Comment {
+    pub journal_entry: Pubkey,
+    pub commenter: Pubkey,
+    pub index: u64,
+    #[max_len(MAX_COMMENT_CHARS)]
+    pub body: String,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+// Marks that `reactor` has reacted to the entry, so each reactor counts once
+#[account]
+#[derive(InitSpace)]
+pub struct Reaction {
+    pub journal_entry: Pubkey,
+    pub reactor: Pubkey,
+    pub emoji_code: u8,
+    pub bump: u8,
+}
+
+// One per (entry, reporter); reason codes are defined by clients
+#[account]
+#[derive(InitSpace)]
+pub struct Report {
+    pub journal_entry: Pubkey,
+    pub reporter: Pubkey,
+    pub reason_code: u8,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+// Links a pseudonymous authority to a hidden owner wallet via a hash commitment
+#[account]
+#[derive(InitSpace)]
+pub struct Pseudonym {
+    pub authority: Pubkey,       // The pseudonym's signing key, which authors its entries
+    pub commitment: [u8; 32],    // hash(owner || salt)
+    pub revealed_owner: Option<Pubkey>, // Set once the owner opens the commitment
+    pub bump: u8,
+}
+
+// Content key of an encrypted entry, wrapped to one reader's X25519 key
+#[account]
+#[derive(InitSpace)]
+pub struct KeyEnvelope {
+    pub journal_entry: Pubkey,
+    pub reader: Pubkey,
+    pub ephemeral_public_key: [u8; 32], // Sender half of the X25519 exchange
+    pub nonce: [u8; 24],
+    pub wrapped_key: [u8; 48], // Encrypted content key plus authentication tag
+    pub bump: u8,
+}
+
+// Ring buffer of the most recently published entries; the newest sits just before `next_slot` (wrapping). Entries made
+// private or deleted after publishing stay listed until overwritten, so readers must re-check them.
+#[account]
+#[derive(InitSpace)]
+pub struct FeedHead {
+    pub entries: [Pubkey; FEED_HEAD_CAPACITY], // Pubkey::default() marks slots not yet written
+    pub next_slot: u16,
+    pub total_published: u64,
+    pub bump: u8,
+}
+
+impl FeedHead {
+    pub fn push(&mut self, entry: Pubkey) -> Result<()> {
+        self.entries[self.next_slot as usize] = entry;
+        self.next_slot = ((self.next_slot as usize + 1) % FEED_HEAD_CAPACITY) as u16;
+        self.total_published = self.total_published.checked_add(1).ok_or(JournalError::Overflow)?;
+        Ok(())
+    }
+}
+
+// Per-entry reaction counters, one per emoji code
+#[account]
+#[derive(InitSpace)]
+pub struct ReactionTally {
+    pub journal_entry: Pubkey,
+    pub counts: [u64; REACTION_KINDS],
+    pub bump: u8,
+}
+
+// Marks that `viewer` has viewed the entry; the entry is part of the seeds, so the receipt stays small
+#[account]
+#[derive(InitSpace)]
+pub struct ViewReceipt {
+    pub viewer: Pubkey,
+    pub bump: u8,
+}
+
+// Distinct viewers of an entry, counted by record_view
+#[account]
+#[derive(InitSpace)]
+pub struct ViewTally {
+    pub journal_entry: Pubkey,
+    pub count: u64,
+    pub bump: u8,
+}
+
+// Reserves a title for one of the authority's entries while unique titles are enabled
+#[account]
+#[derive(InitSpace)]
+pub struct TitleClaim {
+    pub authority: Pubkey,
+    pub journal_entry: Pubkey,
+    pub bump: u8,
+}
+
+// Maps a per-authority slug to one of the authority's entries
+#[account]
+#[derive(InitSpace)]
+pub struct Slug {
+    pub authority: Pubkey,
+    pub journal_entry: Pubkey,
+    pub entry_id: u64,
+    #[max_len(MAX_SLUG_LENGTH)]
+    pub slug: String,
+    pub bump: u8,
+}
+
+// Delegates a subset of the authority's rights to an ephemeral key
+#[account]
+#[derive(InitSpace)]
+pub struct SessionKey {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64, // Unix timestamp after which the key is no longer accepted
+    pub permissions: u8, // PERMISSION_* bits
+    pub bump: u8,
+}
+
+// A proposed edit to a timelocked entry, applied by execute_update once `executable_at` has passed
+#[account]
+#[derive(InitSpace)]
+pub struct PendingUpdate {
+    pub entry: Pubkey,
+    #[max_len(MAX_TITLE_BYTES)]
+    pub title: String,
+    #[max_len(MAX_MESSAGE_BYTES)]
+    pub message: String,
+    pub executable_at: i64,
+    pub bump: u8,
+}
+
+// Per-(entry, mint) running total of SPL tips; the tokens themselves sit in the tip jar's ATA
+#[account]
+#[derive(InitSpace)]
+pub struct TokenTipTally {
+    pub entry: Pubkey,
+    pub mint: Pubkey,
+    pub total_tipped: u64, // Not reduced by withdrawals
+    pub bump: u8,
+}
+
+// Contexts for Instructions
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProgramConfig::INIT_SPACE,
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Treasury::INIT_SPACE,
+        seeds = [TREASURY_SEED],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    // Only the upgrade authority may create the config, so nobody can front-run a fresh deployment.
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::JournalProgram>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ JournalError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        has_one = admin @ JournalError::Unauthorized,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = program_config.pending_admin == Some(new_admin.key()) @ JournalError::Unauthorized,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub new_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        has_one = admin @ JournalError::Unauthorized,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeedHead<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        has_one = admin @ JournalError::Unauthorized,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + FeedHead::INIT_SPACE,
+        seeds = [FEED_HEAD_SEED],
+        bump
+    )]
+    pub feed_head: Account<'info, FeedHead>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddPrompt<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        has_one = admin @ JournalError::Unauthorized,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + PromptList::INIT_SPACE,
+        seeds = [PROMPT_LIST_SEED],
+        bump
+    )]
+    pub prompt_list: Account<'info, PromptList>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CommitDailyPrompt<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        space = 8 + DailyPrompt::INIT_SPACE,
+        seeds = [DAILY_PROMPT_SEED],
+        bump
+    )]
+    pub daily_prompt: Account<'info, DailyPrompt>,
+    /// CHECK: owner constrained to Switchboard; parsed in the handler
+    #[account(owner = SWITCHBOARD_ON_DEMAND_PROGRAM_ID)]
+    pub randomness_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealDailyPrompt<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [DAILY_PROMPT_SEED],
+        bump = daily_prompt.bump,
+        constraint = daily_prompt.pending_randomness == Some(randomness_account.key()) @ JournalError::InvalidRandomness,
+    )]
+    pub daily_prompt: Account<'info, DailyPrompt>,
+    #[account(
+        seeds = [PROMPT_LIST_SEED],
+        bump = prompt_list.bump,
+        constraint = !prompt_list.prompts.is_empty() @ JournalError::InvalidPrompt,
+    )]
+    pub prompt_list: Account<'info, PromptList>,
+    /// CHECK: must be the committed randomness account; parsed in the handler
+    pub randomness_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeUserProfile<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + UserProfile::INIT_SPACE, // 8 bytes for discriminator
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    pub authority: Signer<'info>,
+    // Funds rent; pass the authority again unless a backend wallet is sponsoring the user
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+// The title is used in the title_claim seeds
+#[instruction(title: String)]
+pub struct AddJournalEntry<'info> {
+    // realloc upgrades an old-layout profile in place (a no-op once it's current)
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+        realloc = 8 + UserProfile::INIT_SPACE,
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + JournalEntry::INIT_SPACE, // 8 bytes for discriminator
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &user_profile.entry_count.to_le_bytes()],
+        bump
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+    /// CHECK: authorized in the handler, either as `signer` or through `session_key`
+    pub authority: UncheckedAccount<'info>,
+    // The authority itself, or a session key holder when `session_key` is passed
+    pub signer: Signer<'info>,
+    pub session_key: Option<Account<'info, SessionKey>>,
+    // The signer's token account for the profile's write gate mint; required only when a gate is set
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    // Required only when the profile has unique titles enabled
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + TitleClaim::INIT_SPACE,
+        seeds = [TITLE_CLAIM_SEED, authority.key().as_ref(), &text_seed(&title)],
+        bump
+    )]
+    pub title_claim: Option<Account<'info, TitleClaim>>,
+    /// CHECK: pinned to the SOL/USD push oracle feed account; decoded and validated in the handler
+    // Pass the Pyth SOL/USD feed to freeze the current price on the entry
+    #[account(
+        seeds = [&PYTH_SHARD_ID.to_le_bytes(), SOL_USD_FEED_ID.as_ref()],
+        seeds::program = PYTH_PUSH_ORACLE_PROGRAM_ID,
+        bump
+    )]
+    pub price_update: Option<UncheckedAccount<'info>>,
+    // Pass today's DailyPrompt to record that the entry answers it
+    #[account(seeds = [DAILY_PROMPT_SEED], bump = daily_prompt.bump)]
+    pub daily_prompt: Option<Account<'info, DailyPrompt>>,
+    // Pass one of the authority's notebooks to file the entry there instead of the default stream
+    #[account(mut, has_one = authority)]
+    pub journal: Option<Account<'info, Journal>>,
+    // Required when `journal` publishes new entries by default
+    #[account(mut, seeds = [FEED_HEAD_SEED], bump = feed_head.bump)]
+    pub feed_head: Option<Account<'info, FeedHead>>,
+    // Pass a public entry (from any authority) to post the new entry as a reply to it
+    #[account(mut)]
+    pub reply_parent: Option<Account<'info, JournalEntry>>,
+    // Pass the SPL Memo program to attach a human-readable note of the write to the transaction
+    pub memo_program: Option<Program<'info, Memo>>,
+    /// CHECK: address constraint pins this to the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    // Funds rent, deposit and fee; pass the authority again unless a backend wallet is sponsoring the user
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64, title: String)]
+pub struct AddEntryRelayed<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + JournalEntry::INIT_SPACE,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &user_profile.entry_count.to_le_bytes()],
+        bump
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+    /// CHECK: not a signer here; authorization comes from the verified ed25519 signature
+    pub authority: UncheckedAccount<'info>,
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + TitleClaim::INIT_SPACE,
+        seeds = [TITLE_CLAIM_SEED, authority.key().as_ref(), &text_seed(&title)],
+        bump
+    )]
+    pub title_claim: Option<Account<'info, TitleClaim>>,
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    /// CHECK: address constraint pins this to the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Shared by the instructions that change a setting on the signer's own profile: set_write_gate, unpin_entry,
+// set_recovery_authority, cancel_recovery, set_locale and set_unique_titles
+#[derive(Accounts)]
+pub struct UpdateProfile<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct PinEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateRecovery<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        constraint = user_profile.recovery_authority == Some(recovery_authority.key()) @ JournalError::Unauthorized,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    pub recovery_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateJournal<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Journal::INIT_SPACE,
+        seeds = [JOURNAL_SEED, authority.key().as_ref(), &user_profile.journal_count.to_le_bytes()],
+        bump
+    )]
+    pub journal: Account<'info, Journal>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(journal_index: u32)]
+pub struct UpdateJournal<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_SEED, authority.key().as_ref(), &journal_index.to_le_bytes()],
+        bump = journal.bump,
+        has_one = authority,
+    )]
+    pub journal: Account<'info, Journal>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(journal_index: u32)]
+pub struct CloseJournal<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [JOURNAL_SEED, authority.key().as_ref(), &journal_index.to_le_bytes()],
+        bump = journal.bump,
+        has_one = authority,
+    )]
+    pub journal: Account<'info, Journal>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProfileMetadata<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ProfileMetadata::INIT_SPACE,
+        seeds = [PROFILE_METADATA_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub profile_metadata: Account<'info, ProfileMetadata>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(handle: String)]
+pub struct ClaimHandle<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    // init_if_needed so an existing claim surfaces as HandleTaken instead of a generic "already in use"
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Handle::INIT_SPACE,
+        seeds = [HANDLE_SEED, handle.as_bytes()],
+        bump
+    )]
+    pub handle_account: Account<'info, Handle>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(handle: String)]
+pub struct ReleaseHandle<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [HANDLE_SEED, handle.as_bytes()],
+        bump = handle_account.bump,
+        has_one = authority @ JournalError::Unauthorized,
+    )]
+    pub handle_account: Account<'info, Handle>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_pubkey: Pubkey)]
+pub struct CreateSessionKey<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SessionKey::INIT_SPACE,
+        seeds = [SESSION_KEY_SEED, authority.key().as_ref(), session_pubkey.as_ref()],
+        bump
+    )]
+    pub session_key: Account<'info, SessionKey>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_pubkey: Pubkey)]
+pub struct RevokeSessionKey<'info> {
+    // Deliberately not gated by the pause switch: revoking a leaked key must work during an incident
+    #[account(
+        mut,
+        seeds = [SESSION_KEY_SEED, authority.key().as_ref(), session_pubkey.as_ref()],
+        bump = session_key.bump,
+        has_one = authority,
+        close = authority,
+    )]
+    pub session_key: Account<'info, SessionKey>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundSponsorPool<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [SPONSOR_POOL_SEED],
+        bump,
+    )]
+    pub sponsor_pool: SystemAccount<'info>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddJournalEntrySponsored<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    /// CHECK: created in the handler with the sponsor pool as payer; seeds pin the address
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &user_profile.entry_count.to_le_bytes()],
+        bump
+    )]
+    pub journal_entry: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [SPONSOR_POOL_SEED],
+        bump,
+    )]
+    pub sponsor_pool: SystemAccount<'info>,
+    pub authority: Signer<'info>,
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source_entry_id: u64)]
+pub struct DuplicateEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &source_entry_id.to_le_bytes()],
+        bump = source_entry.bump,
+        has_one = authority,
+    )]
+    pub source_entry: Account<'info, JournalEntry>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + JournalEntry::INIT_SPACE,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &user_profile.entry_count.to_le_bytes()],
+        bump
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    // Destination notebook; may be the source entry's own notebook
+    #[account(mut, has_one = authority)]
+    pub journal: Option<Account<'info, Journal>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProfile<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    /// CHECK: owner, discriminator and size are checked in the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: UncheckedAccount<'info>,
+    /// CHECK: only used to derive the profile address
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct MigrateEntryLayout<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    /// CHECK: owner, discriminator and size are checked in the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump,
+    )]
+    pub journal_entry: UncheckedAccount<'info>,
+    /// CHECK: only used to derive the entry address
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, old_authority.key().as_ref()],
+        bump = old_profile.bump,
+    )]
+    pub old_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, new_authority.key().as_ref()],
+        bump = new_profile.bump,
+        constraint = new_profile.authority == new_authority.key() @ JournalError::Unauthorized,
+    )]
+    pub new_profile: Account<'info, UserProfile>,
+    /// CHECK: must sign unless old_profile was recovered to new_authority; receives the old rent when it signs
+    #[account(mut)]
+    pub old_authority: UncheckedAccount<'info>,
+    // Pays rent for the re-created entries
+    #[account(mut)]
+    pub new_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct UpdateJournalEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    // user_profile is needed to keep the aggregate writing stats up to date.
+    // Both accounts are upgraded in place if they still use an old layout, with the signer covering the rent.
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+        realloc = 8 + UserProfile::INIT_SPACE,
+        realloc::payer = signer,
+        realloc::zero = false,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority, // This checks journal_entry.authority == authority.key()
+        realloc = 8 + JournalEntry::INIT_SPACE,
+        realloc::payer = signer,
+        realloc::zero = false,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    /// CHECK: authorized in the handler, either as `signer` or through `session_key`
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub session_key: Option<Account<'info, SessionKey>>,
+    // Pass the SPL Memo program to attach a human-readable note of the write to the transaction
+    pub memo_program: Option<Program<'info, Memo>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct SetEntryVisibility<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(mut, seeds = [FEED_HEAD_SEED], bump = feed_head.bump)]
+    pub feed_head: Account<'info, FeedHead>,
+    /// CHECK: authorized in the handler, either as `signer` or through `session_key`
+    pub authority: UncheckedAccount<'info>,
+    pub signer: Signer<'info>,
+    pub session_key: Option<Account<'info, SessionKey>>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct MarkRead<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    // The read is recorded in the author's entry history; an old-layout profile is upgraded first, at the
+    // reader's expense
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, journal_entry.authority.as_ref()],
+        bump = user_profile.bump,
+        realloc = 8 + UserProfile::INIT_SPACE,
+        realloc::payer = reader,
+        realloc::zero = false,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, journal_entry.authority.as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        constraint = journal_entry.reader == Some(reader.key()) @ JournalError::NotEntryReader,
+        close = rent_recipient,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(mut, address = journal_entry.rent_payer)]
+    pub rent_recipient: SystemAccount<'info>,
+    #[account(mut)]
+    pub reader: Signer<'info>,
+    // Pass the entry's claim, if it has one, to free the title along with the entry
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [TITLE_CLAIM_SEED, journal_entry.authority.as_ref(), &text_seed(&journal_entry.title)],
+        bump = title_claim.bump,
+        constraint = title_claim.journal_entry == journal_entry.key(),
+    )]
+    pub title_claim: Option<Account<'info, TitleClaim>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct UpdateEntryMetadata<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    /// CHECK: authorized in the handler, either as `signer` or through `session_key`
+    pub authority: UncheckedAccount<'info>,
+    pub signer: Signer<'info>,
+    pub session_key: Option<Account<'info, SessionKey>>,
+}
+
+#[derive(Accounts)]
+pub struct AddComment<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(mut, constraint = journal_entry.is_public @ JournalError::EntryNotPublic)]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        init,
+        payer = commenter,
+        space = 8 + Comment::INIT_SPACE,
+        seeds = [
+            COMMENT_SEED,
+            journal_entry.key().as_ref(),
+            commenter.key().as_ref(),
+            &journal_entry.comment_count.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub comment: Account<'info, Comment>,
+    #[account(mut)]
+    pub commenter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(comment_index: u64)]
+pub struct DeleteComment<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        close = commenter,
+        seeds = [COMMENT_SEED, journal_entry.key().as_ref(), commenter.key().as_ref(), &comment_index.to_le_bytes()],
+        bump = comment.bump,
+        has_one = journal_entry,
+        has_one = commenter,
+    )]
+    pub comment: Account<'info, Comment>,
+    /// CHECK: may already be closed; only read to authorize the entry's authority
+    pub journal_entry: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub commenter: SystemAccount<'info>,
+    // The commenter, or the authority of the entry
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReactToEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(constraint = journal_entry.is_public @ JournalError::EntryNotPublic)]
+    pub journal_entry: Account<'info, JournalEntry>,
+    // init_if_needed so a second reaction surfaces as AlreadyReacted
+    #[account(
+        init_if_needed,
+        payer = reactor,
+        space = 8 + Reaction::INIT_SPACE,
+        seeds = [REACTION_SEED, journal_entry.key().as_ref(), reactor.key().as_ref()],
+        bump
+    )]
+    pub reaction: Account<'info, Reaction>,
+    #[account(
+        init_if_needed,
+        payer = reactor,
+        space = 8 + ReactionTally::INIT_SPACE,
+        seeds = [REACTION_TALLY_SEED, journal_entry.key().as_ref()],
+        bump
+    )]
+    pub reaction_tally: Account<'info, ReactionTally>,
+    #[account(mut)]
+    pub reactor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReportEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(mut, constraint = journal_entry.is_public @ JournalError::EntryNotPublic)]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        init,
+        payer = reporter,
+        space = 8 + Report::INIT_SPACE,
+        seeds = [REPORT_SEED, journal_entry.key().as_ref(), reporter.key().as_ref()],
+        bump
+    )]
+    pub report: Account<'info, Report>,
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetEntryFlagged<'info> {
+    // Deliberately not gated by the pause switch: the moderator must be able to hide abusive entries during an
+    // incident
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = program_config.moderator == Some(moderator.key()) @ JournalError::Unauthorized,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(mut, constraint = journal_entry.is_public @ JournalError::EntryNotPublic)]
+    pub journal_entry: Account<'info, JournalEntry>,
+    pub moderator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePseudonym<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Pseudonym::INIT_SPACE,
+        seeds = [PSEUDONYM_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub pseudonym: Account<'info, Pseudonym>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealPseudonym<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [PSEUDONYM_SEED, pseudonym.authority.as_ref()],
+        bump = pseudonym.bump,
+    )]
+    pub pseudonym: Account<'info, Pseudonym>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64, reader: Pubkey)]
+pub struct GrantKeyEnvelope<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + KeyEnvelope::INIT_SPACE,
+        seeds = [KEY_ENVELOPE_SEED, journal_entry.key().as_ref(), reader.as_ref()],
+        bump
+    )]
+    pub key_envelope: Account<'info, KeyEnvelope>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64, reader: Pubkey)]
+pub struct RevokeKeyEnvelope<'info> {
+    // Deliberately not gated by the pause switch: revoking a reader's access must work during an incident
+    #[account(
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        mut,
+        seeds = [KEY_ENVELOPE_SEED, journal_entry.key().as_ref(), reader.as_ref()],
+        bump = key_envelope.bump,
+        close = authority,
+    )]
+    pub key_envelope: Account<'info, KeyEnvelope>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveReaction<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        close = reactor,
+        seeds = [REACTION_SEED, reaction.journal_entry.as_ref(), reactor.key().as_ref()],
+        bump = reaction.bump,
+        has_one = reactor,
+    )]
+    pub reaction: Account<'info, Reaction>,
+    #[account(
+        mut,
+        seeds = [REACTION_TALLY_SEED, reaction.journal_entry.as_ref()],
+        bump = reaction_tally.bump,
+    )]
+    pub reaction_tally: Account<'info, ReactionTally>,
+    #[account(mut)]
+    pub reactor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordView<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(constraint = journal_entry.is_public @ JournalError::EntryNotPublic)]
+    pub journal_entry: Account<'info, JournalEntry>,
+    // init_if_needed so a repeat view is a no-op rather than an error
+    #[account(
+        init_if_needed,
+        payer = viewer,
+        space = 8 + ViewReceipt::INIT_SPACE,
+        seeds = [VIEW_RECEIPT_SEED, journal_entry.key().as_ref(), viewer.key().as_ref()],
+        bump
+    )]
+    pub view_receipt: Account<'info, ViewReceipt>,
+    #[account(
+        init_if_needed,
+        payer = viewer,
+        space = 8 + ViewTally::INIT_SPACE,
+        seeds = [VIEW_TALLY_SEED, journal_entry.key().as_ref()],
+        bump
+    )]
+    pub view_tally: Account<'info, ViewTally>,
+    #[account(mut)]
+    pub viewer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct SetUpdateDelay<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct ProposeUpdate<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingUpdate::INIT_SPACE,
+        seeds = [PENDING_UPDATE_SEED, journal_entry.key().as_ref()],
+        bump
+    )]
+    pub pending_update: Account<'info, PendingUpdate>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct ExecuteUpdate<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        mut,
+        seeds = [PENDING_UPDATE_SEED, journal_entry.key().as_ref()],
+        bump = pending_update.bump,
+        close = authority,
+    )]
+    pub pending_update: Account<'info, PendingUpdate>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct CancelUpdate<'info> {
+    // Deliberately not gated by the pause switch, like revoking a session key
+    #[account(
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        mut,
+        seeds = [PENDING_UPDATE_SEED, journal_entry.key().as_ref()],
+        bump = pending_update.bump,
+        close = authority,
+    )]
+    pub pending_update: Account<'info, PendingUpdate>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct DeleteJournalEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    // The delete is recorded in the profile's entry history; an old-layout profile is upgraded first
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+        realloc = 8 + UserProfile::INIT_SPACE,
+        realloc::payer = signer,
+        realloc::zero = false,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+        close = rent_recipient,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    // Whoever paid the rent gets it back: the authority, or the sponsor pool for sponsored entries
+    #[account(mut, address = journal_entry.rent_payer)]
+    pub rent_recipient: SystemAccount<'info>,
+    /// CHECK: authorized in the handler, either as `signer` or through `session_key`
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub session_key: Option<Account<'info, SessionKey>>,
+    // Pass the entry's claim, if it has one, to free the title along with the entry
+    #[account(
+        mut,
+        close = rent_recipient,
+        seeds = [TITLE_CLAIM_SEED, authority.key().as_ref(), &text_seed(&journal_entry.title)],
+        bump = title_claim.bump,
+        constraint = title_claim.journal_entry == journal_entry.key(),
+    )]
+    pub title_claim: Option<Account<'info, TitleClaim>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddZeroCopyEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+        realloc = 8 + UserProfile::INIT_SPACE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<ZeroCopyEntry>(),
+        seeds = [ZERO_COPY_ENTRY_SEED, authority.key().as_ref(), &user_profile.entry_count.to_le_bytes()],
+        bump
+    )]
+    pub zero_copy_entry: AccountLoader<'info, ZeroCopyEntry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct ModifyZeroCopyEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+        realloc = 8 + UserProfile::INIT_SPACE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [ZERO_COPY_ENTRY_SEED, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = zero_copy_entry.load()?.bump,
+        has_one = authority,
+    )]
+    pub zero_copy_entry: AccountLoader<'info, ZeroCopyEntry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct DeleteZeroCopyEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority,
+        realloc = 8 + UserProfile::INIT_SPACE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [ZERO_COPY_ENTRY_SEED, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = zero_copy_entry.load()?.bump,
+        has_one = authority,
+        close = authority,
+    )]
+    pub zero_copy_entry: AccountLoader<'info, ZeroCopyEntry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(title: String)]
+pub struct ReleaseTitleClaim<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [TITLE_CLAIM_SEED, authority.key().as_ref(), &text_seed(&title)],
+        bump = title_claim.bump,
+        has_one = authority,
+        has_one = journal_entry,
+    )]
+    pub title_claim: Account<'info, TitleClaim>,
+    /// CHECK: only inspected to see whether the claimed entry still holds this title
+    pub journal_entry: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct ReclaimDeposit<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    // The deposit goes back to whoever paid it
+    #[account(mut, address = journal_entry.rent_payer)]
+    pub rent_recipient: SystemAccount<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64, slug: String)]
+pub struct SetEntrySlug<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    // init_if_needed so a slug already in use surfaces as SlugTaken
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Slug::INIT_SPACE,
+        seeds = [SLUG_SEED, authority.key().as_ref(), &text_seed(&slug)],
+        bump
+    )]
+    pub slug_account: Account<'info, Slug>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(slug: String)]
+pub struct RemoveEntrySlug<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [SLUG_SEED, authority.key().as_ref(), &text_seed(&slug)],
+        bump = slug_account.bump,
+        has_one = authority,
+    )]
+    pub slug_account: Account<'info, Slug>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct MintEntryNft<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+        constraint = journal_entry.nft_mint.is_none() @ JournalError::NftAlreadyMinted,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [ENTRY_NFT_MINT_SEED, journal_entry.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = nft_authority,
+        mint::freeze_authority = nft_authority,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA signer used as mint authority until the master edition takes it over
+    #[account(seeds = [NFT_AUTHORITY_SEED], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = authority,
+        associated_token::token_program = token_program,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: created and validated by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref()],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: created and validated by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref(), b"edition"],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
-// Contexts for Instructions
-
 #[derive(Accounts)]
-pub struct InitializeUserProfile<'info> {
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct MintEntryCnft<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = UserProfile::LEN,
-        seeds = [USER_PROFILE_SEED, authority.key().as_ref()],
-        bump
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+        constraint = program_config.cnft_merkle_tree == Some(merkle_tree.key()) @ JournalError::CnftTreeNotConfigured,
     )]
-    pub user_profile: Account<'info, UserProfile>,
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = journal_entry.bump,
+        has_one = authority,
+        constraint = journal_entry.nft_mint.is_none() @ JournalError::NftAlreadyMinted,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    /// CHECK: Bubblegum tree config PDA, validated by Bubblegum
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        seeds::program = BUBBLEGUM_PROGRAM_ID,
+        bump,
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+    /// CHECK: must match the configured tree; validated by spl-account-compression
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: PDA signer set as the tree delegate
+    #[account(seeds = [NFT_AUTHORITY_SEED], bump)]
+    pub nft_authority: UncheckedAccount<'info>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    /// CHECK: address constrained
+    #[account(address = SPL_NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: address constrained
+    #[account(address = SPL_ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    /// CHECK: address constrained
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(title: String, message: String)] // Used for space calculation if not using fixed max lengths
-pub struct AddJournalEntry<'info> {
+pub struct CreateCompressedJournal<'info> {
     #[account(
-        mut,
-        seeds = [USER_PROFILE_SEED, authority.key().as_ref()],
-        bump = user_profile.bump,
-        has_one = authority, // Ensures the signer is the authority of the profile
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
     )]
-    pub user_profile: Account<'info, UserProfile>,
+    pub program_config: Account<'info, ProgramConfig>,
     #[account(
         init,
         payer = authority,
-        // Using max lengths for space calculation.
-        // For dynamic sizing based on input, it's more complex and often handled by pre-calculating on client.
-        // Anchor's `#[derive(InitSpace)]` helps if all fields are fixed size or have `max_len` attributes.
-        // Here, we will use a fixed size based on MAX_TITLE_LENGTH and MAX_MESSAGE_LENGTH
-        space = JournalEntry::space(MAX_TITLE_LENGTH, MAX_MESSAGE_LENGTH),
-        seeds = [JOURNAL_ENTRY_SEED, authority.key().as_ref(), &user_profile.entry_count.to_le_bytes()],
+        space = 8 + CompressedJournal::INIT_SPACE,
+        seeds = [COMPRESSED_JOURNAL_SEED, authority.key().as_ref()],
         bump
     )]
-    pub journal_entry: Account<'info, JournalEntry>,
+    pub compressed_journal: Account<'info, CompressedJournal>,
+    /// CHECK: allocated by the client and initialized by spl-account-compression
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    /// CHECK: address constrained
+    #[account(address = SPL_NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: address constrained
+    #[account(address = SPL_ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(entry_id: u64, title: String, message: String)]
-pub struct UpdateJournalEntry<'info> {
+pub struct AppendCompressedEntry<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
     #[account(
         mut,
-        seeds = [JOURNAL_ENTRY_SEED, authority.key().as_ref(), &entry_id.to_le_bytes()],
-        bump = journal_entry.bump,
-        has_one = authority, // Ensures the signer is the authority of the entry
+        seeds = [COMPRESSED_JOURNAL_SEED, authority.key().as_ref()],
+        bump = compressed_journal.bump,
+        has_one = authority,
+        has_one = merkle_tree,
     )]
-    pub journal_entry: Account<'info, JournalEntry>,
+    pub compressed_journal: Account<'info, CompressedJournal>,
+    /// CHECK: matched against compressed_journal; validated by spl-account-compression
     #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
     pub authority: Signer<'info>,
+    /// CHECK: address constrained
+    #[account(address = SPL_NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: address constrained
+    #[account(address = SPL_ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(entry_id: u64)]
-pub struct DeleteJournalEntry<'info> {
+pub struct ModifyCompressedEntry<'info> {
     #[account(
-        mut,
-        seeds = [JOURNAL_ENTRY_SEED, authority.key().as_ref(), &entry_id.to_le_bytes()],
-        bump = journal_entry.bump,
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        seeds = [COMPRESSED_JOURNAL_SEED, authority.key().as_ref()],
+        bump = compressed_journal.bump,
         has_one = authority,
-        close = authority, // Lamports from closed account are returned to the authority
+        has_one = merkle_tree,
     )]
-    pub journal_entry: Account<'info, JournalEntry>,
+    pub compressed_journal: Account<'info, CompressedJournal>,
+    /// CHECK: matched against compressed_journal; validated by spl-account-compression
     #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
     pub authority: Signer<'info>,
-    // UserProfile is not modified here for simplicity, but could be if entry_count needs adjustment
-    // #[account(
-    //     mut,
-    //     seeds = [USER_PROFILE_SEED, authority.key().as_ref()],
-    //     bump = user_profile.bump,
-    //     has_one = authority
-    // )]
-    // pub user_profile: Account<'info, UserProfile>,
-}
-
-
-// Error Enum
-#[error_code]
-pub enum JournalError {
-    #[msg("Title is too long.")]
-    TitleTooLong,
-    #[msg("Message is too long.")]
-    MessageTooLong,
-    #[msg("Overflow occurred.")]
-    Overflow,
-}
-
-Considerations for JournalEntry::space and #[derive(InitSpace)]:
-The InitSpace derive macro is very helpful. To use it effectively with Strings, you'd typically add #[max_len(N)] attributes to the string fields within the struct definition.
-
-Rust
-
-#[account]
-#[derive(InitSpace)] // Add this
-pub struct JournalEntry {
-    pub authority: Pubkey,
-    pub id: u64,
-    #[max_len(MAX_TITLE_LENGTH as usize)] // usize needed for max_len
-    pub title: String,
-    #[max_len(MAX_MESSAGE_LENGTH as usize)] // usize needed for max_len
-    pub message: String,
-    pub timestamp: i64,
-    pub bump: u8,
-}
-Then, in AddJournalEntry, the space would be 8 + JournalEntry::INIT_SPACE.
-
-Let's adjust JournalEntry to use InitSpace.
-The MAX_TITLE_LENGTH and MAX_MESSAGE_LENGTH should represent the number of characters, not bytes including the 4-byte prefix. Anchor's #[max_len] handles the 4 + chars internally for space calculation.
-
-Rust
-
-// anchor/programs/journal_program/src/lib.rs
-
-use anchor_lang::prelude::*;
-use anchor_lang::solana_program::clock::Clock;
-
-// IMPORTANT: Replace this with your program's actual ID after deploying/building
-declare_id!("JRNA1S7xcX6P9sS5a95hTSGmD3Yk8z123456789ABC"); 
-
-// Constants for PDA seeds
-const USER_PROFILE_SEED_PREFIX: &[u8] = b"user_profile";
-const JOURNAL_ENTRY_SEED_PREFIX: &[u8] = b"journal_entry";
-
-// Constants for string lengths (characters, not including 4-byte length prefix)
-const MAX_TITLE_CHARS: usize = 50; 
-const MAX_MESSAGE_CHARS: usize = 280; // Like a tweet
-
-#[program]
-pub mod journal_program {
-    use super::*;
-
-    pub fn initialize_user_profile(ctx: Context<InitializeUserProfile>) -> Result<()> {
-        let user_profile = &mut ctx.accounts.user_profile;
-        user_profile.authority = ctx.accounts.authority.key();
-        user_profile.entry_count = 0;
-        user_profile.bump = ctx.bumps.user_profile;
-        msg!("User profile initialized for {}", ctx.accounts.authority.key());
-        Ok(())
-    }
-
-    pub fn add_journal_entry(ctx: Context<AddJournalEntry>, title: String, message: String) -> Result<()> {
-        if title.chars().count() > MAX_TITLE_CHARS {
-            return err!(JournalError::TitleTooLong);
-        }
-        if message.chars().count() > MAX_MESSAGE_CHARS {
-            return err!(JournalError::MessageTooLong);
-        }
-
-        let user_profile = &mut ctx.accounts.user_profile;
-        let journal_entry = &mut ctx.accounts.journal_entry;
-        let authority = &ctx.accounts.authority;
-        let clock = Clock::get()?;
-
-        journal_entry.authority = authority.key();
-        journal_entry.title = title;
-        journal_entry.message = message;
-        journal_entry.timestamp = clock.unix_timestamp;
-        journal_entry.id = user_profile.entry_count; // Use current count as ID for this new entry
-        journal_entry.bump = ctx.bumps.journal_entry;
-
-        // Increment entry count for the next entry
-        user_profile.entry_count = user_profile.entry_count.checked_add(1).ok_or(JournalError::Overflow)?;
-        
-        msg!("Journal entry {} added for user {}", journal_entry.id, authority.key());
-        Ok(())
-    }
-
-    pub fn update_journal_entry(ctx: Context<UpdateJournalEntry>, _entry_id: u64, title: String, message: String) -> Result<()> {
-        if title.chars().count() > MAX_TITLE_CHARS {
-            return err!(JournalError::TitleTooLong);
-        }
-        if message.chars().count() > MAX_MESSAGE_CHARS {
-            return err!(JournalError::MessageTooLong);
-        }
-
-        let journal_entry = &mut ctx.accounts.journal_entry;
-        let clock = Clock::get()?;
-        
-        journal_entry.title = title;
-        journal_entry.message = message;
-        journal_entry.timestamp = clock.unix_timestamp; // Update timestamp on modification
-
-        msg!("Journal entry {} updated for user {}", journal_entry.id, ctx.accounts.authority.key());
-        Ok(())
-    }
-
-    pub fn delete_journal_entry(ctx: Context<DeleteJournalEntry>, _entry_id: u64) -> Result<()> {
-        msg!("Journal entry {} with ID {} deleted for user {}", 
-             ctx.accounts.journal_entry.key(), 
-             ctx.accounts.journal_entry.id, 
-             ctx.accounts.authority.key());
-        // Account is closed by Anchor due to `close = authority` in `DeleteJournalEntry`
-        // Note: This leaves a "gap" in entry_ids if user_profile.entry_count is not managed.
-        // For frontend retrieval, one would iterate from 0 to user_profile.entry_count -1
-        // and attempt to fetch each. If an account is not found, it's considered deleted or never existed.
-        Ok(())
-    }
-}
-
-// Account Structs
-#[account]
-#[derive(InitSpace)] // Automatically calculates space based on fields
-pub struct UserProfile {
-    pub authority: Pubkey,
-    pub entry_count: u64, // Stores the number of entries created by this user, also used as next entry_id
-    pub bump: u8,
-}
-
-
-#[account]
-#[derive(InitSpace)]
-pub struct JournalEntry {
-    pub authority: Pubkey,    // User who owns the entry
-    pub id: u64,              // ID of the entry, specific to the user (0, 1, 2, ...)
-    #[max_len(MAX_TITLE_CHARS)]
-    pub title: String,
-    #[max_len(MAX_MESSAGE_CHARS)]
-    pub message: String,
-    pub timestamp: i64,
-    pub bump: u8,
+    /// CHECK: address constrained
+    #[account(address = SPL_NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: address constrained
+    #[account(address = SPL_ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
 }
 
-// Contexts for Instructions
 #[derive(Accounts)]
-pub struct InitializeUserProfile<'info> {
+pub struct TipEntry<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + UserProfile::INIT_SPACE, // 8 bytes for discriminator
-        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
-        bump
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
     )]
-    pub user_profile: Account<'info, UserProfile>,
+    pub program_config: Account<'info, ProgramConfig>,
+    // Seeds are re-derived from the entry itself since the tipper is not its authority.
+    #[account(
+        mut,
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, journal_entry.authority.as_ref(), &journal_entry.id.to_le_bytes()],
+        bump = journal_entry.bump,
+    )]
+    pub journal_entry: Account<'info, JournalEntry>,
+    #[account(
+        mut,
+        seeds = [TIP_JAR_SEED_PREFIX, journal_entry.key().as_ref()],
+        bump,
+    )]
+    pub tip_jar: SystemAccount<'info>,
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub tipper: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-// instruction macro not strictly needed here for space if using InitSpace on JournalEntry
-// but can be kept for clarity or if args are used in seed paths directly in `#[account(...)]`
-// #[instruction(title: String, message: String)] 
-pub struct AddJournalEntry<'info> {
+#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
+pub struct WithdrawTips<'info> {
     #[account(
-        mut,
-        seeds = [USER_PROFILE_SEED_PREFIX, authority.key().as_ref()],
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    /// CHECK: the key the entry was written under, which its address derives from. The same as `authority` unless
+    /// the profile has since been recovered to a new wallet, which then withdraws the old entries' tips.
+    pub entry_authority: UncheckedAccount<'info>,
+    #[account(
+        seeds = [USER_PROFILE_SEED_PREFIX, entry_authority.key().as_ref()],
         bump = user_profile.bump,
         has_one = authority,
     )]
     pub user_profile: Account<'info, UserProfile>,
+    /// CHECK: only its address is used, so tips can still be withdrawn once the entry is deleted or migrated
     #[account(
-        init,
-        payer = authority,
-        space = 8 + JournalEntry::INIT_SPACE, // 8 bytes for discriminator
-        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &user_profile.entry_count.to_le_bytes()],
-        bump
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, entry_authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump,
     )]
-    pub journal_entry: Account<'info, JournalEntry>,
+    pub journal_entry: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [TIP_JAR_SEED_PREFIX, journal_entry.key().as_ref()],
+        bump,
+    )]
+    pub tip_jar: SystemAccount<'info>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(entry_id: u64)] // entry_id is used in seeds constraint
-pub struct UpdateJournalEntry<'info> {
-    // We need user_profile to check authority if needed, or just ensure journal_entry.authority matches signer.
-    // For simplicity, keeping has_one = authority on journal_entry is sufficient.
+pub struct TipEntrySpl<'info> {
     #[account(
-        mut,
-        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, journal_entry.authority.as_ref(), &journal_entry.id.to_le_bytes()],
         bump = journal_entry.bump,
-        has_one = authority, // This checks journal_entry.authority == authority.key()
     )]
     pub journal_entry: Account<'info, JournalEntry>,
+    /// CHECK: PDA that owns the per-entry token accounts; it never holds data
+    #[account(
+        seeds = [TIP_JAR_SEED_PREFIX, journal_entry.key().as_ref()],
+        bump,
+    )]
+    pub tip_jar: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = tipper,
+        associated_token::mint = mint,
+        associated_token::authority = tip_jar,
+        associated_token::token_program = token_program,
+    )]
+    pub tip_jar_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = tipper,
+        space = 8 + TokenTipTally::INIT_SPACE,
+        seeds = [TOKEN_TIP_TALLY_SEED_PREFIX, journal_entry.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub token_tip_tally: Account<'info, TokenTipTally>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = tipper,
+        token::token_program = token_program,
+    )]
+    pub tipper_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub tipper: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 #[instruction(entry_id: u64)] // entry_id is used in seeds constraint
-pub struct DeleteJournalEntry<'info> {
+pub struct WithdrawTipsSpl<'info> {
     #[account(
-        mut,
-        seeds = [JOURNAL_ENTRY_SEED_PREFIX, authority.key().as_ref(), &entry_id.to_le_bytes()],
-        bump = journal_entry.bump,
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ JournalError::ProgramPaused,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    /// CHECK: the key the entry was written under; see WithdrawTips
+    pub entry_authority: UncheckedAccount<'info>,
+    #[account(
+        seeds = [USER_PROFILE_SEED_PREFIX, entry_authority.key().as_ref()],
+        bump = user_profile.bump,
         has_one = authority,
-        close = authority, 
     )]
-    pub journal_entry: Account<'info, JournalEntry>,
+    pub user_profile: Account<'info, UserProfile>,
+    /// CHECK: only its address is used; see WithdrawTips
+    #[account(
+        seeds = [JOURNAL_ENTRY_SEED_PREFIX, entry_authority.key().as_ref(), &entry_id.to_le_bytes()],
+        bump,
+    )]
+    pub journal_entry: UncheckedAccount<'info>,
+    /// CHECK: PDA signer for the tip jar token accounts
+    #[account(
+        seeds = [TIP_JAR_SEED_PREFIX, journal_entry.key().as_ref()],
+        bump,
+    )]
+    pub tip_jar: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = tip_jar,
+        associated_token::token_program = token_program,
+    )]
+    pub tip_jar_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = authority,
+        token::token_program = token_program,
+    )]
+    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Events
+// Creation/deletion events carry the lamports involved so an indexer can chart what users spend and reclaim
+#[event]
+pub struct EntryCreated {
+    pub authority: Pubkey,
+    pub entry: Pubkey,
+    pub id: u64,
+    pub payer: Pubkey,
+    pub rent_lamports: u64,
+    pub protocol_fee_lamports: u64,
+    pub deposit_lamports: u64,
+    pub priority_fee_estimate_lamports: u64, // 0 unless the instructions sysvar is passed as a remaining account
+}
+
+// Emitted for direct, timelocked and zero-copy updates alike
+#[event]
+pub struct EntryUpdated {
+    pub authority: Pubkey,
+    pub entry: Pubkey,
+    pub id: u64,
+    pub chars_written: u64, // Title plus message, as counted towards the profile's total_chars_written
+}
+
+#[event]
+pub struct EntryDeleted {
+    pub authority: Pubkey,
+    pub entry: Pubkey,
+    pub id: u64,
+    pub refund_recipient: Pubkey,
+    pub refunded_lamports: u64, // Rent plus any deposit still held by the entry
+}
+
+#[event]
+pub struct InstructionDeprecated {
+    pub notice: DeprecationNotice,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub admin: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EntryHistoryAppended {
+    pub authority: Pubkey,
+    pub leaf_index: u64,
+    pub leaf: EntryHistoryLeaf,
+    pub root: [u8; 32],
+}
+
+#[event]
+pub struct CompressedEntryWritten {
+    pub merkle_tree: Pubkey,
+    pub leaf_hash: [u8; 32],
+    pub leaf: CompressedEntryLeaf,
 }
 
 // Error Enum
@@ -427,4 +5158,242 @@ pub enum JournalError {
     MessageTooLong,
     #[msg("An overflow occurred.")]
     Overflow,
-}
\ No newline at end of file
+    #[msg("Tip amount must be greater than zero.")]
+    InvalidTipAmount,
+    #[msg("There are no tips to withdraw.")]
+    NoTipsToWithdraw,
+    #[msg("Signer is not authorized to perform this action.")]
+    Unauthorized,
+    #[msg("Treasury balance is insufficient for this withdrawal.")]
+    InsufficientTreasuryBalance,
+    #[msg("The program is paused.")]
+    ProgramPaused,
+    #[msg("Daily entry limit reached.")]
+    DailyLimitReached,
+    #[msg("There is no deposit to reclaim.")]
+    NoDepositToReclaim,
+    #[msg("The deposit is still locked.")]
+    DepositLocked,
+    #[msg("Expected an ed25519 signature verification instruction before this one.")]
+    MissingSignatureVerification,
+    #[msg("The ed25519 signature verification does not match this entry.")]
+    InvalidSignatureVerification,
+    #[msg("Relayed write nonce does not match the profile's write_nonce.")]
+    InvalidWriteNonce,
+    #[msg("The session key has expired.")]
+    SessionExpired,
+    #[msg("The delegate does not hold the permission this instruction requires.")]
+    PermissionDenied,
+    #[msg("This entry is timelocked; use propose_update and execute_update.")]
+    UpdateTimelocked,
+    #[msg("This entry does not require timelocked updates.")]
+    UpdateNotTimelocked,
+    #[msg("The update delay can only be increased.")]
+    UpdateDelayDecrease,
+    #[msg("The proposed update is still within its delay.")]
+    UpdateStillLocked,
+    #[msg("The writer does not hold enough of the journal's gate token.")]
+    WriteGateNotMet,
+    #[msg("NFT symbol or URI exceeds the Metaplex length limit.")]
+    NftMetadataTooLong,
+    #[msg("Permissions contain unknown bits.")]
+    InvalidPermissions,
+    #[msg("This entry has already been minted as an NFT.")]
+    NftAlreadyMinted,
+    #[msg("The merkle tree is not the one configured for compressed NFTs.")]
+    CnftTreeNotConfigured,
+    #[msg("Display name, bio or avatar URI exceeds its maximum length.")]
+    ProfileMetadataTooLong,
+    #[msg("Handles must be 3-32 lowercase letters, digits or underscores.")]
+    InvalidHandle,
+    #[msg("This handle is already claimed.")]
+    HandleTaken,
+    #[msg("Slugs must be 1-64 lowercase letters, digits or hyphens.")]
+    InvalidSlug,
+    #[msg("This slug is already used by another of your entries.")]
+    SlugTaken,
+    #[msg("This profile requires unique titles; pass the title claim account.")]
+    TitleClaimRequired,
+    #[msg("Another entry already uses this title.")]
+    DuplicateTitle,
+    #[msg("Titles can't be changed while unique titles are enabled.")]
+    TitleLocked,
+    #[msg("The claimed entry still uses this title.")]
+    TitleStillInUse,
+    #[msg("Replies can only be posted to public entries.")]
+    ReplyParentNotPublic,
+    #[msg("Comment exceeds maximum character limit.")]
+    CommentTooLong,
+    #[msg("This entry is not public.")]
+    EntryNotPublic,
+    #[msg("Unknown emoji code.")]
+    InvalidEmojiCode,
+    #[msg("You have already reacted to this entry.")]
+    AlreadyReacted,
+    #[msg("Journal names must be 1-32 characters.")]
+    InvalidJournalName,
+    #[msg("Journal description exceeds maximum character limit.")]
+    JournalDescriptionTooLong,
+    #[msg("Publishing an entry requires the feed head account.")]
+    FeedHeadRequired,
+    #[msg("This entry is already pinned.")]
+    AlreadyPinned,
+    #[msg("The profile already has the maximum number of pinned entries.")]
+    TooManyPinnedEntries,
+    #[msg("This entry is not pinned.")]
+    NotPinned,
+    #[msg("Migration expects (old entry, new entry) account pairs in order.")]
+    InvalidMigrationAccounts,
+    #[msg("Sponsored entries can't be migrated.")]
+    SponsoredEntryMigration,
+    #[msg("The recovery delay is shorter than the minimum challenge period.")]
+    RecoveryDelayTooShort,
+    #[msg("There is no recovery in progress.")]
+    NoRecoveryPending,
+    #[msg("The recovery is still within its challenge period.")]
+    RecoveryStillLocked,
+    #[msg("Prompts must be 1-140 characters.")]
+    InvalidPrompt,
+    #[msg("The prompt list is full.")]
+    TooManyPrompts,
+    #[msg("Today's prompt has already been drawn.")]
+    PromptAlreadyDrawn,
+    #[msg("A prompt draw is already committed and waiting to be revealed.")]
+    PromptDrawPending,
+    #[msg("The randomness account is not valid for this draw.")]
+    InvalidRandomness,
+    #[msg("The randomness has not been revealed in this slot.")]
+    RandomnessNotRevealed,
+    #[msg("The daily prompt is not from today.")]
+    PromptExpired,
+    #[msg("The price account is not a fully verified SOL/USD Pyth price update.")]
+    InvalidPriceUpdate,
+    #[msg("The Pyth price is too old.")]
+    StalePrice,
+    #[msg("Latitude or longitude is out of range.")]
+    InvalidLocation,
+    #[msg("Locale must be a zero-padded ASCII language tag, or the locale family is unknown.")]
+    InvalidLocale,
+    #[msg("Compressed entry index is out of range.")]
+    InvalidCompressedEntryIndex,
+    #[msg("The profile's entry history tree is full.")]
+    EntryHistoryFull,
+    #[msg("Only the entry's designated reader can mark it read.")]
+    NotEntryReader,
+    #[msg("The signer and salt don't match the pseudonym's commitment.")]
+    InvalidPseudonymProof,
+    #[msg("Too many programs in the CPI allowlist.")]
+    TooManyCpiCallers,
+    #[msg("The calling program is not allowed to invoke this instruction through CPI.")]
+    CpiCallerNotAllowed,
+    #[msg("The account already uses the current layout.")]
+    AccountAlreadyCurrent,
+    #[msg("Entry text is not valid UTF-8.")]
+    InvalidUtf8,
+    #[msg("The message patch starts past the end of the message or overflows it.")]
+    MessagePatchOutOfRange,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An entry exactly as the program wrote it before versioning
+    fn unversioned_entry(authority: &Pubkey) -> Vec<u8> {
+        let mut data = JournalEntry::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(authority.as_ref());
+        data.extend_from_slice(&7u64.to_le_bytes()); // id
+        for text in ["Day one", "Hello"] {
+            data.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            data.extend_from_slice(text.as_bytes());
+        }
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes()); // timestamp
+        data.extend_from_slice(&25u64.to_le_bytes()); // tips_received
+        data.extend_from_slice(&[0; 16]); // deposit_lamports, deposit_unlock_at
+        data.extend_from_slice(authority.as_ref()); // rent_payer
+        data.extend_from_slice(&[0; 8]); // update_delay_seconds
+        data.extend_from_slice(&[0, 1, 0]); // nft_mint, is_public, reply_to
+        data.extend_from_slice(&[0; 16]); // reply_count, comment_count
+        data.push(0); // journal
+        data.extend_from_slice(&3u32.to_le_bytes()); // sort_order
+        data.extend_from_slice(&[0; 6]); // prompt_id, price_snapshot, location, weather_code, reader, flagged
+        data.extend_from_slice(&2u32.to_le_bytes()); // report_count
+        data.extend_from_slice(b"ja\0\0\0"); // locale
+        data.push(254); // bump
+        data.resize(8 + legacy::JournalEntryV0::INIT_SPACE, 0);
+        data
+    }
+
+    #[test]
+    fn reads_unversioned_entries() {
+        let authority = Pubkey::new_unique();
+        let data = unversioned_entry(&authority);
+        let entry = JournalEntry::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!((entry.authority, entry.id, entry.version), (authority, 7, JOURNAL_ENTRY_VERSION));
+        assert_eq!((entry.title.as_str(), entry.message.as_str()), ("Day one", "Hello"));
+        assert_eq!((entry.timestamp, entry.tips_received, entry.rent_payer), (1_700_000_000, 25, authority));
+        assert!(entry.is_public && !entry.flagged);
+        assert_eq!((entry.sort_order, entry.report_count, entry.locale, entry.bump), (3, 2, *b"ja\0\0\0", 254));
+
+        let mut upgraded = Vec::new();
+        entry.try_serialize(&mut upgraded).unwrap();
+        assert_eq!(&upgraded[ENTRY_AUTHORITY_OFFSET..ENTRY_ID_OFFSET], authority.as_ref());
+        assert_eq!(upgraded[ENTRY_ID_OFFSET..ENTRY_ID_OFFSET + 8], 7u64.to_le_bytes());
+        assert_eq!(upgraded[ENTRY_ID_OFFSET + 8], JOURNAL_ENTRY_VERSION);
+        upgraded.resize(8 + JournalEntry::INIT_SPACE, 0);
+        assert!(legacy::read_journal_entry(&upgraded[8..]).is_none());
+    }
+
+    // Reads the contexts from this file: an instruction only refuses to run while paused if its context checks
+    #[test]
+    fn every_mutating_instruction_checks_the_pause_switch() {
+        // The admin's own instructions, which must keep working to unpause
+        const ADMIN_CONTEXTS: [&str; 6] =
+            ["InitializeConfig", "UpdateConfig", "AcceptAdmin", "InitializeFeedHead", "AddPrompt", "WithdrawTreasury"];
+        let source = include_str!("lib.rs");
+        let program = &source[source.find("pub mod journal_program").unwrap()..];
+        let program = &program[..program.find("\n}\n").unwrap()];
+
+        let mut contexts: Vec<&str> = program
+            .split("ctx: Context<")
+            .skip(1)
+            .map(|rest| {
+                // "UpdateConfig>", or "'_, '_, 'info, 'info, MigrateEntry<'info>>" with explicit lifetimes
+                let generics = rest.split(['>', ')']).next().unwrap();
+                generics.rsplit(", ").next().unwrap().split('<').next().unwrap()
+            })
+            .collect();
+        contexts.sort_unstable();
+        contexts.dedup();
+        assert!(contexts.len() > 50, "found only {contexts:?}");
+
+        let ungated: Vec<&str> = contexts
+            .into_iter()
+            .filter(|context| !ADMIN_CONTEXTS.contains(context))
+            .filter(|context| {
+                let start = source.find(&format!("pub struct {context}<'info> {{")).expect(context);
+                let body = &source[start..start + source[start..].find("\n}\n").unwrap()];
+                !body.contains("constraint = !program_config.paused @ JournalError::ProgramPaused")
+                    && !body.contains("Deliberately not gated by the pause switch")
+            })
+            .collect();
+        assert!(ungated.is_empty(), "no pause check, and no comment saying why: {ungated:?}");
+    }
+
+    #[test]
+    fn profiles_keep_the_authority_where_it_was() {
+        // Authority and entry_count in front of an otherwise empty unversioned profile
+        let authority = Pubkey::new_unique();
+        let mut body = authority.to_bytes().to_vec();
+        body.extend_from_slice(&12u64.to_le_bytes());
+        body.resize(legacy::UserProfileV0::INIT_SPACE, 0);
+        let profile = legacy::read_user_profile(&body).unwrap().unwrap();
+
+        let mut upgraded = Vec::new();
+        profile.try_serialize(&mut upgraded).unwrap();
+        assert_eq!(&upgraded[..8], UserProfile::DISCRIMINATOR);
+        assert_eq!(upgraded[8..48], body[..40]);
+        assert_eq!(upgraded[48], USER_PROFILE_VERSION);
+        upgraded.resize(8 + UserProfile::INIT_SPACE, 0);
+        assert!(legacy::read_user_profile(&upgraded[8..]).is_none());
+    }
+}
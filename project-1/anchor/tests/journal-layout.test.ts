@@ -0,0 +1,91 @@
+import * as anchor from '@coral-xyz/anchor'
+import { BN, Program } from '@coral-xyz/anchor'
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js'
+import { JournalProgram } from '../target/types/journal'
+import { ensureProgramConfig, findJournalEntry, findUserProfile } from './journal-setup'
+
+// Indexers filter entries with getProgramAccounts memcmp on these offsets (ENTRY_AUTHORITY_OFFSET and
+// ENTRY_ID_OFFSET in the program). Changing them is a breaking change for every indexer.
+const AUTHORITY_OFFSET = 8
+const ID_OFFSET = 40
+
+describe('journal account layout', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+
+  const program = anchor.workspace.Journal as Program<JournalProgram>
+
+  const author = Keypair.generate()
+  const userProfile = findUserProfile(program.programId, author.publicKey)
+  const journalEntry = findJournalEntry(program.programId, author.publicKey, 0)
+  const [zeroCopyEntry] = PublicKey.findProgramAddressSync(
+    [Buffer.from('zero_copy_entry'), author.publicKey.toBuffer(), new BN(1).toArrayLike(Buffer, 'le', 8)],
+    program.programId,
+  )
+
+  function leadingFields(name: string): string[] {
+    const type = program.idl.types!.find((t) => t.name === name)!.type as { fields: { name: string }[] }
+    return type.fields.slice(0, 3).map((field) => field.name)
+  }
+
+  async function expectOwnerAndId(address: PublicKey, id: number) {
+    const data = (await provider.connection.getAccountInfo(address))!.data
+    expect(new PublicKey(data.subarray(AUTHORITY_OFFSET, AUTHORITY_OFFSET + 32)).toBase58()).toEqual(
+      author.publicKey.toBase58(),
+    )
+    expect(data.readBigUInt64LE(ID_OFFSET)).toEqual(BigInt(id))
+  }
+
+  beforeAll(async () => {
+    await ensureProgramConfig(program, provider)
+    const signature = await provider.connection.requestAirdrop(author.publicKey, LAMPORTS_PER_SOL)
+    await provider.connection.confirmTransaction(signature)
+    await program.methods
+      .initializeUserProfile()
+      .accountsPartial({ userProfile, authority: author.publicKey, payer: author.publicKey })
+      .signers([author])
+      .rpc()
+  })
+
+  it('Keeps the fixed-size fields ahead of the version', () => {
+    expect(leadingFields('journalEntry')).toEqual(['authority', 'id', 'version'])
+    expect(leadingFields('userProfile')).toEqual(['authority', 'entryCount', 'version'])
+    expect(leadingFields('zeroCopyEntry')).toEqual(['authority', 'id', 'timestamp'])
+  })
+
+  it('Stores authority and id at the documented offsets', async () => {
+    await program.methods
+      .addJournalEntry('Layout', 'Offsets are part of the API')
+      .accountsPartial({
+        userProfile,
+        journalEntry,
+        authority: author.publicKey,
+        signer: author.publicKey,
+        sessionKey: null,
+        gateTokenAccount: null,
+        titleClaim: null,
+        priceUpdate: null,
+        dailyPrompt: null,
+        journal: null,
+        feedHead: null,
+        replyParent: null,
+        memoProgram: null,
+        payer: author.publicKey,
+      })
+      .signers([author])
+      .rpc()
+    await program.methods
+      .addZeroCopyEntry('Layout', 'Offsets are part of the API')
+      .accountsPartial({ userProfile, zeroCopyEntry, authority: author.publicKey })
+      .signers([author])
+      .rpc()
+
+    await expectOwnerAndId(journalEntry, 0)
+    await expectOwnerAndId(zeroCopyEntry, 1)
+
+    const matches = await program.account.journalEntry.all([
+      { memcmp: { offset: AUTHORITY_OFFSET, bytes: author.publicKey.toBase58() } },
+    ])
+    expect(matches.map((match) => match.publicKey.toBase58())).toEqual([journalEntry.toBase58()])
+  })
+})
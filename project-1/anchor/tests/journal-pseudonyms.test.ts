@@ -0,0 +1,52 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { Keypair, PublicKey } from '@solana/web3.js'
+import { createHash, randomBytes } from 'crypto'
+import { JournalProgram } from '../target/types/journal'
+import { ensureProgramConfig } from './journal-setup'
+
+describe('journal pseudonyms', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const owner = provider.wallet as anchor.Wallet
+
+  const program = anchor.workspace.Journal as Program<JournalProgram>
+
+  const pseudonymKey = Keypair.generate()
+  const [pseudonym] = PublicKey.findProgramAddressSync(
+    [Buffer.from('pseudonym'), pseudonymKey.publicKey.toBuffer()],
+    program.programId,
+  )
+  const salt = randomBytes(32)
+
+  function reveal(salt: Buffer) {
+    return program.methods
+      .revealPseudonym(Array.from(salt))
+      .accountsPartial({ pseudonym, owner: owner.publicKey })
+      .rpc()
+  }
+
+  beforeAll(async () => {
+    await ensureProgramConfig(program, provider)
+    // hash(owner || salt), as the program recomputes it on reveal
+    const commitment = createHash('sha256').update(owner.publicKey.toBuffer()).update(salt).digest()
+    await program.methods
+      .createPseudonym(Array.from(commitment))
+      .accountsPartial({ pseudonym, authority: pseudonymKey.publicKey, payer: owner.publicKey })
+      .signers([pseudonymKey])
+      .rpc()
+  })
+
+  it('Rejects a reveal with the wrong salt', async () => {
+    await expect(reveal(randomBytes(32))).rejects.toThrow(/InvalidPseudonymProof/)
+    const account = await program.account.pseudonym.fetch(pseudonym)
+    expect(account.revealedOwner).toBeNull()
+  })
+
+  it('Reveals the owner with the salt behind the commitment', async () => {
+    await reveal(salt)
+    const account = await program.account.pseudonym.fetch(pseudonym)
+    expect(account.authority.equals(pseudonymKey.publicKey)).toBe(true)
+    expect(account.revealedOwner?.equals(owner.publicKey)).toBe(true)
+  })
+})
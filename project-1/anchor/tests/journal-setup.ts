@@ -0,0 +1,51 @@
+import * as anchor from '@coral-xyz/anchor'
+import { BN, Program } from '@coral-xyz/anchor'
+import { PublicKey } from '@solana/web3.js'
+import { JournalProgram } from '../target/types/journal'
+
+// Shared PDA helpers and one-time setup for the journal test files.
+
+export function findProgramConfig(programId: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('program_config')], programId)[0]
+}
+
+export function findTreasury(programId: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('treasury')], programId)[0]
+}
+
+export function findUserProfile(programId: PublicKey, authority: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('user_profile'), authority.toBuffer()], programId)[0]
+}
+
+export function findJournalEntry(programId: PublicKey, authority: PublicKey, id: number): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('journal_entry'), authority.toBuffer(), new BN(id).toArrayLike(Buffer, 'le', 8)],
+    programId,
+  )[0]
+}
+
+// The config can only be created once per deployment (by the upgrade authority), so every test file
+// calls this instead of initializing it directly. Fees, limits and deposits are all disabled.
+export async function ensureProgramConfig(program: Program<JournalProgram>, provider: anchor.AnchorProvider) {
+  const programConfig = findProgramConfig(program.programId)
+  if (await program.account.programConfig.fetchNullable(programConfig)) {
+    return
+  }
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey('BPFLoaderUpgradeab1e11111111111111111111111'),
+  )
+  await program.methods
+    .initializeConfig(new BN(0), 0, new BN(0), new BN(0))
+    .accounts({ programData, admin: provider.wallet.publicKey })
+    .rpc()
+}
+
+// Publishing an entry pushes it onto the FeedHead, which the admin creates once per deployment
+export async function ensureFeedHead(program: Program<JournalProgram>, provider: anchor.AnchorProvider) {
+  const [feedHead] = PublicKey.findProgramAddressSync([Buffer.from('feed_head')], program.programId)
+  if (await program.account.feedHead.fetchNullable(feedHead)) {
+    return
+  }
+  await program.methods.initializeFeedHead().accounts({ admin: provider.wallet.publicKey }).rpc()
+}
@@ -0,0 +1,159 @@
+import * as anchor from '@coral-xyz/anchor'
+import { BN, Program } from '@coral-xyz/anchor'
+import {
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  mintTo,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+} from '@solana/spl-token'
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js'
+import { JournalProgram } from '../target/types/journal'
+import { ensureProgramConfig, findJournalEntry, findUserProfile } from './journal-setup'
+
+describe('journal spl tips', () => {
+  // Configure the client to use the local cluster.
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const author = provider.wallet as anchor.Wallet
+
+  const program = anchor.workspace.Journal as Program<JournalProgram>
+
+  const userProfile = findUserProfile(program.programId, author.publicKey)
+  const journalEntry = findJournalEntry(program.programId, author.publicKey, 0)
+  const [tipJar] = PublicKey.findProgramAddressSync(
+    [Buffer.from('tip_jar'), journalEntry.toBuffer()],
+    program.programId,
+  )
+
+  const tipper = Keypair.generate()
+
+  beforeAll(async () => {
+    await ensureProgramConfig(program, provider)
+    await program.methods
+      .initializeUserProfile()
+      .accountsPartial({ userProfile, authority: author.publicKey, payer: author.publicKey })
+      .rpc()
+    await program.methods
+      .addJournalEntry('Tips', 'Tip me in any token')
+      .accountsPartial({
+        userProfile,
+        journalEntry,
+        authority: author.publicKey,
+        signer: author.publicKey,
+        sessionKey: null,
+        gateTokenAccount: null,
+        titleClaim: null,
+        priceUpdate: null,
+        dailyPrompt: null,
+        journal: null,
+        feedHead: null,
+        replyParent: null,
+        memoProgram: null,
+        payer: author.publicKey,
+      })
+      .rpc()
+
+    const signature = await provider.connection.requestAirdrop(tipper.publicKey, LAMPORTS_PER_SOL)
+    await provider.connection.confirmTransaction(signature)
+  })
+
+  // A mint with `tipper` holding 1 token, and an empty token account for the author
+  async function fundTipper(tokenProgram: PublicKey) {
+    const mint = await createMint(provider.connection, author.payer, author.publicKey, null, 6, undefined, undefined, tokenProgram)
+    const tipperTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      author.payer,
+      mint,
+      tipper.publicKey,
+      undefined,
+      tokenProgram,
+    )
+    const authorTokenAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      author.payer,
+      mint,
+      author.publicKey,
+      undefined,
+      tokenProgram,
+    )
+    await mintTo(provider.connection, author.payer, mint, tipperTokenAccount, author.payer, 1_000_000, [], undefined, tokenProgram)
+    return { mint, tipperTokenAccount, authorTokenAccount }
+  }
+
+  async function tip(mint: PublicKey, tipperTokenAccount: PublicKey, tokenProgram: PublicKey) {
+    await program.methods
+      .tipEntrySpl(new BN(250_000))
+      .accounts({
+        journalEntry,
+        mint,
+        tipperTokenAccount,
+        tipper: tipper.publicKey,
+        tokenProgram,
+      })
+      .signers([tipper])
+      .rpc()
+  }
+
+  async function withdraw(mint: PublicKey, authorTokenAccount: PublicKey, tokenProgram: PublicKey) {
+    await program.methods
+      .withdrawTipsSpl(new BN(0))
+      .accounts({
+        entryAuthority: author.publicKey,
+        mint,
+        authorityTokenAccount: authorTokenAccount,
+        authority: author.publicKey,
+        tokenProgram,
+      })
+      .rpc()
+  }
+
+  it.each([
+    ['token', TOKEN_PROGRAM_ID],
+    ['token-2022', TOKEN_2022_PROGRAM_ID],
+  ])('Tips and withdraws with the %s program', async (_name, tokenProgram) => {
+    const { mint, tipperTokenAccount, authorTokenAccount } = await fundTipper(tokenProgram)
+    await tip(mint, tipperTokenAccount, tokenProgram)
+
+    const tipJarTokenAccount = getAssociatedTokenAddressSync(mint, tipJar, true, tokenProgram)
+    expect(Number((await getAccount(provider.connection, tipJarTokenAccount, undefined, tokenProgram)).amount)).toEqual(
+      250_000,
+    )
+
+    const [tally] = PublicKey.findProgramAddressSync(
+      [Buffer.from('token_tip_tally'), journalEntry.toBuffer(), mint.toBuffer()],
+      program.programId,
+    )
+    expect((await program.account.tokenTipTally.fetch(tally)).totalTipped.toNumber()).toEqual(250_000)
+
+    await withdraw(mint, authorTokenAccount, tokenProgram)
+
+    expect(Number((await getAccount(provider.connection, authorTokenAccount, undefined, tokenProgram)).amount)).toEqual(
+      250_000,
+    )
+  })
+
+  // Last, since it deletes the entry the other tests tip
+  it('Withdraws tips after the entry is deleted', async () => {
+    const { mint, tipperTokenAccount, authorTokenAccount } = await fundTipper(TOKEN_PROGRAM_ID)
+    await tip(mint, tipperTokenAccount, TOKEN_PROGRAM_ID)
+    await program.methods
+      .deleteJournalEntry(new BN(0))
+      .accountsPartial({
+        userProfile,
+        journalEntry,
+        rentRecipient: author.publicKey,
+        authority: author.publicKey,
+        signer: author.publicKey,
+        sessionKey: null,
+        titleClaim: null,
+      })
+      .rpc()
+    expect(await provider.connection.getAccountInfo(journalEntry)).toBeNull()
+
+    await withdraw(mint, authorTokenAccount, TOKEN_PROGRAM_ID)
+    expect(Number((await getAccount(provider.connection, authorTokenAccount)).amount)).toEqual(250_000)
+  })
+})
@@ -0,0 +1,106 @@
+import * as anchor from '@coral-xyz/anchor'
+import { BN, Program } from '@coral-xyz/anchor'
+import { LAMPORTS_PER_SOL, PublicKey, SystemProgram, Transaction } from '@solana/web3.js'
+import { JournalProgram } from '../target/types/journal'
+import { JournalVault } from '../target/types/journal_vault'
+import { ensureProgramConfig, findJournalEntry, findProgramConfig, findTreasury, findUserProfile } from './journal-setup'
+
+// A program-derived signer (standing in for a Squads vault) owns a journal and drives it through CPI.
+describe('journal with a PDA authority', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+
+  const program = anchor.workspace.Journal as Program<JournalProgram>
+  const vaultProgram = anchor.workspace.JournalVault as Program<JournalVault>
+
+  const [vault] = PublicKey.findProgramAddressSync([Buffer.from('vault')], vaultProgram.programId)
+  const programConfig = findProgramConfig(program.programId)
+  const userProfile = findUserProfile(program.programId, vault)
+  const journalEntry = findJournalEntry(program.programId, vault, 0)
+
+  beforeAll(async () => {
+    await ensureProgramConfig(program, provider)
+    // add_journal_entry only accepts CPI from allowlisted programs
+    await program.methods
+      .setCpiAllowlist([vaultProgram.programId])
+      .accountsPartial({ programConfig, admin: provider.wallet.publicKey })
+      .rpc()
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({ fromPubkey: provider.wallet.publicKey, toPubkey: vault, lamports: LAMPORTS_PER_SOL }),
+      ),
+    )
+  })
+
+  it('Initializes a profile owned by the vault', async () => {
+    await vaultProgram.methods
+      .initializeProfile()
+      .accountsPartial({ vault, programConfig, userProfile, journalProgram: program.programId })
+      .rpc()
+
+    const profile = await program.account.userProfile.fetch(userProfile)
+    expect(profile.authority.toBase58()).toEqual(vault.toBase58())
+  })
+
+  it('Adds an entry through CPI', async () => {
+    await vaultProgram.methods
+      .addEntry('Minutes', 'Approved by the multisig')
+      .accountsPartial({
+        vault,
+        programConfig,
+        treasury: findTreasury(program.programId),
+        userProfile,
+        journalEntry,
+        journalProgram: program.programId,
+      })
+      .rpc()
+
+    const entry = await program.account.journalEntry.fetch(journalEntry)
+    expect(entry.authority.toBase58()).toEqual(vault.toBase58())
+    expect(entry.title).toEqual('Minutes')
+  })
+
+  it('Updates the entry through CPI', async () => {
+    await vaultProgram.methods
+      .updateEntry(new BN(0), 'Minutes', 'Amended by the multisig')
+      .accountsPartial({ vault, programConfig, userProfile, journalEntry, journalProgram: program.programId })
+      .rpc()
+
+    const entry = await program.account.journalEntry.fetch(journalEntry)
+    expect(entry.message).toEqual('Amended by the multisig')
+  })
+
+  it('Deletes the entry through CPI', async () => {
+    await vaultProgram.methods
+      .deleteEntry(new BN(0))
+      .accountsPartial({ vault, programConfig, userProfile, journalEntry, journalProgram: program.programId })
+      .rpc()
+
+    expect(await program.account.journalEntry.fetchNullable(journalEntry)).toBeNull()
+  })
+
+  it('Refuses CPI from a program off the allowlist', async () => {
+    await program.methods.setCpiAllowlist([]).accountsPartial({ programConfig, admin: provider.wallet.publicKey }).rpc()
+    const { entryCount } = await program.account.userProfile.fetch(userProfile)
+    try {
+      await expect(
+        vaultProgram.methods
+          .addEntry('Minutes', 'Not allowed')
+          .accountsPartial({
+            vault,
+            programConfig,
+            treasury: findTreasury(program.programId),
+            userProfile,
+            journalEntry: findJournalEntry(program.programId, vault, entryCount.toNumber()),
+            journalProgram: program.programId,
+          })
+          .rpc(),
+      ).rejects.toThrow(/CpiCallerNotAllowed/)
+    } finally {
+      await program.methods
+        .setCpiAllowlist([vaultProgram.programId])
+        .accountsPartial({ programConfig, admin: provider.wallet.publicKey })
+        .rpc()
+    }
+  })
+})
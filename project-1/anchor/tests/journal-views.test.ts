@@ -0,0 +1,86 @@
+import * as anchor from '@coral-xyz/anchor'
+import { BN, Program } from '@coral-xyz/anchor'
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js'
+import { JournalProgram } from '../target/types/journal'
+import { ensureFeedHead, ensureProgramConfig, findJournalEntry, findUserProfile } from './journal-setup'
+
+describe('journal view counts', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+
+  const program = anchor.workspace.Journal as Program<JournalProgram>
+
+  const author = Keypair.generate()
+  const viewers = [Keypair.generate(), Keypair.generate()]
+  const userProfile = findUserProfile(program.programId, author.publicKey)
+  const journalEntry = findJournalEntry(program.programId, author.publicKey, 0)
+  const [viewTally] = PublicKey.findProgramAddressSync(
+    [Buffer.from('view_tally'), journalEntry.toBuffer()],
+    program.programId,
+  )
+
+  function recordView(viewer: Keypair) {
+    return program.methods
+      .recordView()
+      .accountsPartial({ journalEntry, viewer: viewer.publicKey })
+      .signers([viewer])
+      .rpc()
+  }
+
+  async function viewCount(): Promise<number> {
+    return (await program.account.viewTally.fetch(viewTally)).count.toNumber()
+  }
+
+  beforeAll(async () => {
+    await ensureProgramConfig(program, provider)
+    await ensureFeedHead(program, provider)
+    for (const wallet of [author, ...viewers]) {
+      const signature = await provider.connection.requestAirdrop(wallet.publicKey, LAMPORTS_PER_SOL)
+      await provider.connection.confirmTransaction(signature)
+    }
+    await program.methods
+      .initializeUserProfile()
+      .accountsPartial({ userProfile, authority: author.publicKey, payer: author.publicKey })
+      .signers([author])
+      .rpc()
+    await program.methods
+      .addJournalEntry('Open letter', 'Read me')
+      .accountsPartial({
+        userProfile,
+        journalEntry,
+        authority: author.publicKey,
+        signer: author.publicKey,
+        sessionKey: null,
+        gateTokenAccount: null,
+        titleClaim: null,
+        priceUpdate: null,
+        dailyPrompt: null,
+        journal: null,
+        feedHead: null,
+        replyParent: null,
+        memoProgram: null,
+        payer: author.publicKey,
+      })
+      .signers([author])
+      .rpc()
+  })
+
+  it('Rejects views of private entries', async () => {
+    await expect(recordView(viewers[0])).rejects.toThrow(/EntryNotPublic/)
+  })
+
+  it('Counts each viewer of a public entry once', async () => {
+    await program.methods
+      .setEntryVisibility(new BN(0), true)
+      .accountsPartial({ journalEntry, authority: author.publicKey, signer: author.publicKey, sessionKey: null })
+      .signers([author])
+      .rpc()
+
+    await recordView(viewers[0])
+    await recordView(viewers[0])
+    expect(await viewCount()).toEqual(1)
+
+    await recordView(viewers[1])
+    expect(await viewCount()).toEqual(2)
+  })
+})
@@ -0,0 +1,160 @@
+import * as anchor from '@coral-xyz/anchor'
+import { BN, Program } from '@coral-xyz/anchor'
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js'
+import { JournalProgram } from '../target/types/journal'
+import { ensureProgramConfig, findJournalEntry, findUserProfile } from './journal-setup'
+
+// Compares compute units of the zero-copy entry with the Borsh entry for the same writes.
+describe('journal zero-copy entries', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+
+  const program = anchor.workspace.Journal as Program<JournalProgram>
+
+  const author = Keypair.generate()
+  const userProfile = findUserProfile(program.programId, author.publicKey)
+  // Both kinds of entry share the profile's id counter: the Borsh entry gets id 0, the zero-copy one id 1
+  const journalEntry = findJournalEntry(program.programId, author.publicKey, 0)
+  const [zeroCopyEntry] = PublicKey.findProgramAddressSync(
+    [Buffer.from('zero_copy_entry'), author.publicKey.toBuffer(), new BN(1).toArrayLike(Buffer, 'le', 8)],
+    program.programId,
+  )
+
+  const title = 'Benchmark'
+  const message = 'The same text is written through both entry layouts. '.repeat(4)
+
+  async function computeUnits(signature: string): Promise<number> {
+    const transaction = await provider.connection.getTransaction(signature, {
+      commitment: 'confirmed',
+      maxSupportedTransactionVersion: 0,
+    })
+    return transaction!.meta!.computeUnitsConsumed!
+  }
+
+  function decode(bytes: number[], length: number): string {
+    return Buffer.from(bytes.slice(0, length)).toString('utf8')
+  }
+
+  beforeAll(async () => {
+    await ensureProgramConfig(program, provider)
+    const signature = await provider.connection.requestAirdrop(author.publicKey, LAMPORTS_PER_SOL)
+    await provider.connection.confirmTransaction(signature)
+    await program.methods
+      .initializeUserProfile()
+      .accountsPartial({ userProfile, authority: author.publicKey, payer: author.publicKey })
+      .signers([author])
+      .rpc()
+  })
+
+  it('Creates both entries and uses fewer compute units for the zero-copy one', async () => {
+    const borshSignature = await program.methods
+      .addJournalEntry(title, message)
+      .accountsPartial({
+        userProfile,
+        journalEntry,
+        authority: author.publicKey,
+        signer: author.publicKey,
+        sessionKey: null,
+        gateTokenAccount: null,
+        titleClaim: null,
+        priceUpdate: null,
+        dailyPrompt: null,
+        journal: null,
+        feedHead: null,
+        replyParent: null,
+        memoProgram: null,
+        payer: author.publicKey,
+      })
+      .signers([author])
+      .rpc({ commitment: 'confirmed' })
+    const zeroCopySignature = await program.methods
+      .addZeroCopyEntry(title, message)
+      .accountsPartial({ userProfile, zeroCopyEntry, authority: author.publicKey })
+      .signers([author])
+      .rpc({ commitment: 'confirmed' })
+
+    const borshUnits = await computeUnits(borshSignature)
+    const zeroCopyUnits = await computeUnits(zeroCopySignature)
+    console.log(`create: borsh ${borshUnits} CU, zero-copy ${zeroCopyUnits} CU`)
+    expect(zeroCopyUnits).toBeLessThan(borshUnits)
+
+    const entry = await program.account.zeroCopyEntry.fetch(zeroCopyEntry)
+    expect(entry.id.toNumber()).toEqual(1)
+    expect(decode(entry.title, entry.titleLen)).toEqual(title)
+    expect(decode(entry.message, entry.messageLen)).toEqual(message)
+  })
+
+  it('Updates both entries and uses fewer compute units for the zero-copy one', async () => {
+    const updatedMessage = message.toUpperCase()
+    const borshSignature = await program.methods
+      .updateJournalEntry(new BN(0), title, updatedMessage)
+      .accountsPartial({
+        userProfile,
+        journalEntry,
+        authority: author.publicKey,
+        signer: author.publicKey,
+        sessionKey: null,
+        memoProgram: null,
+      })
+      .signers([author])
+      .rpc({ commitment: 'confirmed' })
+    const zeroCopySignature = await program.methods
+      .updateZeroCopyEntry(new BN(1), title, updatedMessage)
+      .accountsPartial({ userProfile, zeroCopyEntry, authority: author.publicKey })
+      .signers([author])
+      .rpc({ commitment: 'confirmed' })
+
+    const borshUnits = await computeUnits(borshSignature)
+    const zeroCopyUnits = await computeUnits(zeroCopySignature)
+    console.log(`update: borsh ${borshUnits} CU, zero-copy ${zeroCopyUnits} CU`)
+    expect(zeroCopyUnits).toBeLessThan(borshUnits)
+  })
+
+  it('Rejects an update longer than the entry allows', async () => {
+    // 51 characters in 102 bytes: cut to the 100-byte buffer it would be 50 characters, within the limit
+    await expect(
+      program.methods
+        .updateZeroCopyEntry(new BN(1), 'é'.repeat(51), message)
+        .accountsPartial({ userProfile, zeroCopyEntry, authority: author.publicKey })
+        .signers([author])
+        .rpc(),
+    ).rejects.toThrow(/TitleTooLong/)
+
+    const entry = await program.account.zeroCopyEntry.fetch(zeroCopyEntry)
+    expect(decode(entry.title, entry.titleLen)).toEqual(title)
+  })
+
+  it('Patches part of the message in place', async () => {
+    const signature = await program.methods
+      .patchZeroCopyMessage(new BN(1), 4, Buffer.from('patched'))
+      .accountsPartial({ userProfile, zeroCopyEntry, authority: author.publicKey })
+      .signers([author])
+      .rpc({ commitment: 'confirmed' })
+    console.log(`patch: zero-copy ${await computeUnits(signature)} CU`)
+
+    const entry = await program.account.zeroCopyEntry.fetch(zeroCopyEntry)
+    const patched = decode(entry.message, entry.messageLen)
+    expect(patched.slice(0, 11)).toEqual('THE patched')
+    expect(patched.length).toEqual(message.length)
+  })
+
+  it('Rejects a patch that starts past the end of the message', async () => {
+    await expect(
+      program.methods
+        .patchZeroCopyMessage(new BN(1), message.length + 1, Buffer.from('gap'))
+        .accountsPartial({ userProfile, zeroCopyEntry, authority: author.publicKey })
+        .signers([author])
+        .rpc(),
+    ).rejects.toThrow(/MessagePatchOutOfRange/)
+  })
+
+  it('Deletes the zero-copy entry', async () => {
+    await program.methods
+      .deleteZeroCopyEntry(new BN(1))
+      .accountsPartial({ userProfile, zeroCopyEntry, authority: author.publicKey })
+      .signers([author])
+      .rpc()
+
+    expect(await program.account.zeroCopyEntry.fetchNullable(zeroCopyEntry)).toBeNull()
+  })
+})
@@ -0,0 +1,217 @@
+// Deep links for journal entries, shared by the mobile apps and the web frontend.
+//
+// Custom scheme:   solana-journal://entry/<owner>/<index>
+// Universal link:  https://<host>/entry/<owner>/<index>
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+pub const SCHEME: &str = "solana-journal";
+const ENTRY_PATH: &str = "entry";
+// Domain separator so a share signature can't be replayed as any other signed message
+const SHARE_DOMAIN: &[u8] = b"solana-journal-share:v1:";
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum LinkError {
+    #[error("link does not use the solana-journal scheme or an https host")]
+    InvalidScheme,
+    #[error("link path is not /entry/<owner>/<index>")]
+    InvalidPath,
+    #[error("owner is not a base58 encoded 32 byte public key")]
+    InvalidOwner,
+    #[error("entry index is not a valid u64")]
+    InvalidIndex,
+    #[error("share payload signature is invalid")]
+    InvalidSignature,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryLink {
+    pub owner: [u8; 32], // Entry authority (journal-program) or owner (pda-optimized program)
+    pub index: u64,      // `id` / `entry_index` of the entry
+}
+
+impl EntryLink {
+    pub fn new(owner: [u8; 32], index: u64) -> Self {
+        Self { owner, index }
+    }
+
+    pub fn owner_base58(&self) -> String {
+        bs58::encode(self.owner).into_string()
+    }
+
+    pub fn to_deep_link(&self) -> String {
+        format!("{}://{}/{}/{}", SCHEME, ENTRY_PATH, self.owner_base58(), self.index)
+    }
+
+    pub fn to_universal_link(&self, host: &str) -> String {
+        format!("https://{}/{}/{}/{}", host, ENTRY_PATH, self.owner_base58(), self.index)
+    }
+
+    // Accepts both the custom scheme and universal (https) links
+    pub fn parse(link: &str) -> Result<Self, LinkError> {
+        let path = if let Some(rest) = link.strip_prefix(&format!("{}://", SCHEME)) {
+            rest
+        } else if let Some(rest) = link.strip_prefix("https://") {
+            // Drop the host, keep the path
+            rest.split_once('/').map(|(_, path)| path).ok_or(LinkError::InvalidPath)?
+        } else {
+            return Err(LinkError::InvalidScheme);
+        };
+
+        // Ignore any query string or fragment appended by the sharing app
+        let path = path.split(['?', '#']).next().unwrap_or_default().trim_end_matches('/');
+        let mut segments = path.split('/');
+        let (Some(ENTRY_PATH), Some(owner), Some(index), None) =
+            (segments.next(), segments.next(), segments.next(), segments.next())
+        else {
+            return Err(LinkError::InvalidPath);
+        };
+
+        let owner: [u8; 32] = bs58::decode(owner)
+            .into_vec()
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(LinkError::InvalidOwner)?;
+        let index = index.parse().map_err(|_| LinkError::InvalidIndex)?;
+        Ok(Self { owner, index })
+    }
+}
+
+// apple-app-site-association body routing /entry/* universal links into the app
+pub fn apple_app_site_association(app_ids: &[&str]) -> serde_json::Value {
+    json!({
+        "applinks": {
+            "details": [{
+                "appIDs": app_ids,
+                "components": [{ "/": format!("/{}/*", ENTRY_PATH) }],
+            }],
+        },
+    })
+}
+
+// Android Digital Asset Links (assetlinks.json) body for the same universal links
+pub fn android_asset_links(package_name: &str, sha256_cert_fingerprints: &[&str]) -> serde_json::Value {
+    json!([{
+        "relation": ["delegate_permission/common.handle_all_urls"],
+        "target": {
+            "namespace": "android_app",
+            "package_name": package_name,
+            "sha256_cert_fingerprints": sha256_cert_fingerprints,
+        },
+    }])
+}
+
+// A link signed by the entry owner, so receivers can trust who shared it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharePayload {
+    pub link: String,      // Custom scheme deep link
+    pub issued_at: i64,    // Unix timestamp chosen by the sharer
+    pub signature: String, // base58 ed25519 signature by the link owner
+}
+
+impl SharePayload {
+    pub fn sign(link: &EntryLink, issued_at: i64, owner_key: &SigningKey) -> Self {
+        let deep_link = link.to_deep_link();
+        let signature = owner_key.sign(&share_message(&deep_link, issued_at));
+        Self {
+            link: deep_link,
+            issued_at,
+            signature: bs58::encode(signature.to_bytes()).into_string(),
+        }
+    }
+
+    // Checks the signature against the owner named in the link and returns the parsed link
+    pub fn verify(&self) -> Result<EntryLink, LinkError> {
+        let link = EntryLink::parse(&self.link)?;
+        let owner = VerifyingKey::from_bytes(&link.owner).map_err(|_| LinkError::InvalidOwner)?;
+        let signature: [u8; 64] = bs58::decode(&self.signature)
+            .into_vec()
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(LinkError::InvalidSignature)?;
+        owner
+            .verify(&share_message(&self.link, self.issued_at), &Signature::from_bytes(&signature))
+            .map_err(|_| LinkError::InvalidSignature)?;
+        Ok(link)
+    }
+}
+
+fn share_message(deep_link: &str, issued_at: i64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(SHARE_DOMAIN.len() + deep_link.len() + 8);
+    message.extend_from_slice(SHARE_DOMAIN);
+    message.extend_from_slice(deep_link.as_bytes());
+    message.extend_from_slice(&issued_at.to_le_bytes());
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner_key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    fn link() -> EntryLink {
+        EntryLink::new(owner_key().verifying_key().to_bytes(), 42)
+    }
+
+    #[test]
+    fn deep_and_universal_links_round_trip() {
+        let link = link();
+        assert_eq!(EntryLink::parse(&link.to_deep_link()), Ok(link));
+        assert_eq!(EntryLink::parse(&link.to_universal_link("journal.example")), Ok(link));
+
+        let shared = format!("{}/?utm_source=share#top", link.to_universal_link("journal.example"));
+        assert_eq!(EntryLink::parse(&shared), Ok(link));
+    }
+
+    #[test]
+    fn rejects_malformed_links() {
+        let owner = link().owner_base58();
+        assert_eq!(EntryLink::parse(&format!("http://host/entry/{owner}/1")), Err(LinkError::InvalidScheme));
+        assert_eq!(EntryLink::parse(&format!("solana-journal://note/{owner}/1")), Err(LinkError::InvalidPath));
+        assert_eq!(EntryLink::parse(&format!("solana-journal://entry/{owner}/1/2")), Err(LinkError::InvalidPath));
+        assert_eq!(EntryLink::parse("solana-journal://entry/0OIl/1"), Err(LinkError::InvalidOwner));
+        assert_eq!(EntryLink::parse(&format!("solana-journal://entry/{owner}/-1")), Err(LinkError::InvalidIndex));
+    }
+
+    #[test]
+    fn universal_link_association_files() {
+        let apple = apple_app_site_association(&["TEAMID.app.journal"]);
+        assert_eq!(apple["applinks"]["details"][0]["appIDs"], json!(["TEAMID.app.journal"]));
+        assert_eq!(apple["applinks"]["details"][0]["components"][0]["/"], "/entry/*");
+
+        let android = android_asset_links("app.journal", &["AA:BB"]);
+        assert_eq!(android[0]["target"]["package_name"], "app.journal");
+        assert_eq!(android[0]["target"]["sha256_cert_fingerprints"], json!(["AA:BB"]));
+        assert_eq!(android[0]["relation"][0], "delegate_permission/common.handle_all_urls");
+    }
+
+    #[test]
+    fn signed_share_verifies() {
+        let payload = SharePayload::sign(&link(), 1_700_000_000, &owner_key());
+        assert_eq!(payload.link, link().to_deep_link());
+        assert_eq!(payload.verify(), Ok(link()));
+    }
+
+    #[test]
+    fn tampered_share_is_rejected() {
+        let payload = SharePayload::sign(&link(), 1_700_000_000, &owner_key());
+
+        let later = SharePayload { issued_at: payload.issued_at + 1, ..payload.clone() };
+        assert_eq!(later.verify(), Err(LinkError::InvalidSignature));
+
+        let other_entry = SharePayload { link: EntryLink { index: 43, ..link() }.to_deep_link(), ..payload.clone() };
+        assert_eq!(other_entry.verify(), Err(LinkError::InvalidSignature));
+
+        // Signed by someone other than the owner the link names
+        let forged = SharePayload::sign(&link(), payload.issued_at, &SigningKey::from_bytes(&[8; 32]));
+        assert_eq!(forged.verify(), Err(LinkError::InvalidSignature));
+
+        let garbled = SharePayload { signature: "not base58!".into(), ..payload };
+        assert_eq!(garbled.verify(), Err(LinkError::InvalidSignature));
+    }
+}
@@ -1,7 +1,34 @@
 use anchor_lang::prelude::*;
+#[cfg(feature = "verbose-logs")]
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::system_program;
 
 declare_id!("94L2mJxVu6ZMmHaGsCHRQ65Kk2mea6aTnwWjSdfSsmBC"); // Replace with your new Program ID after building
 
+// Schema version of JournalEntryState, stored after the owner and entry index. Entries created before the field
+// existed are recognized by size and upgraded by migrate_entry or their next update.
+const JOURNAL_ENTRY_STATE_VERSION: u8 = 1;
+
+// Stable byte offsets (discriminator included) of the owner and entry index in JournalEntryState, for
+// getProgramAccounts memcmp filters. V0 entries keep the owner at the same offset but not the index.
+pub const ENTRY_OWNER_OFFSET: usize = 8;
+pub const ENTRY_INDEX_OFFSET: usize = 40;
+
+pub const IDEMPOTENCY_SEED: &[u8] = b"idempotency";
+
+// Instruction logs are only compiled in with the `verbose-logs` feature. Release builds leave them out to save
+// compute units and so long logs can't push Anchor events past the log truncation limit.
+#[cfg(feature = "verbose-logs")]
+macro_rules! verbose_msg {
+    ($($arg:tt)*) => { msg!($($arg)*) };
+}
+
+#[cfg(not(feature = "verbose-logs"))]
+macro_rules! verbose_msg {
+    ($($arg:tt)*) => {};
+}
+
 #[program]
 pub mod journal_pda_optimized {
     use super::*;
@@ -12,62 +39,202 @@ pub mod journal_pda_optimized {
         ctx.accounts.user_journal_counter.last_entry_index = 0;
         // Corrected bump access:
         ctx.accounts.user_journal_counter.bump = ctx.bumps.user_journal_counter;
-        msg!("User journal counter initialized for: {}", ctx.accounts.owner.key());
+        verbose_msg!("User journal counter initialized for: {}", ctx.accounts.owner.key());
         Ok(())
     }
 
+    // `expected_index` is the counter value the client derived the entry address from. If another device
+    // created an entry in the meantime, this fails with StaleCounter and the client can re-fetch and retry.
+    //
+    // With an `idempotency_key` (and its idempotency_record account), the first create records the key and any
+    // retry with the same key succeeds without creating another entry, whatever index it was built for.
     pub fn create_journal_entry(
         ctx: Context<CreateEntry>,
+        expected_index: u64,
         title: String,
         message: String,
+        idempotency_key: Option<[u8; 16]>,
     ) -> Result<()> {
-        let user_journal_counter = &mut ctx.accounts.user_journal_counter;
-        let current_entry_index = user_journal_counter.last_entry_index;
+        let owner_key = ctx.accounts.owner.key();
+        let current_entry_index = ctx.accounts.user_journal_counter.last_entry_index;
 
-        let journal_entry = &mut ctx.accounts.journal_entry;
-        journal_entry.owner = *ctx.accounts.owner.key;
-        journal_entry.title = title.clone(); // Keep title for display/data
-        journal_entry.message = message.clone();
-        journal_entry.entry_index = current_entry_index;
-        // Corrected bump access:
-        journal_entry.bump = ctx.bumps.journal_entry;
+        // A retry whose key is already recorded succeeds without creating anything
+        if let (Some(record), Some(_)) = (&ctx.accounts.idempotency_record, idempotency_key) {
+            if !record.data_is_empty() {
+                require_keys_eq!(*record.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+                #[cfg_attr(not(feature = "verbose-logs"), allow(unused_variables))]
+                let existing = IdempotencyRecord::try_deserialize(&mut &record.try_borrow_data()?[..])?;
+                verbose_msg!("Idempotency key already used for entry {}; nothing to do", existing.entry_index);
+                return Ok(());
+            }
+        }
+
+        // Checked before any account is created, so a stale or duplicate create doesn't consume the key or pay
+        // for accounts only to have them rolled back
+        require_eq!(current_entry_index, expected_index, JournalError::StaleCounter);
+        let content_hash = JournalEntryState::content_hash(&title, &message);
+        if let Some(previous_entry) = &ctx.accounts.previous_entry {
+            if previous_entry.content_hash == content_hash {
+                return err!(JournalError::DuplicateContent);
+            }
+        }
+
+        match (&ctx.accounts.idempotency_record, idempotency_key) {
+            (Some(record), Some(key)) => {
+                let record_bump = ctx.bumps.idempotency_record.ok_or(JournalError::IdempotencyKeyMismatch)?;
+                let signer_seeds: &[&[u8]] = &[IDEMPOTENCY_SEED, owner_key.as_ref(), key.as_ref(), &[record_bump]];
+                let space = 8 + IdempotencyRecord::INIT_SPACE;
+                create_program_account(record, &ctx.accounts.owner, &ctx.accounts.system_program, space, signer_seeds)?;
+                let idempotency_record = IdempotencyRecord { owner: owner_key, entry_index: current_entry_index, bump: record_bump };
+                idempotency_record.try_serialize(&mut &mut record.try_borrow_mut_data()?[..])?;
+            }
+            (None, None) => {}
+            _ => return err!(JournalError::IdempotencyKeyMismatch),
+        }
+
+        // The entry is created here rather than with `init`, which Anchor would run before the checks above
+        let bump = ctx.bumps.journal_entry;
+        let index_bytes = expected_index.to_le_bytes();
+        let signer_seeds: &[&[u8]] = &[owner_key.as_ref(), b"journal".as_ref(), index_bytes.as_ref(), &[bump]];
+        let space = 8 + JournalEntryState::INIT_SPACE;
+        create_program_account(&ctx.accounts.journal_entry, &ctx.accounts.owner, &ctx.accounts.system_program, space, signer_seeds)?;
+
+        // The strings are moved into the account; logs below only report their sizes and hashes
+        let journal_entry = JournalEntryState {
+            owner: owner_key,
+            entry_index: current_entry_index,
+            version: JOURNAL_ENTRY_STATE_VERSION,
+            edit_count: 0,
+            content_hash,
+            title,
+            message,
+            bump,
+        };
+        journal_entry.try_serialize(&mut &mut ctx.accounts.journal_entry.try_borrow_mut_data()?[..])?;
 
         // Increment the user's entry counter for the next entry
-        user_journal_counter.last_entry_index = current_entry_index.checked_add(1).ok_or_else(|| ProgramError::Custom(0))?; // Added proper error handling for overflow
+        let user_journal_counter = &mut ctx.accounts.user_journal_counter;
+        user_journal_counter.last_entry_index = current_entry_index.checked_add(1).ok_or(ProgramError::Custom(0))?; // Added proper error handling for overflow
 
-        msg!("Journal Entry Created");
-        msg!("Owner: {}", journal_entry.owner);
-        msg!("Title: {}", title);
-        msg!("Message: {}", message);
-        msg!("Entry Index: {}", current_entry_index);
+        verbose_msg!("Journal Entry Created");
+        verbose_msg!("Owner: {}", journal_entry.owner);
+        verbose_msg!("Title: {} bytes, hash {}", journal_entry.title.len(), hash(journal_entry.title.as_bytes()));
+        verbose_msg!("Message: {} bytes, hash {}", journal_entry.message.len(), hash(journal_entry.message.as_bytes()));
+        verbose_msg!("Entry Index: {}", current_entry_index);
         Ok(())
     }
 
+    // `expected_edit_count` is the entry's edit_count as the client last read it. A mismatch means someone else
+    // edited the entry since, and the update fails with ConcurrentModification instead of overwriting their edit.
     pub fn update_journal_entry(
         ctx: Context<UpdateEntry>,
         _entry_index: u64, // entry_index is now part of seeds, so implicitly validated
+        _expected_edit_count: u32, // checked in UpdateEntry
         new_title: String,
         new_message: String,
     ) -> Result<()> {
-        msg!("Journal Entry Updating");
-        msg!("Owner: {}", ctx.accounts.owner.key());
-        msg!("Entry Index: {}", ctx.accounts.journal_entry.entry_index);
-        msg!("New Title: {}", new_title);
-        msg!("New Message: {}", new_message);
+        verbose_msg!("Journal Entry Updating");
+        verbose_msg!("Owner: {}", ctx.accounts.owner.key());
+        verbose_msg!("Entry Index: {}", ctx.accounts.journal_entry.entry_index);
+        verbose_msg!("New Title: {} bytes, hash {}", new_title.len(), hash(new_title.as_bytes()));
+        verbose_msg!("New Message: {} bytes, hash {}", new_message.len(), hash(new_message.as_bytes()));
 
         let journal_entry = &mut ctx.accounts.journal_entry;
+        journal_entry.content_hash = JournalEntryState::content_hash(&new_title, &new_message);
         journal_entry.title = new_title;
         journal_entry.message = new_message;
+        journal_entry.edit_count = journal_entry.edit_count.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+
+        Ok(())
+    }
+
+    // Rewrites an entry in an older layout in the current one; the owner pays the extra rent
+    pub fn migrate_entry(ctx: Context<MigrateEntry>, _entry_index: u64) -> Result<()> {
+        let info = ctx.accounts.journal_entry.to_account_info();
+        let body_len = info.data_len().saturating_sub(8);
+        if body_len != JournalEntryStateV0::INIT_SPACE {
+            return err!(JournalError::AccountAlreadyCurrent);
+        }
+        let upgraded = JournalEntryState::try_deserialize(&mut &info.try_borrow_data()?[..])?;
 
+        let space = 8 + JournalEntryState::INIT_SPACE;
+        let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.owner.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        info.realloc(space, false)?;
+        upgraded.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        verbose_msg!("Journal entry {} migrated to version {}", upgraded.entry_index, JOURNAL_ENTRY_STATE_VERSION);
         Ok(())
     }
 
     pub fn delete_journal_entry(_ctx: Context<DeleteEntry>, _entry_index: u64) -> Result<()> {
-        msg!("Journal entry at index {} for owner {} deleted", _ctx.accounts.journal_entry.entry_index, _ctx.accounts.owner.key());
+        verbose_msg!("Journal entry at index {} for owner {} deleted", _ctx.accounts.journal_entry.entry_index, _ctx.accounts.owner.key());
         Ok(())
     }
 }
 
+// Does what `init` would: funds a PDA with rent from `owner`, allocates it and assigns it to this program.
+// An address that was sent lamports beforehand can't go through create_account, so it is topped up instead.
+fn create_program_account<'info>(
+    account: &UncheckedAccount<'info>,
+    owner: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+    if current_lamports == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                system_program::CreateAccount { from: owner.to_account_info(), to: account.to_account_info() },
+                &[signer_seeds],
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    let shortfall = rent.saturating_sub(current_lamports);
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer { from: owner.to_account_info(), to: account.to_account_info() },
+            ),
+            shortfall,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Allocate { account_to_allocate: account.to_account_info() },
+            &[signer_seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Assign { account_to_assign: account.to_account_info() },
+            &[signer_seeds],
+        ),
+        &crate::ID,
+    )
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct UserJournalCounter {
@@ -76,9 +243,100 @@ pub struct UserJournalCounter {
     pub bump: u8,
 }
 
+// Marks an idempotency key as used; entry_index is the entry its first create made
 #[account]
 #[derive(InitSpace)]
+pub struct IdempotencyRecord {
+    pub owner: Pubkey,
+    pub entry_index: u64,
+    pub bump: u8,
+}
+
+// Implements the account traits by hand instead of using #[account] so that entries still in an older layout
+// deserialize (upgraded in memory); update_journal_entry then grows them and writes the current layout back.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct JournalEntryState {
+    // owner and entry_index must stay first, at ENTRY_OWNER_OFFSET and ENTRY_INDEX_OFFSET
+    pub owner: Pubkey,
+    pub entry_index: u64,
+    pub version: u8,
+    pub edit_count: u32, // Successful updates so far; clients pass it back to update_journal_entry
+    pub content_hash: [u8; 32], // See JournalEntryState::content_hash
+    #[max_len(50)]
+    pub title: String,
+    #[max_len(280)]
+    pub message: String,
+    pub bump: u8,
+}
+
+impl JournalEntryState {
+    // sha256 of the borsh-encoded (title, message)
+    pub fn content_hash(title: &str, message: &str) -> [u8; 32] {
+        hashv(&[
+            &(title.len() as u32).to_le_bytes(),
+            title.as_bytes(),
+            &(message.len() as u32).to_le_bytes(),
+            message.as_bytes(),
+        ])
+        .to_bytes()
+    }
+
+    // Current layout of an entry read from the unversioned one; fields it didn't have start as on a new entry
+    fn from_legacy(owner: Pubkey, entry_index: u64, title: String, message: String, bump: u8) -> Self {
+        JournalEntryState {
+            owner,
+            entry_index,
+            version: JOURNAL_ENTRY_STATE_VERSION,
+            edit_count: 0,
+            content_hash: Self::content_hash(&title, &message),
+            title,
+            message,
+            bump,
+        }
+    }
+}
+
+impl anchor_lang::Discriminator for JournalEntryState {
+    // sha256("account:JournalEntryState")[..8], as #[account] derives it
+    const DISCRIMINATOR: &'static [u8] = &[113, 86, 110, 124, 140, 14, 58, 66];
+}
+
+impl anchor_lang::Owner for JournalEntryState {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+}
+
+impl anchor_lang::AccountSerialize for JournalEntryState {
+    fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(Self::DISCRIMINATOR).map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+        AnchorSerialize::serialize(self, writer).map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+        Ok(())
+    }
+}
+
+impl anchor_lang::AccountDeserialize for JournalEntryState {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        if !buf.starts_with(Self::DISCRIMINATOR) {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let body = buf.get(Self::DISCRIMINATOR.len()..).ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        // Accounts still sized for the unversioned layout hold data in it
+        if body.len() == JournalEntryStateV0::INIT_SPACE {
+            let old = JournalEntryStateV0::deserialize(&mut &body[..])?;
+            return Ok(old.upgrade());
+        }
+        AnchorDeserialize::deserialize(&mut &body[..]).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+// JournalEntryState before the version field was added
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct JournalEntryStateV0 {
     pub owner: Pubkey,
     #[max_len(50)]
     pub title: String,
@@ -88,6 +346,12 @@ pub struct JournalEntryState {
     pub bump: u8,
 }
 
+impl JournalEntryStateV0 {
+    fn upgrade(self) -> JournalEntryState {
+        JournalEntryState::from_legacy(self.owner, self.entry_index, self.title, self.message, self.bump)
+    }
+}
+
 #[derive(Accounts)]
 pub struct InitializeUserJournalCounter<'info> {
     #[account(
@@ -104,33 +368,51 @@ pub struct InitializeUserJournalCounter<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(title: String, message: String)]
+#[instruction(expected_index: u64, title: String, message: String, idempotency_key: Option<[u8; 16]>)]
 pub struct CreateEntry<'info> {
+    // The handler checks last_entry_index against expected_index, after looking for an idempotency record
     #[account(
-        init,
-        payer = owner,
-        space = 8 + JournalEntryState::INIT_SPACE,
+        mut,
+        seeds = [b"counter".as_ref(), owner.key().as_ref()],
+        bump = user_journal_counter.bump,
+    )]
+    pub user_journal_counter: Account<'info, UserJournalCounter>,
+    /// CHECK: created and written by the handler (see create_program_account) once the counter has been checked
+    #[account(
+        mut,
         seeds = [
             owner.key().as_ref(),
             b"journal".as_ref(),
-            user_journal_counter.last_entry_index.to_le_bytes().as_ref()
+            expected_index.to_le_bytes().as_ref()
         ],
         bump // Anchor will find and assign the canonical bump to ctx.bumps.journal_entry
     )]
-    pub journal_entry: Account<'info, JournalEntryState>,
+    pub journal_entry: UncheckedAccount<'info>,
+    // Pass the entry just before this one to reject the create if it has the same title and message, e.g. when a
+    // client retries a create that actually went through
+    #[account(
+        seeds = [
+            owner.key().as_ref(),
+            b"journal".as_ref(),
+            expected_index.wrapping_sub(1).to_le_bytes().as_ref()
+        ],
+        bump = previous_entry.bump
+    )]
+    pub previous_entry: Option<Account<'info, JournalEntryState>>,
+    /// CHECK: IdempotencyRecord for idempotency_key; the handler reads it if it exists and creates it otherwise
     #[account(
         mut,
-        seeds = [b"counter".as_ref(), owner.key().as_ref()],
-        bump = user_journal_counter.bump
+        seeds = [IDEMPOTENCY_SEED, owner.key().as_ref(), idempotency_key.unwrap_or_default().as_ref()],
+        bump
     )]
-    pub user_journal_counter: Account<'info, UserJournalCounter>,
+    pub idempotency_record: Option<UncheckedAccount<'info>>,
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(entry_index: u64, new_title: String, new_message: String)]
+#[instruction(entry_index: u64, expected_edit_count: u32, new_title: String, new_message: String)]
 pub struct UpdateEntry<'info> {
     #[account(
         mut,
@@ -140,6 +422,11 @@ pub struct UpdateEntry<'info> {
             entry_index.to_le_bytes().as_ref()
         ],
         bump = journal_entry.bump,
+        // Upgrades an older-layout entry in place, a no-op once it's current
+        realloc = 8 + JournalEntryState::INIT_SPACE,
+        realloc::payer = owner,
+        realloc::zero = false,
+        constraint = journal_entry.edit_count == expected_edit_count @ JournalError::ConcurrentModification,
     )]
     pub journal_entry: Account<'info, JournalEntryState>,
     #[account(mut)]
@@ -147,6 +434,26 @@ pub struct UpdateEntry<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(entry_index: u64)]
+pub struct MigrateEntry<'info> {
+    /// CHECK: owner is checked here, discriminator and size in the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [
+            owner.key().as_ref(),
+            b"journal".as_ref(),
+            entry_index.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub journal_entry: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(entry_index: u64)]
 pub struct DeleteEntry<'info> {
@@ -166,10 +473,34 @@ pub struct DeleteEntry<'info> {
     pub system_program: Program<'info, System>,
 }
 
-// Added for .ok_or_else in create_journal_entry for better error handling
-// You might want to define more specific errors.
-// #[error_code]
-// pub enum JournalError {
-//     #[msg("Index overflow when creating new entry.")]
-//     IndexOverflow,
-// } 
\ No newline at end of file
+#[error_code]
+pub enum JournalError {
+    #[msg("The account already uses the current layout.")]
+    AccountAlreadyCurrent,
+    #[msg("The entry counter has moved past the expected index; re-fetch it and retry.")]
+    StaleCounter,
+    #[msg("The entry was edited since it was read; re-fetch it and retry.")]
+    ConcurrentModification,
+    #[msg("The entry has the same title and message as the previous one.")]
+    DuplicateContent,
+    #[msg("Pass both the idempotency key and its record account, or neither.")]
+    IdempotencyKeyMismatch,
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_unversioned_entries() {
+        // An entry as the program wrote it before versioning: owner, title, message, index, bump
+        let owner = Pubkey::new_unique();
+        let mut data = JournalEntryState::DISCRIMINATOR.to_vec();
+        (owner, "Day one", "Hello", 5u64, 253u8).serialize(&mut data).unwrap();
+        data.resize(8 + JournalEntryStateV0::INIT_SPACE, 0);
+
+        let entry = JournalEntryState::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!((entry.owner, entry.entry_index, entry.version), (owner, 5, JOURNAL_ENTRY_STATE_VERSION));
+        assert_eq!((entry.title.as_str(), entry.message.as_str(), entry.bump), ("Day one", "Hello", 253));
+        assert_eq!((entry.edit_count, entry.content_hash), (0, JournalEntryState::content_hash("Day one", "Hello")));
+    }
+}
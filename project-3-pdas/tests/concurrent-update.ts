@@ -0,0 +1,53 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import { NewCrudApp } from "../target/types/new_crud_app";
+import { JournalOwner } from "./helpers";
+
+// Two clients read the entry at edit_count 0 and both try to save an edit; the second one must not win silently.
+describe("update_journal_entry compare-and-swap", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.newCrudApp as Program<NewCrudApp>;
+  const owner = new JournalOwner(program);
+  const index = new anchor.BN(0);
+  const journalEntry = owner.findEntry(0);
+
+  function update(expectedEditCount: number, message: string) {
+    return program.methods
+      .updateJournalEntry(index, expectedEditCount, "Shared", message)
+      .accountsPartial({ journalEntry, owner: owner.publicKey })
+      .signers([owner.keypair])
+      .rpc();
+  }
+
+  before(async () => {
+    await owner.initialize();
+    await owner.createEntry(0, "Shared", "First draft");
+  });
+
+  it("Rejects an update based on a stale edit count", async () => {
+    await update(0, "Edited on my phone");
+
+    try {
+      await update(0, "Edited on my laptop");
+      expect.fail("the stale update should have failed");
+    } catch (err) {
+      expect(err).to.be.instanceOf(anchor.AnchorError);
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("ConcurrentModification");
+    }
+
+    const entry = await program.account.journalEntryState.fetch(journalEntry);
+    expect(entry.message).to.equal("Edited on my phone");
+    expect(entry.editCount).to.equal(1);
+  });
+
+  it("Accepts an update based on the current edit count", async () => {
+    await update(1, "Merged on my laptop");
+
+    const entry = await program.account.journalEntryState.fetch(journalEntry);
+    expect(entry.message).to.equal("Merged on my laptop");
+    expect(entry.editCount).to.equal(2);
+  });
+});
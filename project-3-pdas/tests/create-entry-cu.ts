@@ -0,0 +1,41 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import { NewCrudApp } from "../target/types/new_crud_app";
+import { JournalOwner } from "./helpers";
+
+// create_journal_entry moves the title and message into the account instead of cloning them for the logs, so
+// a full-length message should cost little more than a short one. Run with the default (non verbose-logs) build.
+const MAX_LENGTH_OVERHEAD_CU = 5_000;
+// Generous ceiling for a full-length create, for regressions that don't grow with the message length
+const MAX_CREATE_CU = 40_000;
+
+describe("create_journal_entry compute units", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.newCrudApp as Program<NewCrudApp>;
+  const owner = new JournalOwner(program);
+
+  async function createEntry(index: number, title: string, message: string): Promise<number> {
+    const signature = await owner.createEntry(index, title, message, { commitment: "confirmed" });
+    const transaction = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    return transaction!.meta!.computeUnitsConsumed!;
+  }
+
+  before(async () => {
+    await owner.initialize();
+  });
+
+  it("Barely grows with the message length", async () => {
+    const shortUnits = await createEntry(0, "Short", "Hi");
+    const longUnits = await createEntry(1, "x".repeat(50), "y".repeat(280));
+    console.log(`create_journal_entry: ${shortUnits} CU (2 chars), ${longUnits} CU (280 chars)`);
+
+    expect(longUnits - shortUnits).to.be.lessThan(MAX_LENGTH_OVERHEAD_CU);
+    expect(longUnits).to.be.lessThan(MAX_CREATE_CU);
+  });
+});
@@ -0,0 +1,52 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import { NewCrudApp } from "../target/types/new_crud_app";
+import { JournalOwner } from "./helpers";
+
+// A create that passes the previous entry is rejected when its title and message match that entry.
+describe("create_journal_entry duplicate check", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.newCrudApp as Program<NewCrudApp>;
+  const owner = new JournalOwner(program);
+
+  function createEntry(index: number, message: string, checkPrevious: boolean) {
+    return owner.createEntry(index, "Morning", message, {
+      previousEntry: checkPrevious ? owner.findEntry(index - 1) : null,
+    });
+  }
+
+  before(async () => {
+    await owner.initialize();
+    await createEntry(0, "Coffee and a walk", false);
+  });
+
+  it("Rejects the same content as the previous entry", async () => {
+    try {
+      await createEntry(1, "Coffee and a walk", true);
+      expect.fail("the duplicate create should have failed");
+    } catch (err) {
+      expect(err).to.be.instanceOf(anchor.AnchorError);
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("DuplicateContent");
+    }
+    const counter = await program.account.userJournalCounter.fetch(owner.userJournalCounter);
+    expect(counter.lastEntryIndex.toNumber()).to.equal(1);
+  });
+
+  it("Accepts different content", async () => {
+    await createEntry(1, "Tea and a run", true);
+
+    const entry = await program.account.journalEntryState.fetch(owner.findEntry(1));
+    const previous = await program.account.journalEntryState.fetch(owner.findEntry(0));
+    expect(entry.contentHash).to.not.deep.equal(previous.contentHash);
+  });
+
+  it("Skips the check when the previous entry isn't passed", async () => {
+    await createEntry(2, "Tea and a run", false);
+
+    const counter = await program.account.userJournalCounter.fetch(owner.userJournalCounter);
+    expect(counter.lastEntryIndex.toNumber()).to.equal(3);
+  });
+});
@@ -0,0 +1,45 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { expect } from "chai";
+import { NewCrudApp } from "../target/types/new_crud_app";
+import { JournalOwner } from "./helpers";
+
+// Indexers filter entries with getProgramAccounts memcmp on these offsets (ENTRY_OWNER_OFFSET and
+// ENTRY_INDEX_OFFSET in the program). Changing them is a breaking change for every indexer.
+const OWNER_OFFSET = 8;
+const ENTRY_INDEX_OFFSET = 40;
+
+describe("JournalEntryState layout", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.newCrudApp as Program<NewCrudApp>;
+  const owner = new JournalOwner(program);
+  const journalEntry = owner.findEntry(0);
+
+  before(async () => {
+    await owner.initialize();
+    await owner.createEntry(0, "Layout", "Offsets are part of the API");
+  });
+
+  it("Keeps owner and entry_index as the leading fields", () => {
+    const type = program.idl.types!.find((t) => t.name === "journalEntryState")!.type as {
+      fields: { name: string }[];
+    };
+    expect(type.fields.slice(0, 3).map((field) => field.name)).to.deep.equal(["owner", "entryIndex", "version"]);
+  });
+
+  it("Stores owner and entry_index at the documented offsets", async () => {
+    const data = (await provider.connection.getAccountInfo(journalEntry))!.data;
+    expect(new PublicKey(data.subarray(OWNER_OFFSET, OWNER_OFFSET + 32)).toBase58()).to.equal(
+      owner.publicKey.toBase58()
+    );
+    expect(data.readBigUInt64LE(ENTRY_INDEX_OFFSET)).to.equal(BigInt(0));
+
+    const matches = await program.account.journalEntryState.all([
+      { memcmp: { offset: OWNER_OFFSET, bytes: owner.publicKey.toBase58() } },
+    ]);
+    expect(matches.map((match) => match.publicKey.toBase58())).to.deep.equal([journalEntry.toBase58()]);
+  });
+});
@@ -0,0 +1,73 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Commitment, Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { NewCrudApp } from "../target/types/new_crud_app";
+
+// Shared setup for the test files: each file creates its own JournalOwner, a fresh wallet with its own counter
+// and entries, so files can't see each other's state whatever order they run in.
+
+export interface CreateOptions {
+  previousEntry?: PublicKey | null; // Passing it turns on the duplicate content check
+  idempotencyKey?: number[] | null;
+  idempotencyRecord?: PublicKey | null; // Defaults to the key's record when a key is given
+  commitment?: Commitment;
+}
+
+export class JournalOwner {
+  readonly keypair = Keypair.generate();
+  readonly userJournalCounter: PublicKey;
+
+  constructor(readonly program: Program<NewCrudApp>) {
+    this.userJournalCounter = PublicKey.findProgramAddressSync(
+      [Buffer.from("counter"), this.publicKey.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  get publicKey(): PublicKey {
+    return this.keypair.publicKey;
+  }
+
+  // Funds the wallet and initializes its counter; call it from before()
+  async initialize() {
+    const connection = this.program.provider.connection;
+    const signature = await connection.requestAirdrop(this.publicKey, LAMPORTS_PER_SOL);
+    await connection.confirmTransaction(signature);
+    await this.program.methods
+      .initializeUserJournalCounter()
+      .accountsPartial({ userJournalCounter: this.userJournalCounter, owner: this.publicKey })
+      .signers([this.keypair])
+      .rpc();
+  }
+
+  findEntry(index: number): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [this.publicKey.toBuffer(), Buffer.from("journal"), new anchor.BN(index).toArrayLike(Buffer, "le", 8)],
+      this.program.programId
+    )[0];
+  }
+
+  findIdempotencyRecord(key: number[]): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("idempotency"), this.publicKey.toBuffer(), Buffer.from(key)],
+      this.program.programId
+    )[0];
+  }
+
+  // create_journal_entry at `index`, the counter value the caller read; resolves to the transaction signature
+  createEntry(index: number, title: string, message: string, options: CreateOptions = {}): Promise<string> {
+    const idempotencyKey = options.idempotencyKey ?? null;
+    const defaultRecord = idempotencyKey ? this.findIdempotencyRecord(idempotencyKey) : null;
+    return this.program.methods
+      .createJournalEntry(new anchor.BN(index), title, message, idempotencyKey)
+      .accountsPartial({
+        journalEntry: this.findEntry(index),
+        previousEntry: options.previousEntry ?? null,
+        idempotencyRecord: options.idempotencyRecord === undefined ? defaultRecord : options.idempotencyRecord,
+        userJournalCounter: this.userJournalCounter,
+        owner: this.publicKey,
+      })
+      .signers([this.keypair])
+      .rpc(options.commitment ? { commitment: options.commitment } : undefined);
+  }
+}
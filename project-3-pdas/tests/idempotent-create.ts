@@ -0,0 +1,57 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import { randomBytes } from "crypto";
+import { NewCrudApp } from "../target/types/new_crud_app";
+import { CreateOptions, JournalOwner } from "./helpers";
+
+// A client that times out and retries with a fresh counter read must not end up with two entries.
+describe("create_journal_entry with an idempotency key", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.newCrudApp as Program<NewCrudApp>;
+  const owner = new JournalOwner(program);
+  const idempotencyKey = Array.from(randomBytes(16));
+  const idempotencyRecord = owner.findIdempotencyRecord(idempotencyKey);
+
+  function createWithKey(index: number, options: CreateOptions = {}) {
+    return owner.createEntry(index, "Retried", "Sent over a flaky connection", { idempotencyKey, ...options });
+  }
+
+  before(async () => {
+    await owner.initialize();
+  });
+
+  it("Creates the entry once and ignores retries", async () => {
+    await createWithKey(0);
+    // One retry built from the same counter read, one from a re-fetched counter
+    await createWithKey(0);
+    await createWithKey(1);
+
+    const counter = await program.account.userJournalCounter.fetch(owner.userJournalCounter);
+    expect(counter.lastEntryIndex.toNumber()).to.equal(1);
+    expect(await provider.connection.getAccountInfo(owner.findEntry(1))).to.be.null;
+
+    const record = await program.account.idempotencyRecord.fetch(idempotencyRecord);
+    expect(record.entryIndex.toNumber()).to.equal(0);
+  });
+
+  it("Treats a retry as done even when it would duplicate the previous entry", async () => {
+    // The retry of entry 0, built from a re-fetched counter, has the same content as entry 0
+    await createWithKey(1, { previousEntry: owner.findEntry(0) });
+
+    const counter = await program.account.userJournalCounter.fetch(owner.userJournalCounter);
+    expect(counter.lastEntryIndex.toNumber()).to.equal(1);
+  });
+
+  it("Requires the record account along with the key", async () => {
+    try {
+      await createWithKey(1, { idempotencyRecord: null });
+      expect.fail("the create without the record account should have failed");
+    } catch (err) {
+      expect(err).to.be.instanceOf(anchor.AnchorError);
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("IdempotencyKeyMismatch");
+    }
+  });
+});
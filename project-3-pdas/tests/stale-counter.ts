@@ -0,0 +1,36 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import { NewCrudApp } from "../target/types/new_crud_app";
+import { JournalOwner } from "./helpers";
+
+// Two devices read the same counter and both try to create entry 0; the second one must get StaleCounter.
+describe("create_journal_entry with a stale counter", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.newCrudApp as Program<NewCrudApp>;
+  const owner = new JournalOwner(program);
+
+  function createAtIndexZero(title: string) {
+    return owner.createEntry(0, title, "Written while offline");
+  }
+
+  before(async () => {
+    await owner.initialize();
+  });
+
+  it("Rejects the second create with StaleCounter", async () => {
+    await createAtIndexZero("From my phone");
+
+    try {
+      await createAtIndexZero("From my laptop");
+      expect.fail("the stale create should have failed");
+    } catch (err) {
+      expect(err).to.be.instanceOf(anchor.AnchorError);
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("StaleCounter");
+    }
+    const counter = await program.account.userJournalCounter.fetch(owner.userJournalCounter);
+    expect(counter.lastEntryIndex.toNumber()).to.equal(1);
+  });
+});